@@ -0,0 +1,505 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fs, io,
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use miette::Diagnostic;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::{
+    generator::{
+        header::{hash, Source},
+        GeneratedFile,
+    },
+    schema::{
+        graph::with_referrers,
+        info::raw::{RawSchemaInfo, RawSchemaInfoError},
+        info::SchemaInfo,
+        module_path::ModulePath,
+        registry::{RawSchemaEntry, Registry},
+        root::SchemaRoot,
+    },
+};
+
+/// File in `desergen_root` the cache is kept in
+pub const CACHE_FILE: &str = ".desergen-cache.json";
+
+/// What a generation was made from and what it made, so the next one
+/// can skip the schemas that wouldn't come out any different
+#[derive(Default, Serialize, Deserialize)]
+pub struct Cache {
+    /// Version of desergen that wrote the cache, any other one regenerates everything
+    version: String,
+    /// Hash of the config file and the templates
+    config: String,
+    /// By schema file
+    schemas: BTreeMap<PathBuf, SchemaEntry>,
+    /// Hashes of the generated files that don't belong to a single schema, like support files
+    shared: BTreeMap<PathBuf, String>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct SchemaEntry {
+    hash: String,
+    /// Contents of the schema file, so it doesn't need to be parsed again while its hash is the same
+    raw: RawSchemaInfo,
+    /// Schema files it references
+    references: Vec<PathBuf>,
+    /// Hashes of the files generated from it
+    outputs: BTreeMap<PathBuf, String>,
+}
+
+/// Everything the output depends on, as of now
+pub struct Inputs {
+    config: String,
+    /// By schema file
+    schemas: BTreeMap<PathBuf, SchemaInput>,
+}
+
+struct SchemaInput {
+    root: Option<String>,
+    mod_path: ModulePath,
+    /// Modification time of the schema file in milliseconds since the Unix epoch
+    modified: u128,
+    hash: String,
+    raw: RawSchemaInfo,
+}
+
+impl Inputs {
+    /// Fingerprints the config and reads the selected schema files, only parsing
+    /// the ones whose contents changed since they were cached
+    pub fn read(
+        config_path: &Path,
+        templates_dir: Option<&Path>,
+        schema_roots: &[SchemaRoot],
+        cache: &Cache,
+    ) -> CacheResult<Self> {
+        let mut config = read(config_path)?;
+
+        if let Some(templates_dir) = templates_dir {
+            let mut templates = fs::read_dir(templates_dir)
+                .and_then(|entries| {
+                    entries
+                        .map(|entry| entry.map(|entry| entry.path()))
+                        .collect::<io::Result<Vec<_>>>()
+                })
+                .map_err(|err| CacheError::IO(err, templates_dir.into()))?;
+            templates.sort();
+
+            for path in templates.iter().filter(|path| path.is_file()) {
+                config.extend(path.to_string_lossy().as_bytes());
+                config.extend(read(path)?);
+            }
+        }
+
+        let mut schemas = BTreeMap::new();
+
+        for SchemaRoot {
+            name,
+            dir,
+            schemas: mod_paths,
+        } in schema_roots
+        {
+            for mod_path in mod_paths {
+                let (format, path) = RawSchemaInfo::find(dir, mod_path.clone())?;
+                let modified = fs::metadata(&path)
+                    .and_then(|meta| meta.modified())
+                    .map_err(|err| CacheError::IO(err, path.clone()))?
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_millis();
+
+                let contents =
+                    fs::read_to_string(&path).map_err(|err| CacheError::IO(err, path.clone()))?;
+                let hash = hash(&contents);
+                let raw = match cache.schemas.get(&path) {
+                    Some(entry) if entry.hash == hash => entry.raw.clone(),
+                    _ => {
+                        tracing::debug!("Parsing {path:?}");
+                        format.parse(&contents, &path)?
+                    }
+                };
+
+                schemas.insert(
+                    path,
+                    SchemaInput {
+                        root: name.clone(),
+                        mod_path: mod_path.clone(),
+                        modified,
+                        hash,
+                        raw,
+                    },
+                );
+            }
+        }
+
+        Ok(Self {
+            config: hash(config),
+            schemas,
+        })
+    }
+
+    /// The schema files as read, to build the registry from
+    pub fn raw_schemas(&self) -> Vec<RawSchemaEntry> {
+        self.schemas
+            .iter()
+            .map(|(path, input)| RawSchemaEntry {
+                root: input.root.clone(),
+                mod_path: input.mod_path.clone(),
+                schema_info: input.raw.clone(),
+                path: path.clone(),
+                last_updated: input.modified,
+            })
+            .collect()
+    }
+}
+
+impl Cache {
+    /// The cache of the last generation, an empty one when there is none or it can't be read
+    pub fn load(desergen_root: &Path) -> Self {
+        let path = desergen_root.join(CACHE_FILE);
+
+        match fs::read(&path) {
+            Ok(contents) => serde_json::from_slice(&contents).unwrap_or_else(|err| {
+                tracing::warn!("Ignoring the unreadable cache {path:?}: {err}");
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn save(&self, desergen_root: &Path) -> CacheResult<()> {
+        let path = desergen_root.join(CACHE_FILE);
+        let contents = serde_json::to_string_pretty(self).expect("caches always serialize");
+
+        fs::create_dir_all(desergen_root)
+            .map_err(|err| CacheError::IO(err, desergen_root.into()))?;
+        fs::write(&path, contents + "\n").map_err(|err| CacheError::IO(err, path))
+    }
+
+    /// Whether the inputs are the cached ones and every cached output is still there as it was written
+    pub fn is_fresh(&self, inputs: &Inputs) -> bool {
+        self.is_valid_for(inputs)
+            && self.schemas.len() == inputs.schemas.len()
+            && inputs.schemas.iter().all(|(path, input)| {
+                self.schemas
+                    .get(path)
+                    .is_some_and(|entry| entry.hash == input.hash && outputs_intact(&entry.outputs))
+            })
+            && outputs_intact(&self.shared)
+    }
+
+    /// Whether the cache was written by this version of desergen for the same config and templates
+    fn is_valid_for(&self, inputs: &Inputs) -> bool {
+        self.version == env!("CARGO_PKG_VERSION") && self.config == inputs.config
+    }
+
+    /// Schemas to regenerate: the changed or new ones, the ones with missing or edited outputs
+    /// and every schema depending on one of them, `None` when all of them need to be
+    pub fn stale(&self, inputs: &Inputs, registry: &Registry) -> Option<HashSet<Uuid>> {
+        if !self.is_valid_for(inputs) {
+            return None;
+        }
+
+        let stale = registry
+            .entries()
+            .filter(|(_, schema_info)| {
+                let entry = self.schemas.get(&schema_info.path);
+                let hash = inputs
+                    .schemas
+                    .get(&schema_info.path)
+                    .map(|input| &input.hash);
+
+                !entry.is_some_and(|entry| {
+                    Some(&entry.hash) == hash
+                        && entry.references == references(registry, schema_info)
+                        && outputs_intact(&entry.outputs)
+                })
+            })
+            .map(|(id, _)| *id)
+            .collect::<HashSet<_>>();
+
+        Some(with_referrers(registry, stale))
+    }
+
+    /// The cache after generating `files`, keeping the outputs of the schemas that weren't regenerated
+    pub fn update(&self, inputs: &Inputs, registry: &Registry, files: &[GeneratedFile]) -> Self {
+        let mut outputs = HashMap::<&PathBuf, BTreeMap<PathBuf, String>>::new();
+        let mut shared = BTreeMap::new();
+
+        for GeneratedFile {
+            path,
+            contents,
+            source,
+        } in files
+        {
+            match source {
+                Some(Source {
+                    path: schema_path, ..
+                }) => outputs.entry(schema_path).or_default(),
+                None => &mut shared,
+            }
+            .insert(path.clone(), hash(contents));
+        }
+
+        let schemas = registry
+            .schemas()
+            .filter_map(|schema_info| {
+                let SchemaInput { hash, raw, .. } = inputs.schemas.get(&schema_info.path)?;
+                let references = references(registry, schema_info);
+                let outputs = match outputs.remove(&schema_info.path) {
+                    Some(outputs) => outputs,
+                    None => self.schemas.get(&schema_info.path)?.outputs.clone(),
+                };
+
+                Some((
+                    schema_info.path.clone(),
+                    SchemaEntry {
+                        hash: hash.clone(),
+                        raw: raw.clone(),
+                        references,
+                        outputs,
+                    },
+                ))
+            })
+            .collect();
+
+        Self {
+            version: env!("CARGO_PKG_VERSION").into(),
+            config: inputs.config.clone(),
+            schemas,
+            shared,
+        }
+    }
+}
+
+/// Files of the schemas a schema references, which resolve to other files when schemas are moved
+fn references(registry: &Registry, schema_info: &SchemaInfo) -> Vec<PathBuf> {
+    schema_info
+        .schema
+        .references()
+        .iter()
+        .filter_map(|id| registry.get(id).ok())
+        .map(|reference| reference.path.clone())
+        .collect()
+}
+
+/// Whether every file still has the hash it was written with
+fn outputs_intact(outputs: &BTreeMap<PathBuf, String>) -> bool {
+    outputs
+        .iter()
+        .all(|(path, expected)| fs::read(path).is_ok_and(|contents| hash(contents) == *expected))
+}
+
+fn read(path: &Path) -> CacheResult<Vec<u8>> {
+    fs::read(path).map_err(|err| CacheError::IO(err, path.into()))
+}
+
+pub type CacheResult<T> = Result<T, CacheError>;
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Error, Diagnostic)]
+pub enum CacheError {
+    #[error("[Cache] [IO ({1:?})] {0}")]
+    IO(io::Error, PathBuf),
+    #[error("[Cache] {0}")]
+    RawSchemaInfo(#[from] RawSchemaInfoError),
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs::File, time::Duration};
+
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::{schema::info::SchemaInfoDefaults, test_utils::temp_dir};
+
+    /// `a` references `b` which references `c`, `d` stands alone
+    const SCHEMAS: [(&str, &str); 4] = [
+        ("a", r#"(schema: Class({ "b": DefClass("b") }))"#),
+        ("b", r#"(schema: Class({ "c": DefClass("c") }))"#),
+        ("c", r#"(schema: Class({ "value": Num }))"#),
+        ("d", r#"(schema: Class({ "value": Str }))"#),
+    ];
+
+    struct Project {
+        dir: TempDir,
+        roots: Vec<SchemaRoot>,
+    }
+
+    impl Project {
+        fn new() -> Self {
+            let dir = temp_dir();
+            fs::create_dir_all(dir.path().join("schemas")).unwrap();
+            fs::create_dir_all(dir.path().join("out")).unwrap();
+            fs::write(dir.path().join("desergen.toml"), "").unwrap();
+
+            for (name, contents) in SCHEMAS {
+                fs::write(dir.path().join(format!("schemas/{name}.ron")), contents).unwrap();
+            }
+
+            let roots = vec![SchemaRoot {
+                name: None,
+                dir: dir.path().join("schemas"),
+                schemas: SCHEMAS
+                    .iter()
+                    .map(|(name, _)| ModulePath::single(*name))
+                    .collect(),
+            }];
+
+            Self { dir, roots }
+        }
+
+        fn inputs(&self, cache: &Cache) -> Inputs {
+            Inputs::read(
+                &self.dir.path().join("desergen.toml"),
+                None,
+                &self.roots,
+                cache,
+            )
+            .unwrap()
+        }
+
+        fn registry(inputs: &Inputs) -> Registry {
+            let mut registry = Registry::default();
+            registry
+                .process_raw_schemas(inputs.raw_schemas(), &SchemaInfoDefaults::default())
+                .unwrap();
+            registry
+        }
+
+        /// Generates one file per schema and caches them
+        fn generate(&self) -> Cache {
+            let inputs = self.inputs(&Cache::default());
+            let registry = Self::registry(&inputs);
+            let files = registry
+                .schemas()
+                .map(|schema_info| GeneratedFile {
+                    path: self.output(&schema_info.mod_path.to_string()),
+                    contents: schema_info.name.clone(),
+                    source: Some(Source {
+                        path: schema_info.path.clone(),
+                        mod_path: schema_info.mod_path.clone(),
+                    }),
+                })
+                .collect::<Vec<_>>();
+
+            for file in &files {
+                fs::write(&file.path, &file.contents).unwrap();
+            }
+
+            Cache::default().update(&inputs, &registry, &files)
+        }
+
+        fn output(&self, name: &str) -> PathBuf {
+            self.dir.path().join(format!("out/{name}.ts"))
+        }
+
+        /// Rewrites a schema file, moving its modification time
+        fn edit(&self, name: &str, contents: &str) {
+            let path = self.dir.path().join(format!("schemas/{name}.ron"));
+            fs::write(&path, contents).unwrap();
+            File::options()
+                .write(true)
+                .open(&path)
+                .unwrap()
+                .set_modified(UNIX_EPOCH + Duration::from_secs(1))
+                .unwrap();
+        }
+
+        /// Names of the stale schemas, `None` when everything is
+        fn stale(&self, cache: &Cache) -> Option<Vec<String>> {
+            let inputs = self.inputs(cache);
+            let registry = Self::registry(&inputs);
+
+            cache.stale(&inputs, &registry).map(|stale| {
+                let mut names = stale
+                    .iter()
+                    .map(|id| registry.get(id).unwrap().mod_path.to_string())
+                    .collect::<Vec<_>>();
+                names.sort();
+                names
+            })
+        }
+    }
+
+    #[test]
+    fn fresh() {
+        let project = Project::new();
+        let cache = project.generate();
+
+        assert!(cache.is_fresh(&project.inputs(&cache)));
+        assert_eq!(project.stale(&cache), Some(vec![]));
+    }
+
+    #[test]
+    fn changed_schema_and_transitive_referrers() {
+        let project = Project::new();
+        let cache = project.generate();
+        project.edit("c", r#"(schema: Class({ "value": Str }))"#);
+
+        assert!(!cache.is_fresh(&project.inputs(&cache)));
+        assert_eq!(
+            project.stale(&cache),
+            Some(vec!["a".into(), "b".into(), "c".into()])
+        );
+    }
+
+    #[test]
+    fn edited_output() {
+        let project = Project::new();
+        let cache = project.generate();
+        fs::write(project.output("b"), "edited").unwrap();
+
+        assert_eq!(project.stale(&cache), Some(vec!["a".into(), "b".into()]));
+    }
+
+    #[test]
+    fn changed_references() {
+        let project = Project::new();
+        let cache = project.generate();
+        project.edit("d", r#"(schema: Class({ "c": DefClass("c") }))"#);
+
+        assert_eq!(project.stale(&cache), Some(vec!["d".into()]));
+    }
+
+    #[test]
+    fn changed_config() {
+        let project = Project::new();
+        let cache = project.generate();
+        fs::write(project.dir.path().join("desergen.toml"), "# changed").unwrap();
+
+        assert_eq!(project.stale(&cache), None);
+    }
+
+    #[test]
+    fn touched_schema() {
+        let project = Project::new();
+        let cache = project.generate();
+        project.edit("d", SCHEMAS[3].1);
+
+        assert_eq!(project.stale(&cache), Some(vec![]));
+    }
+
+    #[test]
+    fn edit_keeping_modification_time() {
+        let project = Project::new();
+        let cache = project.generate();
+        let path = project.dir.path().join("schemas/d.ron");
+        let modified = fs::metadata(&path).unwrap().modified().unwrap();
+        fs::write(&path, r#"(schema: Class({ "value": Num }))"#).unwrap();
+        File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+
+        assert_eq!(project.stale(&cache), Some(vec!["d".into()]));
+    }
+}
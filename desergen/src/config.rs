@@ -1,10 +1,17 @@
-use std::path::{Path, PathBuf};
+use std::{
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
+use indexmap::IndexMap;
 use miette::Diagnostic;
-use serde::Deserialize;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use thiserror::Error;
 
-use crate::schema::module_path::ModulePath;
+use crate::{
+    generator::{rust::RustOptions, ts::printer::TsStyle},
+    schema::{info::SchemaInfoDefaults, module_path::ModulePath},
+};
 
 pub type ConfigResult<T> = Result<T, ConfigError>;
 
@@ -26,7 +33,108 @@ pub struct Config {
     pub desergen_root: PathBuf,
     #[serde(default = "Config::default_src_output_root")]
     pub src_output_root: PathBuf,
+    /// Schemas to process, relative to `<desergen_root>/schemas`
+    #[serde(default)]
     pub schemas: Vec<ModulePath>,
+    /// Globs over schema file paths to pick up without listing them in `schemas`
+    #[serde(default)]
+    pub include: Vec<String>,
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    /// Additional schema roots by name, referenced from each other as `<name>::path::to::schema`
+    #[serde(default)]
+    pub roots: IndexMap<String, RootConfig>,
+    /// Kinds of code to generate, `ts` classes, `zod` schemas, `rust` structs or `python` dataclasses
+    #[serde(default = "Config::default_targets")]
+    pub targets: Vec<TargetConfig>,
+    #[serde(default)]
+    pub rust: RustOptions,
+    /// Formatting of the `ts` target
+    #[serde(default)]
+    pub ts: TsStyle,
+    /// Directory of templates overriding the built-in ones of the `ts` target, relative to the config file
+    pub templates: Option<PathBuf>,
+    /// External generators by the target name they're selected with
+    #[serde(default)]
+    pub plugins: IndexMap<String, PluginConfig>,
+    #[serde(flatten)]
+    pub schema_defaults: SchemaInfoDefaults,
+}
+
+/// Output backend, either by name alone or with where its files go
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub enum TargetConfig {
+    Name(String),
+    Table {
+        name: String,
+        /// Directory relative to each root's output directory
+        #[serde(default)]
+        output_subdir: Option<PathBuf>,
+    },
+}
+
+impl TargetConfig {
+    pub fn name(&self) -> &str {
+        match self {
+            TargetConfig::Name(name) | TargetConfig::Table { name, .. } => name,
+        }
+    }
+
+    pub fn output_subdir(&self) -> Option<&Path> {
+        match self {
+            TargetConfig::Name(_) => None,
+            TargetConfig::Table { output_subdir, .. } => output_subdir.as_deref(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct PluginConfig {
+    /// Executable, looked up on `PATH` when it's a bare name, otherwise relative to the config file
+    pub command: PathBuf,
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+impl PluginConfig {
+    pub fn command(&self, config_root_dir: &Path) -> PathBuf {
+        match self.command.components().count() {
+            1 => self.command.clone(),
+            _ => config_root_dir.join(&self.command),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct RootConfig {
+    /// Directory of the root's schema files, relative to the config file
+    pub schemas_dir: PathBuf,
+    /// Directory the root's generated code is written to, relative to the config file
+    pub output_dir: PathBuf,
+    #[serde(default)]
+    pub schemas: Vec<ModulePath>,
+    #[serde(default)]
+    pub include: Vec<String>,
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
+/// Schema root with its directories resolved against the config file's directory
+#[derive(Debug, Clone)]
+pub struct ResolvedRoot {
+    pub name: Option<String>,
+    pub schemas_dir: PathBuf,
+    pub output_dir: PathBuf,
+    pub schemas: Vec<ModulePath>,
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+}
+
+impl ResolvedRoot {
+    pub fn selects_schemas(&self) -> bool {
+        !self.schemas.is_empty() || !self.include.is_empty()
+    }
 }
 
 impl Config {
@@ -37,17 +145,112 @@ impl Config {
             return Err(ConfigError::DoesNotExist(path.into()));
         }
 
-        let ext = match path.extension().and_then(|ext| ext.to_str()) {
-            Some(ext) => ConfigFormat::try_from(ext)?,
-            None => return Err(ConfigError::NoFileExtension(ConfigFormat::ALL)),
-        };
         let config_str = std::fs::read_to_string(path)?;
 
-        Ok(match ext {
-            ConfigFormat::TOML => toml::from_str(&config_str)?,
-            ConfigFormat::JSON => serde_json::from_str(&config_str)?,
-            ConfigFormat::YAML => serde_yaml::from_str(&config_str)?,
-        })
+        Ok(ConfigFormat::of(path)?.deserialize(&config_str)?)
+    }
+
+    /// Adds a schema to the `schemas` of the unnamed root or of a named one in the config file,
+    /// keeping the formatting and comments of TOML files
+    pub fn add_schema(
+        path: impl AsRef<Path>,
+        root: Option<&String>,
+        mod_path: &ModulePath,
+    ) -> ConfigResult<()> {
+        let path = path.as_ref();
+        let config_str = std::fs::read_to_string(path)?;
+        let mod_path = mod_path.to_string();
+
+        let config_str = match ConfigFormat::of(path)? {
+            ConfigFormat::TOML => {
+                let mut doc = config_str.parse::<toml_edit::DocumentMut>()?;
+                let table = match root {
+                    Some(root) => &mut doc["roots"][root.as_str()],
+                    None => doc.as_item_mut(),
+                };
+                let schemas = table["schemas"]
+                    .or_insert(toml_edit::value(toml_edit::Array::new()))
+                    .as_array_mut()
+                    .ok_or(ConfigError::InvalidSchemas)?;
+
+                if !schemas
+                    .iter()
+                    .any(|schema| schema.as_str() == Some(&mod_path))
+                {
+                    schemas.push(mod_path);
+                }
+
+                doc.to_string()
+            }
+            ConfigFormat::JSON => {
+                let mut value = serde_json::from_str::<serde_json::Value>(&config_str)?;
+                push_schema(&mut value, root, mod_path)?;
+
+                serde_json::to_string_pretty(&value)? + "\n"
+            }
+            ConfigFormat::YAML => {
+                let mut value = serde_yaml::from_str::<serde_json::Value>(&config_str)?;
+                push_schema(&mut value, root, mod_path)?;
+
+                serde_yaml::to_string(&value)?
+            }
+        };
+
+        Ok(std::fs::write(path, config_str)?)
+    }
+
+    /// `desergen.<ext>` in `dir` in the first format it exists in, `desergen.toml` when there is none
+    pub fn default_path(dir: &Path) -> PathBuf {
+        ConfigFormat::ALL
+            .iter()
+            .map(|format| dir.join(format!("desergen.{}", format.extension())))
+            .find(|path| path.exists())
+            .unwrap_or(dir.join("desergen.toml"))
+    }
+
+    pub fn templates_dir(&self, config_root_dir: &Path) -> Option<PathBuf> {
+        self.templates
+            .as_ref()
+            .map(|templates| config_root_dir.join(templates))
+    }
+
+    /// The unnamed root from `desergen_root` and `src_output_root`, followed by the named ones
+    pub fn roots(&self, config_root_dir: &Path) -> ConfigResult<Vec<ResolvedRoot>> {
+        let unnamed = ResolvedRoot {
+            name: None,
+            schemas_dir: config_root_dir.join(&self.desergen_root).join("schemas"),
+            output_dir: config_root_dir
+                .join(&self.src_root)
+                .join(&self.src_output_root),
+            schemas: self.schemas.clone(),
+            include: self.include.clone(),
+            exclude: self.exclude.clone(),
+        };
+
+        let named = self.roots.iter().map(|(name, root)| {
+            if name.is_empty() || name.contains("::") {
+                return Err(ConfigError::InvalidRootName(name.clone()));
+            }
+
+            let RootConfig {
+                schemas_dir,
+                output_dir,
+                schemas,
+                include,
+                exclude,
+            } = root;
+
+            Ok(ResolvedRoot {
+                name: Some(name.clone()),
+                schemas_dir: config_root_dir.join(schemas_dir),
+                output_dir: config_root_dir.join(output_dir),
+                schemas: schemas.clone(),
+                include: include.clone(),
+                exclude: exclude.clone(),
+            })
+        });
+
+        [Ok(unnamed)].into_iter().chain(named).collect()
     }
 
     default_path_funcs![
@@ -55,10 +258,76 @@ impl Config {
         desergen_root = "desergen",
         src_output_root = "desergen",
     ];
+
+    fn default_targets() -> Vec<TargetConfig> {
+        vec![TargetConfig::Name("ts".into())]
+    }
+}
+
+/// Adds a schema to the `schemas` of a parsed JSON or YAML config unless it's already there
+fn push_schema(
+    config: &mut serde_json::Value,
+    root: Option<&String>,
+    mod_path: String,
+) -> ConfigResult<()> {
+    let table = match root {
+        Some(root) => config.pointer_mut(&format!("/roots/{root}")),
+        None => Some(config),
+    }
+    .and_then(|table| table.as_object_mut())
+    .ok_or(ConfigError::InvalidSchemas)?;
+
+    let schemas = table
+        .entry("schemas")
+        .or_insert_with(|| serde_json::Value::Array(vec![]))
+        .as_array_mut()
+        .ok_or(ConfigError::InvalidSchemas)?;
+
+    if !schemas
+        .iter()
+        .any(|schema| schema.as_str() == Some(&mod_path))
+    {
+        schemas.push(mod_path.into());
+    }
+
+    Ok(())
+}
+
+/// Config written by `desergen init`, the defaults spelled out to show where things go
+#[derive(Serialize)]
+pub struct InitConfig {
+    pub src_root: PathBuf,
+    pub desergen_root: PathBuf,
+    pub src_output_root: PathBuf,
+    pub schemas: Vec<ModulePath>,
+    pub targets: Vec<String>,
+}
+
+impl Default for InitConfig {
+    fn default() -> Self {
+        Self {
+            src_root: Config::default_src_root(),
+            desergen_root: Config::default_desergen_root(),
+            src_output_root: Config::default_src_output_root(),
+            schemas: vec![],
+            targets: vec!["ts".into()],
+        }
+    }
+}
+
+impl InitConfig {
+    pub fn contents(&self, format: ConfigFormat) -> ConfigResult<String> {
+        Ok(match format {
+            ConfigFormat::TOML => toml::to_string(self)?,
+            ConfigFormat::JSON => serde_json::to_string_pretty(self)? + "\n",
+            ConfigFormat::YAML => serde_yaml::to_string(self)?,
+        })
+    }
 }
 
+/// Formats the config file can be written in, which schema files can be written in as well
 #[allow(clippy::upper_case_acronyms)]
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ConfigFormat {
     TOML,
     JSON,
@@ -69,28 +338,71 @@ pub type AllConfigFormats = [ConfigFormat; 3];
 
 impl ConfigFormat {
     const ALL: AllConfigFormats = [ConfigFormat::TOML, ConfigFormat::JSON, ConfigFormat::YAML];
-}
 
-impl<'a> TryFrom<&'a str> for ConfigFormat {
-    type Error = ConfigError;
+    /// Format of a config file by its extension
+    pub fn of(path: &Path) -> ConfigResult<Self> {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) => ext.parse(),
+            None => Err(ConfigError::NoFileExtension(Self::ALL)),
+        }
+    }
 
-    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
-        Ok(match value {
-            "toml" | "TOML" => Self::TOML,
-            "json" | "JSON" => Self::JSON,
-            "yaml" | "YAML" => Self::YAML,
-            ext => return Err(ConfigError::WrongFormat(ext.into(), Self::ALL)),
+    pub fn extensions(&self) -> &'static [&'static str] {
+        match self {
+            ConfigFormat::TOML => &["toml"],
+            ConfigFormat::JSON => &["json"],
+            ConfigFormat::YAML => &["yaml", "yml"],
+        }
+    }
+
+    /// Extension files in this format are created with
+    pub fn extension(&self) -> &'static str {
+        self.extensions()[0]
+    }
+
+    pub fn deserialize<T: DeserializeOwned>(&self, contents: &str) -> Result<T, FormatError> {
+        Ok(match self {
+            ConfigFormat::TOML => toml::from_str(contents)?,
+            ConfigFormat::JSON => serde_json::from_str(contents)?,
+            ConfigFormat::YAML => serde_yaml::from_str(contents)?,
         })
     }
 }
 
+impl FromStr for ConfigFormat {
+    type Err = ConfigError;
+
+    fn from_str(ext: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|format| format.extensions().contains(&ext.to_lowercase().as_str()))
+            .ok_or(ConfigError::WrongFormat(ext.into(), Self::ALL))
+    }
+}
+
+/// Failure to deserialize a file in one of the config formats
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Error, Diagnostic)]
+pub enum FormatError {
+    #[error("[TOML] {0}")]
+    TOML(#[from] toml::de::Error),
+    #[error("[JSON] {0}")]
+    JSON(#[from] serde_json::Error),
+    #[error("[YAML] {0}")]
+    YAML(#[from] serde_yaml::Error),
+}
+
 #[allow(clippy::upper_case_acronyms)]
 #[derive(Debug, Error, Diagnostic)]
 pub enum ConfigError {
     #[error("[Config] {0}")]
     IO(#[from] std::io::Error),
     #[error("[Config] {0}")]
-    TOML(#[from] toml::de::Error),
+    Format(#[from] FormatError),
+    #[error("[Config] {0}")]
+    TOMLSer(#[from] toml::ser::Error),
+    #[error("[Config] {0}")]
+    TOMLEdit(#[from] toml_edit::TomlError),
     #[error("[Config] {0}")]
     JSON(#[from] serde_json::Error),
     #[error("[Config] {0}")]
@@ -101,4 +413,99 @@ pub enum ConfigError {
     NoFileExtension(AllConfigFormats),
     #[error("[Config] Wrong Format {0}, Available ones: {1:?}")]
     WrongFormat(String, AllConfigFormats),
+    #[error(
+        "[Config] '{0}' can't be used as a root name, it has to be a single module path component"
+    )]
+    InvalidRootName(String),
+    #[error("[Config] `schemas` has to be a list of module paths")]
+    InvalidSchemas,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::temp_dir;
+
+    fn config(contents: &str) -> Config {
+        ConfigFormat::TOML.deserialize(contents).unwrap()
+    }
+
+    #[test]
+    fn roots() {
+        let roots = config(
+            r#"
+            schemas = ["order"]
+
+            [roots.shared]
+            schemas_dir = "shared/schemas"
+            output_dir = "shared/src"
+            include = ["**"]
+            "#,
+        )
+        .roots(Path::new("project"))
+        .unwrap();
+
+        let [unnamed, shared] = &roots[..] else {
+            panic!("expected the unnamed root and `shared`, got {roots:?}");
+        };
+        assert_eq!(unnamed.name, None);
+        assert_eq!(unnamed.schemas_dir, Path::new("project/desergen/schemas"));
+        assert_eq!(unnamed.output_dir, Path::new("project/src/desergen"));
+        assert_eq!(shared.name.as_deref(), Some("shared"));
+        assert_eq!(shared.schemas_dir, Path::new("project/shared/schemas"));
+        assert_eq!(shared.output_dir, Path::new("project/shared/src"));
+        assert!(shared.selects_schemas());
+    }
+
+    #[test]
+    fn invalid_root_names() {
+        for name in ["\"\"", "\"a::b\""] {
+            let roots = config(&format!(
+                "[roots.{name}]\nschemas_dir = \"a\"\noutput_dir = \"b\"\n"
+            ))
+            .roots(Path::new(""));
+
+            assert!(
+                matches!(roots, Err(ConfigError::InvalidRootName(_))),
+                "{name}"
+            );
+        }
+    }
+
+    #[test]
+    fn adds_schemas() {
+        let dir = temp_dir();
+        let order = "api::order".parse::<ModulePath>().unwrap();
+        let shared = "shared".to_string();
+
+        for (format, contents) in [
+            (
+                ConfigFormat::TOML,
+                "# Listed by hand\nschemas = [\"a\"]\n\n[roots.shared]\nschemas_dir = \"s\"\noutput_dir = \"o\"\n",
+            ),
+            (
+                ConfigFormat::JSON,
+                r#"{ "schemas": ["a"], "roots": { "shared": { "schemas_dir": "s", "output_dir": "o" } } }"#,
+            ),
+            (
+                ConfigFormat::YAML,
+                "schemas: [a]\nroots:\n  shared:\n    schemas_dir: s\n    output_dir: o\n",
+            ),
+        ] {
+            let path = dir.path().join(format!("desergen.{}", format.extension()));
+            std::fs::write(&path, contents).unwrap();
+
+            Config::add_schema(&path, None, &order).unwrap();
+            Config::add_schema(&path, None, &order).unwrap();
+            Config::add_schema(&path, Some(&shared), &order).unwrap();
+
+            let config = Config::from_path(&path).unwrap();
+            let mod_paths = |schemas: &[ModulePath]| schemas.iter().map(ModulePath::to_string).collect::<Vec<_>>();
+            assert_eq!(mod_paths(&config.schemas), ["a", "api::order"], "{format:?}");
+            assert_eq!(mod_paths(&config.roots["shared"].schemas), ["api::order"], "{format:?}");
+        }
+
+        let toml = std::fs::read_to_string(dir.path().join("desergen.toml")).unwrap();
+        assert!(toml.starts_with("# Listed by hand\n"));
+    }
 }
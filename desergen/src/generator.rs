@@ -1,82 +1,512 @@
-pub mod class;
-pub mod enum_;
-pub mod str;
+pub mod backend;
+pub mod changes;
+pub mod header;
+pub mod manifest;
+pub mod plugin;
+pub mod python;
+pub mod rust;
+pub mod ts;
+pub mod zod;
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fs, io,
+    path::{Component, Path, PathBuf},
+};
 
 use miette::Diagnostic;
 use thiserror::Error;
+use uuid::Uuid;
 
 use crate::schema::{
-    info::SchemaInfo,
-    module_path::ModulePath,
-    registry::Registry,
-    validation_info::{
-        ClassSchemaValidationInfo, EnumSchemaValidationInfo, SchemaValidationDefaults,
-        SchemaValidationInfo,
+    graph::components, info::SchemaInfo, module_path::ModulePath, registry::Registry, EnumSchema,
+    Schema,
+};
+
+use self::{
+    backend::{Backend, Imports, SchemaContext},
+    header::Source,
+    plugin::PluginError,
+    python::{PythonBackend, PythonGeneratorError},
+    rust::{RustBackend, RustGeneratorError, RustOptions},
+    ts::{
+        class::ClassGeneratorError, enum_::EnumGeneratorError, printer::TsStyle,
+        template::TemplateError, TsBackend,
     },
-    ClassSchema, EnumSchema,
+    zod::{ZodBackend, ZodGeneratorError},
 };
 
-use self::enum_::{generate_enum, EnumGeneratorError};
+/// Module with the helpers shared by all generated modules of an output directory
+pub const RUNTIME_MODULE: &str = "_runtime";
 
-pub struct ClassSchemaGenInfo<'a> {
-    name: &'a String,
-    file_name: &'a String,
-    mod_path: &'a String,
-    class_schema: &'a ClassSchema,
-    validation: &'a Option<ClassSchemaValidationInfo<'a>>,
+#[derive(Debug)]
+pub struct GeneratedFile {
+    /// Path the file is written to
+    pub path: PathBuf,
+    pub contents: String,
+    /// Schema the file was generated from, `None` for support files
+    pub source: Option<Source>,
 }
 
-pub struct EnumSchemaGenInfo<'a> {
-    name: &'a String,
-    file_name: &'a String,
-    mod_path: &'a ModulePath,
-    enum_schema: &'a EnumSchema,
-    validation: &'a Option<EnumSchemaValidationInfo<'a>>,
+/// Directories the generated code of every schema root goes to
+#[derive(Debug, Default, Clone)]
+pub struct OutputRoots(HashMap<Option<String>, PathBuf>);
+
+impl OutputRoots {
+    pub fn dir(&self, root: Option<&String>) -> Option<&PathBuf> {
+        self.0.get(&root.cloned())
+    }
+
+    /// Path of a schema's generated module, without an extension
+    pub fn module(&self, schema_info: &SchemaInfo) -> Option<PathBuf> {
+        self.dir(schema_info.root.as_ref())
+            .map(|dir| normalize(&dir.join(module(schema_info))))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Option<&String>, &PathBuf)> {
+        self.0.iter().map(|(root, dir)| (root.as_ref(), dir))
+    }
+
+    pub fn runtime_module(&self, root: Option<&String>) -> Option<PathBuf> {
+        self.dir(root)
+            .map(|dir| normalize(&dir.join(RUNTIME_MODULE)))
+    }
+
+    /// The same roots with every directory moved into `subdir`
+    pub fn join(&self, subdir: &Path) -> Self {
+        self.0
+            .iter()
+            .map(|(root, dir)| (root.clone(), dir.join(subdir)))
+            .collect()
+    }
 }
 
-pub fn generate(registry: &Registry) -> GeneratorResult<()> {
-    for SchemaInfo {
-        name,
-        file_name,
-        mod_path,
-        schema,
-        validation,
-        ..
-    } in registry.schemas()
+impl FromIterator<(Option<String>, PathBuf)> for OutputRoots {
+    fn from_iter<T: IntoIterator<Item = (Option<String>, PathBuf)>>(iter: T) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+/// Names of the built-in backends, as used in `Config::targets`
+pub const BACKENDS: [&str; 4] = ["ts", "zod", "rust", "python"];
+
+pub fn backend(
+    name: &str,
+    rust_options: &RustOptions,
+    ts_style: TsStyle,
+    templates_dir: Option<&Path>,
+) -> GeneratorResult<Box<dyn Backend>> {
+    Ok(match name {
+        "ts" => Box::new(TsBackend::new(templates_dir, ts_style)?),
+        "zod" => Box::new(ZodBackend::new(ts_style)),
+        "rust" => Box::new(RustBackend {
+            options: rust_options.clone(),
+        }),
+        "python" => Box::new(PythonBackend),
+        name => return Err(GeneratorError::UnknownBackend(name.into(), BACKENDS)),
+    })
+}
+
+/// Every schema's file along with the support files of every output directory.
+/// With `only`, the files of the other schemas are left out but still accounted for in the support files
+pub fn generate(
+    backend: &dyn Backend,
+    registry: &Registry,
+    outputs: &OutputRoots,
+    only: Option<&HashSet<Uuid>>,
+) -> GeneratorResult<Vec<GeneratedFile>> {
+    let mut entries = registry.entries().collect::<Vec<_>>();
+    entries.sort_by_key(|(_, schema_info)| schema_info.qualified_mod_path().to_string());
+
+    let components = components(registry);
+
+    // Output directory -> schema files relative to it
+    let mut dirs = BTreeMap::<&PathBuf, Vec<PathBuf>>::new();
+    let mut schema_files = Vec::with_capacity(entries.len());
+
+    for (id, schema_info) in entries {
+        let dir = outputs
+            .dir(schema_info.root.as_ref())
+            .ok_or_else(|| GeneratorError::NoOutputRoot(schema_info.qualified_mod_path()))?;
+        let file = backend.file(schema_info);
+
+        if only.is_some_and(|only| !only.contains(id)) {
+            dirs.entry(dir).or_default().push(file);
+            continue;
+        }
+
+        tracing::info!(
+            "Generating {} ({}) with {}...",
+            schema_info.name,
+            schema_info.qualified_mod_path(),
+            backend.name()
+        );
+
+        let ctx = SchemaContext {
+            id,
+            schema_info,
+            registry,
+            outputs,
+            components: &components,
+        };
+        let mut imports = Imports::default();
+
+        let body = match &schema_info.schema {
+            Schema::Class(class_schema) => backend.class(&ctx, class_schema, &mut imports)?,
+            Schema::Enum(enum_schema) => backend.enum_(&ctx, enum_schema, &mut imports)?,
+        };
+        let contents = backend.imports(&ctx, &imports)? + &body;
+
+        tracing::debug!("File String:\n{}", contents);
+
+        schema_files.push(GeneratedFile {
+            path: dir.join(&file),
+            contents,
+            source: Some(Source {
+                path: schema_info.path.clone(),
+                mod_path: schema_info.qualified_mod_path(),
+            }),
+        });
+        dirs.entry(dir).or_default().push(file);
+    }
+
+    let mut files = Vec::with_capacity(schema_files.len());
+
+    for (dir, dir_files) in dirs {
+        files.extend(
+            backend
+                .support_files(&dir_files)
+                .into_iter()
+                .map(|(path, contents)| GeneratedFile {
+                    path: dir.join(path),
+                    contents,
+                    source: None,
+                }),
+        );
+    }
+
+    files.extend(schema_files);
+
+    Ok(files)
+}
+
+/// Fails when two targets would write the same file
+pub fn ensure_distinct(files: &[GeneratedFile]) -> GeneratorResult<()> {
+    let mut paths = HashSet::with_capacity(files.len());
+
+    match files
+        .iter()
+        .find(|file| !paths.insert(normalize(&file.path)))
     {
-        match schema {
-            crate::schema::Schema::Class(_) => {
-                tracing::debug!("Still working on enums");
-            }
-            crate::schema::Schema::Enum(enum_schema) => {
-                generate_enum(EnumSchemaGenInfo {
-                    name,
-                    file_name,
-                    mod_path,
-                    enum_schema,
-                    validation: &validation.as_ref().map(
-                        |SchemaValidationInfo {
-                             aliases, defaults, ..
-                         }| EnumSchemaValidationInfo {
-                            aliases,
-                            default: defaults.as_ref().and_then(|defaults| match defaults {
-                                SchemaValidationDefaults::Enum(default) => Some(default),
-                                _ => None,
-                            }),
-                        },
-                    ),
-                })?;
-            }
+        Some(file) => Err(GeneratorError::DuplicateOutput(file.path.clone())),
+        None => Ok(()),
+    }
+}
+
+pub fn write_files(files: &[GeneratedFile]) -> GeneratorResult<()> {
+    for GeneratedFile { path, contents, .. } in files {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|err| GeneratorError::IO(err, parent.into()))?;
         }
+
+        tracing::info!("Writing {path:?}...");
+        fs::write(path, contents).map_err(|err| GeneratorError::IO(err, path.clone()))?;
     }
 
     Ok(())
 }
 
+/// Variant missing values fall back to, the configured default or else the first one
+pub fn default_variant<'a>(
+    schema_info: &'a SchemaInfo,
+    enum_schema: &'a EnumSchema,
+) -> GeneratorResult<&'a String> {
+    schema_info
+        .validation
+        .as_ref()
+        .and_then(|validation| validation.enum_().default)
+        .or_else(|| enum_schema.first())
+        .ok_or_else(|| GeneratorError::NoVariants(schema_info.qualified_mod_path()))
+}
+
+/// Path of the generated module relative to the output root, without an extension
+pub fn module(schema_info: &SchemaInfo) -> PathBuf {
+    schema_info
+        .mod_path
+        .parents()
+        .iter()
+        .chain([&schema_info.file_name])
+        .collect()
+}
+
+/// Resolves `.` and `..` components without touching the file system,
+/// so modules of different roots can be related to each other
+pub fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir
+                if matches!(
+                    normalized.components().next_back(),
+                    Some(Component::Normal(_))
+                ) =>
+            {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+
+    normalized
+}
+
+/// Relative import specifier pointing from one generated module to another
+pub fn import_path(from: &Path, to: &Path) -> String {
+    let from_dir = from
+        .parent()
+        .map(|parent| parent.iter().collect::<Vec<_>>())
+        .unwrap_or_default();
+    let to = to.iter().collect::<Vec<_>>();
+
+    let common = from_dir
+        .iter()
+        .zip(&to[..to.len() - 1])
+        .take_while(|(from, to)| from == to)
+        .count();
+
+    let prefix = match from_dir.len() - common {
+        0 => "./".to_string(),
+        ups => "../".repeat(ups),
+    };
+
+    prefix
+        + &to[common..]
+            .iter()
+            .map(|component| component.to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/")
+}
+
+fn ts_file(module: &Path) -> PathBuf {
+    let mut path = module.as_os_str().to_owned();
+    path.push(".ts");
+
+    path.into()
+}
+
 pub type GeneratorResult<T> = Result<T, GeneratorError>;
 
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Debug, Error, Diagnostic)]
 pub enum GeneratorError {
+    #[error("[Generator] {0}")]
+    Class(#[from] ClassGeneratorError),
     #[error("[Generator] {0}")]
     Enum(#[from] EnumGeneratorError),
+    #[error("[Generator] {0}")]
+    Zod(#[from] ZodGeneratorError),
+    #[error("[Generator] {0}")]
+    Rust(#[from] RustGeneratorError),
+    #[error("[Generator] {0}")]
+    Python(#[from] PythonGeneratorError),
+    #[error("[Generator] {0}")]
+    Plugin(#[from] PluginError),
+    #[error("[Generator] {0}")]
+    Template(#[from] TemplateError),
+    #[error("[Generator] [IO ({1:?})] {0}")]
+    IO(io::Error, PathBuf),
+    #[error("[Generator] No output directory for the root of '{0}'")]
+    NoOutputRoot(ModulePath),
+    #[error("[Generator] Enum '{0}' has no variants")]
+    NoVariants(ModulePath),
+    #[error("[Generator] Unknown target '{0}', neither one of {1:?} nor a configured plugin")]
+    UnknownBackend(String, [&'static str; 4]),
+    #[error("[Generator] {0:?} is generated by more than one target, give them different `output_subdir`s")]
+    DuplicateOutput(PathBuf),
+    #[error(
+        "[Generator] Generated files were edited by hand: {0:?}, pass `--force` to overwrite them"
+    )]
+    Modified(Vec<PathBuf>),
+    #[error("[Generator] {0} generated files are out of date, run desergen to regenerate them")]
+    Stale(usize),
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use super::*;
+    use crate::{
+        schema::{ClassSchema, EnumSchema},
+        test_utils::{registry, rooted_registry},
+    };
+
+    /// Writes one line per schema and lists the schema files of each directory in an index
+    #[derive(Default)]
+    struct ListBackend {
+        support_calls: RefCell<Vec<Vec<PathBuf>>>,
+    }
+
+    impl Backend for ListBackend {
+        fn name(&self) -> &'static str {
+            "list"
+        }
+
+        fn file(&self, schema_info: &SchemaInfo) -> PathBuf {
+            module(schema_info).with_extension("txt")
+        }
+
+        fn support_files(&self, schema_files: &[PathBuf]) -> Vec<(PathBuf, String)> {
+            self.support_calls.borrow_mut().push(schema_files.to_vec());
+
+            vec![(PathBuf::from("index.txt"), format!("{schema_files:?}"))]
+        }
+
+        fn class(
+            &self,
+            ctx: &SchemaContext,
+            class_schema: &ClassSchema,
+            imports: &mut Imports,
+        ) -> GeneratorResult<String> {
+            imports.add("fields", class_schema.len().to_string());
+
+            Ok(format!("class {}\n", ctx.schema_info.name))
+        }
+
+        fn enum_(
+            &self,
+            ctx: &SchemaContext,
+            _enum_schema: &EnumSchema,
+            _imports: &mut Imports,
+        ) -> GeneratorResult<String> {
+            Ok(format!("enum {}\n", ctx.schema_info.name))
+        }
+
+        fn imports(&self, _ctx: &SchemaContext, imports: &Imports) -> GeneratorResult<String> {
+            Ok(imports
+                .modules()
+                .map(|(from, names)| format!("{from}: {}\n", names.join(", ")))
+                .collect())
+        }
+    }
+
+    fn schemas() -> Registry {
+        registry(&[
+            ("a", r#"(schema: Class({ "x": Num, "y": Num }))"#),
+            ("nested::b", r#"(schema: Enum(["v"]))"#),
+        ])
+        .unwrap()
+    }
+
+    fn out() -> OutputRoots {
+        OutputRoots::from_iter([(None, PathBuf::from("out"))])
+    }
+
+    fn paths(files: &[GeneratedFile]) -> Vec<&Path> {
+        files.iter().map(|file| file.path.as_path()).collect()
+    }
+
+    #[test]
+    fn generates_through_the_backend() {
+        let backend = ListBackend::default();
+        let files = generate(&backend, &schemas(), &out(), None).unwrap();
+
+        assert_eq!(
+            paths(&files),
+            [
+                Path::new("out/index.txt"),
+                Path::new("out/a.txt"),
+                Path::new("out/nested/b.txt")
+            ]
+        );
+        assert_eq!(files[1].contents, "fields: 2\nclass A\n");
+        assert_eq!(files[2].contents, "enum B\n");
+        assert!(files[0].source.is_none());
+        assert_eq!(
+            files[2].source.as_ref().unwrap().mod_path.to_string(),
+            "nested::b"
+        );
+    }
+
+    #[test]
+    fn only_some_schemas() {
+        let backend = ListBackend::default();
+        let registry = schemas();
+        let only = registry
+            .entries()
+            .filter(|(_, schema_info)| schema_info.name == "B")
+            .map(|(id, _)| *id)
+            .collect();
+        let files = generate(&backend, &registry, &out(), Some(&only)).unwrap();
+
+        assert_eq!(
+            paths(&files),
+            [Path::new("out/index.txt"), Path::new("out/nested/b.txt")]
+        );
+        // The support files still account for the schema that wasn't regenerated
+        assert_eq!(
+            *backend.support_calls.borrow(),
+            [[PathBuf::from("a.txt"), PathBuf::from("nested/b.txt")]]
+        );
+    }
+
+    #[test]
+    fn needs_an_output_root() {
+        let registry =
+            rooted_registry(&[(Some("shared"), "money", r#"(schema: Class({ "x": Num }))"#)])
+                .unwrap();
+
+        assert!(matches!(
+            generate(&ListBackend::default(), &registry, &out(), None),
+            Err(GeneratorError::NoOutputRoot(mod_path)) if mod_path.to_string() == "shared::money"
+        ));
+    }
+
+    #[test]
+    fn selects_backends_by_name() {
+        for name in BACKENDS {
+            let backend = backend(name, &RustOptions::default(), TsStyle::default(), None);
+
+            assert_eq!(backend.unwrap().name(), name);
+        }
+
+        assert!(matches!(
+            backend("cobol", &RustOptions::default(), TsStyle::default(), None),
+            Err(GeneratorError::UnknownBackend(name, _)) if name == "cobol"
+        ));
+    }
+
+    #[test]
+    fn distinct_outputs() {
+        let file = |path: &str| GeneratedFile {
+            path: path.into(),
+            contents: String::new(),
+            source: None,
+        };
+
+        assert!(ensure_distinct(&[file("out/a.ts"), file("out/b.ts")]).is_ok());
+        assert!(matches!(
+            ensure_distinct(&[file("out/a.ts"), file("out/./x/../a.ts")]),
+            Err(GeneratorError::DuplicateOutput(_))
+        ));
+    }
+
+    #[test]
+    fn import_paths() {
+        assert_eq!(import_path(Path::new("out/a"), Path::new("out/b")), "./b");
+        assert_eq!(
+            import_path(Path::new("out/api/order"), Path::new("out/common/money")),
+            "../common/money"
+        );
+        assert_eq!(
+            import_path(Path::new("out/a"), Path::new("out/nested/b")),
+            "./nested/b"
+        );
+        assert_eq!(
+            normalize(Path::new("app/src/../../shared/./src")),
+            Path::new("shared/src")
+        );
+    }
 }
@@ -0,0 +1,90 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    path::PathBuf,
+};
+
+use uuid::Uuid;
+
+use crate::schema::{info::SchemaInfo, registry::Registry, ClassSchema, EnumSchema};
+
+use super::{GeneratorResult, OutputRoots};
+
+/// Everything known about the schema being generated
+pub struct SchemaContext<'a> {
+    pub id: &'a Uuid,
+    pub schema_info: &'a SchemaInfo,
+    pub registry: &'a Registry,
+    pub outputs: &'a OutputRoots,
+    /// Strongly connected component of every schema, see [`crate::schema::graph::components`]
+    pub components: &'a HashMap<Uuid, usize>,
+}
+
+impl<'a> SchemaContext<'a> {
+    /// Whether a reference from this schema leads back to it
+    pub fn in_cycle(&self, id: &Uuid) -> bool {
+        self.components.get(id) == self.components.get(self.id)
+    }
+
+    /// Whether the schema references itself, directly or through others
+    pub fn is_cyclic(&self) -> bool {
+        self.schema_info
+            .schema
+            .references()
+            .iter()
+            .any(|id| self.in_cycle(id))
+    }
+}
+
+/// Names a schema's file imports, by the module they come from
+#[derive(Debug, Default)]
+pub struct Imports {
+    modules: BTreeMap<String, BTreeSet<String>>,
+    /// Whether the runtime of the output directory is used
+    pub runtime: bool,
+}
+
+impl Imports {
+    pub fn add(&mut self, from: impl Into<String>, name: impl Into<String>) {
+        self.modules
+            .entry(from.into())
+            .or_default()
+            .insert(name.into());
+    }
+
+    /// Modules in order with their names in order
+    pub fn modules(&self) -> impl Iterator<Item = (&String, Vec<String>)> {
+        self.modules
+            .iter()
+            .map(|(from, names)| (from, names.iter().cloned().collect()))
+    }
+}
+
+/// Output language of the generated code
+pub trait Backend {
+    /// Name the backend is selected by in `Config::targets`
+    fn name(&self) -> &'static str;
+
+    /// Path of a schema's file relative to the output directory of its root
+    fn file(&self, schema_info: &SchemaInfo) -> PathBuf;
+
+    /// Files an output directory needs besides the schema files in it (runtime, package markers),
+    /// relative to the directory
+    fn support_files(&self, schema_files: &[PathBuf]) -> Vec<(PathBuf, String)>;
+
+    fn class(
+        &self,
+        ctx: &SchemaContext,
+        class_schema: &ClassSchema,
+        imports: &mut Imports,
+    ) -> GeneratorResult<String>;
+
+    fn enum_(
+        &self,
+        ctx: &SchemaContext,
+        enum_schema: &EnumSchema,
+        imports: &mut Imports,
+    ) -> GeneratorResult<String>;
+
+    /// Start of a schema's file, up to where the class or enum begins
+    fn imports(&self, ctx: &SchemaContext, imports: &Imports) -> GeneratorResult<String>;
+}
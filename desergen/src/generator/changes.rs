@@ -0,0 +1,297 @@
+use std::{
+    collections::HashSet,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use similar::TextDiff;
+use walkdir::WalkDir;
+
+use super::{
+    header::is_generated, manifest::ManifestEntry, normalize, GeneratedFile, GeneratorError,
+    GeneratorResult, OutputRoots,
+};
+
+/// How a file on disk differs from the generated output
+#[derive(Debug)]
+pub enum Change<'a> {
+    /// Not written yet
+    Created(&'a str),
+    /// Written with other contents
+    Changed { old: String, new: &'a str },
+    /// Generated before, but none of the schemas generates it anymore, `edited` since it was written
+    Orphaned { old: String, edited: bool },
+}
+
+impl Change<'_> {
+    /// Unified diff from the contents on disk to the generated ones
+    pub fn diff(&self, path: &Path) -> String {
+        let (old, new) = match self {
+            Change::Created(new) => ("", *new),
+            Change::Changed { old, new } => (old.as_str(), *new),
+            Change::Orphaned { old, .. } => (old.as_str(), ""),
+        };
+        let path = path.to_string_lossy();
+
+        TextDiff::from_lines(old, new)
+            .unified_diff()
+            .header(&path, &path)
+            .to_string()
+    }
+}
+
+/// Every generated file that differs from the one on disk, followed by the previously generated
+/// files that are no longer part of the output: the ones of the manifest and, with `outputs`,
+/// any other file under the output directories starting with the generated header
+pub fn changes<'a>(
+    files: &'a [GeneratedFile],
+    outputs: Option<&OutputRoots>,
+    manifest_files: &[(PathBuf, &ManifestEntry)],
+) -> GeneratorResult<Vec<(PathBuf, Change<'a>)>> {
+    let mut changes = vec![];
+
+    for GeneratedFile { path, contents, .. } in files {
+        match fs::read_to_string(path) {
+            Ok(existing) if existing == *contents => {}
+            Ok(old) => changes.push((path.clone(), Change::Changed { old, new: contents })),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                changes.push((path.clone(), Change::Created(contents)))
+            }
+            Err(err) => return Err(GeneratorError::IO(err, path.clone())),
+        }
+    }
+
+    // Also holds the orphans found so far, as output directories can be nested
+    let mut seen = files
+        .iter()
+        .map(|GeneratedFile { path, .. }| normalize(path))
+        .collect::<HashSet<_>>();
+
+    for (path, entry) in manifest_files {
+        if !seen.insert(normalize(path)) {
+            continue;
+        }
+
+        match fs::read_to_string(path) {
+            Ok(old) => {
+                let edited = entry.is_edited(&old);
+                changes.push((path.clone(), Change::Orphaned { old, edited }))
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => return Err(GeneratorError::IO(err, path.clone())),
+        }
+    }
+
+    for (_, dir) in outputs.iter().flat_map(|outputs| outputs.iter()) {
+        for (path, contents) in generated_files(dir)? {
+            if seen.insert(normalize(&path)) {
+                let change = Change::Orphaned {
+                    old: contents,
+                    edited: false,
+                };
+                changes.push((path, change));
+            }
+        }
+    }
+
+    Ok(changes)
+}
+
+/// Files under `dir` starting with the generated header along with their contents,
+/// skipping hidden directories and `node_modules`
+fn generated_files(dir: &Path) -> GeneratorResult<Vec<(PathBuf, String)>> {
+    let mut files = vec![];
+
+    let entries = WalkDir::new(dir)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|entry| {
+            entry.depth() == 0
+                || !entry.file_type().is_dir()
+                || !entry
+                    .file_name()
+                    .to_str()
+                    .is_some_and(|name| name.starts_with('.') || name == "node_modules")
+        });
+
+    for entry in entries {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(err) if entry_missing(&err) => continue,
+            Err(err) => {
+                let path = err.path().unwrap_or(dir).into();
+                return Err(GeneratorError::IO(err.into(), path));
+            }
+        };
+
+        if !entry.file_type().is_file() {
+            continue;
+        }
+
+        let path = entry.into_path();
+
+        // Files that aren't text can't be generated ones
+        if let Ok(contents) = fs::read_to_string(&path) {
+            if is_generated(&path, &contents) {
+                files.push((path, contents));
+            }
+        }
+    }
+
+    Ok(files)
+}
+
+/// The output directory not existing yet just means nothing was generated
+fn entry_missing(err: &walkdir::Error) -> bool {
+    err.io_error()
+        .is_some_and(|err| err.kind() == io::ErrorKind::NotFound)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::OsStr;
+
+    use super::*;
+    use crate::{
+        generator::{header::add_headers, manifest::Manifest},
+        test_utils::temp_dir,
+    };
+
+    /// Files with the generated header, as they'd be written
+    fn generated(files: &[(&Path, &str)]) -> Vec<GeneratedFile> {
+        let mut files = files
+            .iter()
+            .map(|(path, contents)| GeneratedFile {
+                path: path.to_path_buf(),
+                contents: contents.to_string(),
+                source: None,
+            })
+            .collect::<Vec<_>>();
+        add_headers(&mut files);
+        files
+    }
+
+    fn kinds<'a>(changes: &'a [(PathBuf, Change)], root: &Path) -> Vec<(&'a Path, &'static str)> {
+        changes
+            .iter()
+            .map(|(path, change)| {
+                let kind = match change {
+                    Change::Created(_) => "created",
+                    Change::Changed { .. } => "changed",
+                    Change::Orphaned { .. } => "orphaned",
+                };
+                (path.strip_prefix(root).unwrap(), kind)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn stale_outputs() {
+        let root = temp_dir();
+        let out = root.path().join("out");
+        let outputs = OutputRoots::from_iter([(None, out.clone())]);
+        let written = generated(&[
+            (&out.join("same.ts"), "same\n"),
+            (&out.join("changed.ts"), "old\n"),
+            (&out.join("nested/orphan.ts"), "orphan\n"),
+            (&out.join("node_modules/dep.ts"), "dep\n"),
+            (&out.join(".cache/hidden.ts"), "hidden\n"),
+        ]);
+        fs::create_dir_all(out.join("nested")).unwrap();
+        fs::create_dir_all(out.join("node_modules")).unwrap();
+        fs::create_dir_all(out.join(".cache")).unwrap();
+        for file in &written {
+            fs::write(&file.path, &file.contents).unwrap();
+        }
+        fs::write(out.join("handwritten.ts"), "export {};\n").unwrap();
+
+        let files = generated(&[
+            (&out.join("same.ts"), "same\n"),
+            (&out.join("changed.ts"), "new\n"),
+            (&out.join("created.ts"), "created\n"),
+        ]);
+        let changes = changes(&files, Some(&outputs), &[]).unwrap();
+
+        assert_eq!(
+            kinds(&changes, &out),
+            [
+                (Path::new("changed.ts"), "changed"),
+                (Path::new("created.ts"), "created"),
+                (Path::new("nested/orphan.ts"), "orphaned"),
+            ]
+        );
+        assert!(changes
+            .iter()
+            .all(|(_, change)| !matches!(change, Change::Orphaned { edited: true, .. })));
+    }
+
+    #[test]
+    fn missing_output_dir() {
+        let root = temp_dir();
+        let outputs = OutputRoots::from_iter([(None, root.path().join("out"))]);
+        let files = generated(&[(&root.path().join("out/a.ts"), "a\n")]);
+
+        assert_eq!(
+            kinds(&changes(&files, Some(&outputs), &[]).unwrap(), root.path()),
+            [(Path::new("out/a.ts"), "created")]
+        );
+    }
+
+    #[test]
+    fn manifest_orphans() {
+        let root = temp_dir();
+        let (kept, edited) = (root.path().join("kept.ts"), root.path().join("edited.ts"));
+        let written = generated(&[(&kept, "kept\n"), (&edited, "edited\n")]);
+        for file in &written {
+            fs::write(&file.path, &file.contents).unwrap();
+        }
+        let manifest = Manifest::default()
+            .update(root.path(), &written, &HashSet::new())
+            .0;
+        fs::write(&edited, "by hand\n").unwrap();
+        // Looks generated, but without `outputs` only the manifest's files count
+        fs::write(root.path().join("other.ts"), &written[0].contents).unwrap();
+
+        let changes = changes(&[], None, &manifest.files(root.path())).unwrap();
+
+        assert_eq!(
+            changes
+                .iter()
+                .map(|(path, change)| match change {
+                    Change::Orphaned { edited, .. } => (path.file_name().unwrap(), *edited),
+                    change => panic!("expected an orphan, got {change:?}"),
+                })
+                .collect::<Vec<_>>(),
+            [
+                (OsStr::new("edited.ts"), true),
+                (OsStr::new("kept.ts"), false)
+            ]
+        );
+    }
+
+    #[test]
+    fn diffs() {
+        let path = Path::new("out/a.ts");
+
+        assert_eq!(
+            Change::Changed {
+                old: "a\nb\n".into(),
+                new: "a\nc\n"
+            }
+            .diff(path),
+            "--- out/a.ts\n+++ out/a.ts\n@@ -1,2 +1,2 @@\n a\n-b\n+c\n"
+        );
+        assert_eq!(
+            Change::Created("a\n").diff(path),
+            "--- out/a.ts\n+++ out/a.ts\n@@ -0,0 +1 @@\n+a\n"
+        );
+        assert_eq!(
+            Change::Orphaned {
+                old: "a\n".into(),
+                edited: false
+            }
+            .diff(path),
+            "--- out/a.ts\n+++ out/a.ts\n@@ -1 +0,0 @@\n-a\n"
+        );
+    }
+}
@@ -0,0 +1,208 @@
+use std::{
+    fs, io,
+    path::{Component, Path, PathBuf},
+};
+
+use sha2::{Digest, Sha256};
+
+use crate::schema::module_path::ModulePath;
+
+use super::{manifest::ManifestEntry, normalize, GeneratedFile, GeneratorError, GeneratorResult};
+
+/// First line of every header, telling generated files apart from hand-written ones
+const NOTICE: &str = "@generated by desergen, do not edit by hand";
+const SCHEMA_LABEL: &str = "schema: ";
+const HASH_LABEL: &str = "hash: sha256:";
+
+/// Schema a generated file was made from
+#[derive(Debug, Clone)]
+pub struct Source {
+    /// Schema file, relative to the same directory as the path of the generated file
+    pub path: PathBuf,
+    pub mod_path: ModulePath,
+}
+
+/// Starts every file that can hold comments with the generated notice, its source schema
+/// and the hash of the rest of the file
+pub fn add_headers(files: &mut [GeneratedFile]) {
+    for GeneratedFile {
+        path,
+        contents,
+        source,
+    } in files
+    {
+        let Some(comment) = line_comment(path) else {
+            continue;
+        };
+
+        let mut header = format!("{comment} {NOTICE}\n");
+
+        if let Some(Source {
+            path: schema_path,
+            mod_path,
+        }) = source
+        {
+            let dir = path.parent().unwrap_or(Path::new(""));
+
+            header += &format!(
+                "{comment} {SCHEMA_LABEL}{} ({mod_path})\n",
+                relative_path(dir, schema_path)
+            );
+        }
+
+        let body = contents.trim_start_matches('\n');
+        header += &format!("{comment} {HASH_LABEL}{}\n\n", hash(body));
+
+        *contents = header + body;
+    }
+}
+
+/// Fails on generated files that were edited since they were written. Files without a header
+/// are left to be overwritten, unless the manifest lists them as written by desergen
+pub fn ensure_unmodified(
+    files: &[GeneratedFile],
+    manifest_files: &[(PathBuf, &ManifestEntry)],
+) -> GeneratorResult<()> {
+    let mut modified = vec![];
+
+    for GeneratedFile { path, .. } in files {
+        let existing = match fs::read_to_string(path) {
+            Ok(existing) => existing,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+            Err(err) => return Err(GeneratorError::IO(err, path.clone())),
+        };
+
+        let edited = match line_comment(path).and_then(|comment| parse(comment, &existing)) {
+            Some((recorded, body)) => recorded != hash(body),
+            None => {
+                let path = normalize(path);
+
+                manifest_files
+                    .iter()
+                    .find(|(listed, _)| *listed == path)
+                    .is_some_and(|(_, entry)| entry.is_edited(&existing))
+            }
+        };
+
+        if edited {
+            tracing::error!("{path:?} was edited since it was generated");
+            modified.push(path.clone());
+        }
+    }
+
+    match modified.is_empty() {
+        true => Ok(()),
+        false => Err(GeneratorError::Modified(modified)),
+    }
+}
+
+/// Whether a file's contents start with the generated header
+pub fn is_generated(path: &Path, contents: &str) -> bool {
+    line_comment(path).is_some_and(|comment| parse(comment, contents).is_some())
+}
+
+/// The recorded hash and the body it was computed from
+fn parse<'a>(comment: &str, contents: &'a str) -> Option<(&'a str, &'a str)> {
+    let mut rest = contents.strip_prefix(comment)?.strip_prefix(' ')?;
+    rest = rest.strip_prefix(NOTICE)?.strip_prefix('\n')?;
+
+    let mut recorded = None;
+
+    while let Some(line_rest) = rest.strip_prefix(comment) {
+        let (line, next) = line_rest.split_once('\n')?;
+
+        if let Some(hash) = line.trim_start().strip_prefix(HASH_LABEL) {
+            recorded = Some(hash.trim_end());
+        }
+
+        rest = next;
+    }
+
+    Some((recorded?, rest.strip_prefix('\n')?))
+}
+
+/// Hex SHA-256 of the body of a generated file, or of any other contents
+pub fn hash(contents: impl AsRef<[u8]>) -> String {
+    Sha256::digest(contents.as_ref())
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// Line comment prefix of the language a file is in, `None` for formats without comments
+fn line_comment(path: &Path) -> Option<&'static str> {
+    match path.extension()?.to_str()? {
+        "ts" | "tsx" | "js" | "mjs" | "cjs" | "rs" | "go" | "java" | "kt" | "swift" | "dart"
+        | "cs" | "c" | "h" | "cpp" | "hpp" | "scala" => Some("//"),
+        "py" | "rb" | "sh" | "toml" | "yaml" | "yml" => Some("#"),
+        _ => None,
+    }
+}
+
+/// `/`-separated path leading from `from_dir` to `to`
+pub fn relative_path(from_dir: &Path, to: &Path) -> String {
+    let from_dir = normalize(from_dir);
+    let to = normalize(to);
+
+    let from_components = from_dir.components().collect::<Vec<_>>();
+    let to_components = to.components().collect::<Vec<_>>();
+
+    let common = from_components
+        .iter()
+        .zip(&to_components)
+        .take_while(|(from, to)| from == to)
+        .count();
+
+    from_components[common..]
+        .iter()
+        .map(|_| Component::ParentDir)
+        .chain(to_components[common..].iter().cloned())
+        .map(|component| component.as_os_str().to_string_lossy().into_owned())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::{generator::manifest::Manifest, test_utils::temp_dir};
+
+    fn is_unmodified(files: &[GeneratedFile], manifest: &Manifest, root: &Path) -> bool {
+        match ensure_unmodified(files, &manifest.files(root)) {
+            Ok(()) => true,
+            Err(GeneratorError::Modified(modified)) => {
+                assert_eq!(modified, [files[0].path.clone()]);
+                false
+            }
+            Err(err) => panic!("{err}"),
+        }
+    }
+
+    #[test]
+    fn edited_files() {
+        let root = temp_dir();
+        let mut files = [GeneratedFile {
+            path: root.path().join("a.ts"),
+            contents: "export type A = number;\n".into(),
+            source: None,
+        }];
+        add_headers(&mut files);
+        let [file] = &files;
+        fs::write(&file.path, &file.contents).unwrap();
+        let manifest = Manifest::default()
+            .update(root.path(), &files, &HashSet::new())
+            .0;
+
+        assert!(is_unmodified(&files, &manifest, root.path()));
+
+        fs::write(&file.path, file.contents.replace("number", "string")).unwrap();
+        assert!(!is_unmodified(&files, &manifest, root.path()));
+
+        // Without its header, only the manifest tells that desergen wrote it
+        fs::write(&file.path, "export type A = number;\n").unwrap();
+        assert!(!is_unmodified(&files, &manifest, root.path()));
+        assert!(is_unmodified(&files, &Manifest::default(), root.path()));
+    }
+}
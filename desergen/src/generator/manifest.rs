@@ -0,0 +1,312 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    header::{hash, relative_path, Source},
+    normalize, GeneratedFile, GeneratorError, GeneratorResult,
+};
+
+/// File in `desergen_root` the manifest is kept in
+pub const MANIFEST_FILE: &str = ".desergen-manifest.json";
+
+/// Every file desergen wrote, so the ones no schema generates anymore can be
+/// removed without touching anything else in the output directories
+#[derive(Default, Serialize, Deserialize)]
+pub struct Manifest {
+    /// By path relative to `desergen_root`
+    files: BTreeMap<String, ManifestEntry>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    /// Schema file it was generated from relative to `desergen_root`, `None` for support files
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    schema: Option<String>,
+    /// Hash of the contents it was written with
+    hash: String,
+}
+
+impl ManifestEntry {
+    /// Whether the file no longer has the contents it was written with
+    pub fn is_edited(&self, contents: impl AsRef<[u8]>) -> bool {
+        hash(contents) != self.hash
+    }
+}
+
+impl Manifest {
+    /// The manifest of the last generation, an empty one when there is none or it can't be read
+    pub fn load(desergen_root: &Path) -> Self {
+        let path = desergen_root.join(MANIFEST_FILE);
+
+        match fs::read(&path) {
+            Ok(contents) => serde_json::from_slice(&contents).unwrap_or_else(|err| {
+                tracing::warn!("Ignoring the unreadable manifest {path:?}: {err}");
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn save(&self, desergen_root: &Path) -> GeneratorResult<()> {
+        let path = desergen_root.join(MANIFEST_FILE);
+        let contents = serde_json::to_string_pretty(self).expect("manifests always serialize");
+
+        fs::create_dir_all(desergen_root)
+            .map_err(|err| GeneratorError::IO(err, desergen_root.into()))?;
+        fs::write(&path, contents + "\n").map_err(|err| GeneratorError::IO(err, path))
+    }
+
+    /// Every listed file by its path
+    pub fn files(&self, desergen_root: &Path) -> Vec<(PathBuf, &ManifestEntry)> {
+        self.files
+            .iter()
+            .map(|(path, entry)| (normalize(&desergen_root.join(path)), entry))
+            .collect()
+    }
+
+    /// The manifest after writing `files`, keeping the files of the `skipped` schemas that weren't
+    /// regenerated, along with the listed files that are no longer generated
+    pub fn update(
+        &self,
+        desergen_root: &Path,
+        files: &[GeneratedFile],
+        skipped: &HashSet<PathBuf>,
+    ) -> (Self, Vec<(PathBuf, ManifestEntry)>) {
+        let mut updated = BTreeMap::new();
+
+        for GeneratedFile {
+            path,
+            contents,
+            source,
+        } in files
+        {
+            updated.insert(
+                relative_path(desergen_root, path),
+                ManifestEntry {
+                    schema: source
+                        .as_ref()
+                        .map(|Source { path, .. }| relative_path(desergen_root, path)),
+                    hash: hash(contents),
+                },
+            );
+        }
+
+        let mut orphans = vec![];
+
+        for (path, entry) in &self.files {
+            if updated.contains_key(path) {
+                continue;
+            }
+
+            let is_skipped = entry
+                .schema
+                .as_ref()
+                .is_some_and(|schema| skipped.contains(&normalize(&desergen_root.join(schema))));
+
+            match is_skipped {
+                true => {
+                    updated.insert(path.clone(), entry.clone());
+                }
+                false => orphans.push((normalize(&desergen_root.join(path)), entry.clone())),
+            }
+        }
+
+        (Self { files: updated }, orphans)
+    }
+
+    /// Deletes the orphaned files, keeping the ones edited since they were written unless `force`.
+    /// Those stay listed, to be reported again on the next run
+    pub fn remove_orphans(
+        &mut self,
+        desergen_root: &Path,
+        orphans: Vec<(PathBuf, ManifestEntry)>,
+        force: bool,
+    ) -> GeneratorResult<()> {
+        for (path, entry) in orphans {
+            let contents = match fs::read(&path) {
+                Ok(contents) => contents,
+                Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+                Err(err) => return Err(GeneratorError::IO(err, path)),
+            };
+
+            if !force && entry.is_edited(contents) {
+                tracing::warn!(
+                    "{path:?} no longer has a schema but was edited since it was generated, pass `--force` to remove it"
+                );
+                self.files
+                    .insert(relative_path(desergen_root, &path), entry);
+                continue;
+            }
+
+            tracing::info!("Removing {path:?}...");
+            fs::remove_file(&path).map_err(|err| GeneratorError::IO(err, path))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::{schema::module_path::ModulePath, test_utils::temp_dir};
+
+    struct Project {
+        root: TempDir,
+    }
+
+    impl Project {
+        fn new() -> Self {
+            let root = temp_dir();
+            fs::create_dir_all(root.path().join("out")).unwrap();
+
+            Self { root }
+        }
+
+        fn file(&self, name: &str, schema: Option<&str>) -> GeneratedFile {
+            GeneratedFile {
+                path: self.root.path().join(format!("out/{name}.ts")),
+                contents: format!("// {name}\n"),
+                source: schema.map(|schema| Source {
+                    path: self.root.path().join(format!("schemas/{schema}.ron")),
+                    mod_path: ModulePath::single(schema),
+                }),
+            }
+        }
+
+        /// Writes the files and the manifest listing them
+        fn generate(&self, files: &[GeneratedFile]) -> Manifest {
+            for file in files {
+                fs::write(&file.path, &file.contents).unwrap();
+            }
+
+            Manifest::default()
+                .update(self.root.path(), files, &HashSet::new())
+                .0
+        }
+
+        fn listed(&self, manifest: &Manifest) -> Vec<PathBuf> {
+            manifest
+                .files(self.root.path())
+                .into_iter()
+                .map(|(path, _)| path)
+                .collect()
+        }
+    }
+
+    #[test]
+    fn orphans() {
+        let project = Project::new();
+        let (a, b, runtime) = (
+            project.file("a", Some("a")),
+            project.file("b", Some("b")),
+            project.file("runtime", None),
+        );
+        let manifest = project.generate(&[a, b, runtime]);
+
+        let (mut updated, orphans) = manifest.update(
+            project.root.path(),
+            &[project.file("a", Some("a"))],
+            &HashSet::new(),
+        );
+
+        assert_eq!(
+            orphans.iter().map(|(path, _)| path).collect::<Vec<_>>(),
+            [
+                &project.root.path().join("out/b.ts"),
+                &project.root.path().join("out/runtime.ts")
+            ]
+        );
+
+        updated
+            .remove_orphans(project.root.path(), orphans, false)
+            .unwrap();
+
+        assert!(project.root.path().join("out/a.ts").exists());
+        assert!(!project.root.path().join("out/b.ts").exists());
+        assert!(!project.root.path().join("out/runtime.ts").exists());
+        assert_eq!(
+            project.listed(&updated),
+            [project.root.path().join("out/a.ts")]
+        );
+    }
+
+    #[test]
+    fn skipped_schemas_keep_their_files() {
+        let project = Project::new();
+        let manifest =
+            project.generate(&[project.file("a", Some("a")), project.file("b", Some("b"))]);
+        let skipped = HashSet::from([project.root.path().join("schemas/b.ron")]);
+
+        let (updated, orphans) = manifest.update(
+            project.root.path(),
+            &[project.file("a", Some("a"))],
+            &skipped,
+        );
+
+        assert!(orphans.is_empty());
+        assert_eq!(
+            project.listed(&updated),
+            [
+                project.root.path().join("out/a.ts"),
+                project.root.path().join("out/b.ts")
+            ]
+        );
+    }
+
+    #[test]
+    fn edited_orphans() {
+        let project = Project::new();
+        let manifest =
+            project.generate(&[project.file("a", Some("a")), project.file("b", Some("b"))]);
+        let b = project.root.path().join("out/b.ts");
+        fs::write(&b, "// edited\n").unwrap();
+
+        let (mut updated, orphans) = manifest.update(
+            project.root.path(),
+            &[project.file("a", Some("a"))],
+            &HashSet::new(),
+        );
+
+        assert!(orphans[0].1.is_edited(fs::read(&b).unwrap()));
+
+        updated
+            .remove_orphans(project.root.path(), orphans.clone(), false)
+            .unwrap();
+
+        assert!(b.exists());
+        // Still listed, to be reported again
+        assert_eq!(
+            project.listed(&updated),
+            [project.root.path().join("out/a.ts"), b.clone()]
+        );
+
+        updated
+            .remove_orphans(project.root.path(), orphans, true)
+            .unwrap();
+
+        assert!(!b.exists());
+    }
+
+    #[test]
+    fn missing_orphans() {
+        let project = Project::new();
+        let manifest = project.generate(&[project.file("a", Some("a"))]);
+        fs::remove_file(project.root.path().join("out/a.ts")).unwrap();
+
+        let (mut updated, orphans) = manifest.update(project.root.path(), &[], &HashSet::new());
+
+        updated
+            .remove_orphans(project.root.path(), orphans, false)
+            .unwrap();
+
+        assert!(project.listed(&updated).is_empty());
+    }
+}
@@ -0,0 +1,530 @@
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+    path::{Component, Path, PathBuf},
+    process::{Command, ExitStatus, Stdio},
+    thread,
+};
+
+use miette::Diagnostic;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::schema::{
+    graph::components,
+    info::SchemaInfo,
+    registry::{Registry, RegistryError},
+    rename_rule::RenameRule,
+    unknown_keys::UnknownKeys,
+    validation_info::{ClassSchemaValidationInfo, EnumSchemaValidationInfo},
+    ClassSchema, EnumSchema, Schema, SchemaMemberType,
+};
+
+use super::{GeneratedFile, OutputRoots};
+
+/// Version of the JSON exchanged with plugins, bumped on breaking changes
+const PROTOCOL_VERSION: u32 = 1;
+
+/// External executable generating the code of a target.
+/// It gets a [`PluginRequest`] as JSON on stdin and answers with a JSON array of [`PluginFile`]s on stdout,
+/// anything it writes to stderr is shown to the user
+pub struct Plugin<'a> {
+    pub name: &'a str,
+    pub command: PathBuf,
+    pub args: &'a [String],
+}
+
+impl Plugin<'_> {
+    pub fn run(
+        &self,
+        registry: &Registry,
+        outputs: &OutputRoots,
+    ) -> PluginResult<Vec<GeneratedFile>> {
+        let request = PluginRequest::new(self.name, registry, outputs)?;
+        let input = serde_json::to_vec(&request).expect("plugin requests always serialize");
+
+        tracing::info!("Running plugin '{}' ({:?})...", self.name, self.command);
+
+        let mut child = Command::new(&self.command)
+            .args(self.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .map_err(|err| PluginError::Spawn(self.name.into(), self.command.clone(), err))?;
+
+        // Written from another thread so a plugin answering before reading everything can't block us
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        let writer = thread::spawn(move || stdin.write_all(&input));
+
+        let output = child
+            .wait_with_output()
+            .map_err(|err| PluginError::IO(self.name.into(), err))?;
+
+        if !output.status.success() {
+            return Err(PluginError::Failed(self.name.into(), output.status));
+        }
+
+        // A plugin that succeeded without reading all of its input just didn't need the rest
+        match writer.join().expect("stdin writer doesn't panic") {
+            Err(err) if err.kind() != io::ErrorKind::BrokenPipe => {
+                return Err(PluginError::IO(self.name.into(), err))
+            }
+            _ => {}
+        }
+
+        let files: Vec<PluginFile> = serde_json::from_slice(&output.stdout)
+            .map_err(|err| PluginError::InvalidOutput(self.name.into(), err))?;
+
+        files
+            .into_iter()
+            .map(|file| self.resolve(file, outputs))
+            .collect()
+    }
+
+    fn resolve(&self, file: PluginFile, outputs: &OutputRoots) -> PluginResult<GeneratedFile> {
+        let PluginFile {
+            path,
+            root,
+            contents,
+        } = file;
+
+        if path.as_os_str().is_empty()
+            || !path
+                .components()
+                .all(|component| matches!(component, Component::Normal(_)))
+        {
+            return Err(PluginError::InvalidPath(self.name.into(), path));
+        }
+
+        let dir = outputs
+            .dir(root.as_ref())
+            .ok_or_else(|| PluginError::UnknownRoot(self.name.into(), root.clone()))?;
+
+        Ok(GeneratedFile {
+            path: dir.join(path),
+            contents,
+            source: None,
+        })
+    }
+}
+
+/// File a plugin wants written, relative to the output directory of `root`
+#[derive(Deserialize)]
+struct PluginFile {
+    path: PathBuf,
+    #[serde(default)]
+    root: Option<String>,
+    contents: String,
+}
+
+/// Everything a plugin is given: the resolved schemas with their validation info
+#[derive(Serialize)]
+struct PluginRequest<'a> {
+    version: u32,
+    target: &'a str,
+    outputs: Vec<OutputModel<'a>>,
+    /// In order of their qualified module paths
+    schemas: Vec<SchemaModel<'a>>,
+}
+
+#[derive(Serialize)]
+struct OutputModel<'a> {
+    root: Option<&'a String>,
+    dir: &'a Path,
+}
+
+#[derive(Serialize)]
+struct SchemaModel<'a> {
+    id: String,
+    root: Option<&'a String>,
+    /// Module path inside of its root
+    mod_path: String,
+    /// Module path prefixed with its root's name, as references spell it
+    qualified_mod_path: String,
+    name: &'a String,
+    file_name: &'a String,
+    rename_all: Option<&'a RenameRule>,
+    unknown_keys: UnknownKeys,
+    /// Ids of the referenced schemas, in order of appearance
+    references: Vec<String>,
+    /// Whether a reference leads back to the schema, directly or through others
+    cyclic: bool,
+    #[serde(flatten)]
+    kind: KindModel<'a>,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum KindModel<'a> {
+    Class {
+        members: Vec<MemberModel<'a>>,
+    },
+    Enum {
+        variants: Vec<VariantModel<'a>>,
+        default: Option<&'a String>,
+    },
+}
+
+#[derive(Serialize)]
+struct MemberModel<'a> {
+    /// Name on the wire
+    name: &'a String,
+    /// Name after `rename_all`
+    property: String,
+    #[serde(rename = "type")]
+    ty: TypeModel,
+    allow_undefined: bool,
+    aliases: &'a [String],
+    /// Default as it appears on the wire
+    default: Option<serde_json::Value>,
+}
+
+#[derive(Serialize)]
+struct VariantModel<'a> {
+    name: &'a String,
+    aliases: &'a [String],
+}
+
+#[derive(Serialize)]
+#[serde(tag = "kind")]
+enum TypeModel {
+    Num,
+    Str,
+    Bool,
+    Arr {
+        item: Box<TypeModel>,
+    },
+    Map {
+        key: Box<TypeModel>,
+        value: Box<TypeModel>,
+    },
+    Opt {
+        inner: Box<TypeModel>,
+    },
+    DefClass {
+        id: String,
+        qualified_mod_path: String,
+    },
+    DefEnum {
+        id: String,
+        qualified_mod_path: String,
+    },
+}
+
+impl<'a> PluginRequest<'a> {
+    fn new(
+        target: &'a str,
+        registry: &'a Registry,
+        outputs: &'a OutputRoots,
+    ) -> PluginResult<Self> {
+        let mut entries = registry.entries().collect::<Vec<_>>();
+        entries.sort_by_key(|(_, schema_info)| schema_info.qualified_mod_path().to_string());
+
+        let mut outputs = outputs
+            .iter()
+            .map(|(root, dir)| OutputModel { root, dir })
+            .collect::<Vec<_>>();
+        outputs.sort_by_key(|output| output.root);
+
+        let components = components(registry);
+
+        let schemas = entries
+            .into_iter()
+            .map(|(id, schema_info)| {
+                let references = schema_info.schema.references();
+                let cyclic = references
+                    .iter()
+                    .any(|reference| components.get(reference) == components.get(id));
+
+                Ok(SchemaModel {
+                    id: id.to_string(),
+                    root: schema_info.root.as_ref(),
+                    mod_path: schema_info.mod_path.to_string(),
+                    qualified_mod_path: schema_info.qualified_mod_path().to_string(),
+                    name: &schema_info.name,
+                    file_name: &schema_info.file_name,
+                    rename_all: schema_info.rename_all.as_ref(),
+                    unknown_keys: schema_info.unknown_keys,
+                    references: references.iter().map(Uuid::to_string).collect(),
+                    cyclic,
+                    kind: KindModel::new(schema_info, registry)?,
+                })
+            })
+            .collect::<PluginResult<_>>()?;
+
+        Ok(Self {
+            version: PROTOCOL_VERSION,
+            target,
+            outputs,
+            schemas,
+        })
+    }
+}
+
+impl<'a> KindModel<'a> {
+    fn new(schema_info: &'a SchemaInfo, registry: &Registry) -> PluginResult<Self> {
+        let validation = schema_info.validation.as_ref();
+
+        Ok(match &schema_info.schema {
+            Schema::Class(class_schema) => Self::class(
+                schema_info,
+                class_schema,
+                validation.map(|validation| validation.class()),
+                registry,
+            )?,
+            Schema::Enum(enum_schema) => {
+                Self::enum_(enum_schema, validation.map(|validation| validation.enum_()))
+            }
+        })
+    }
+
+    fn class(
+        schema_info: &'a SchemaInfo,
+        class_schema: &'a ClassSchema,
+        validation: Option<ClassSchemaValidationInfo<'a>>,
+        registry: &Registry,
+    ) -> PluginResult<Self> {
+        let members = class_schema
+            .iter()
+            .map(|(name, member_type)| {
+                Ok(MemberModel {
+                    name,
+                    property: schema_info.property_name(name),
+                    ty: TypeModel::new(member_type, registry)?,
+                    allow_undefined: validation
+                        .as_ref()
+                        .is_some_and(|validation| validation.allow_undefined.contains(name)),
+                    aliases: aliases(
+                        validation.as_ref().map(|validation| validation.aliases),
+                        name,
+                    ),
+                    default: validation
+                        .as_ref()
+                        .and_then(|validation| validation.defaults)
+                        .and_then(|defaults| defaults.get(name))
+                        .map(|default| default.to_json()),
+                })
+            })
+            .collect::<PluginResult<_>>()?;
+
+        Ok(Self::Class { members })
+    }
+
+    fn enum_(
+        enum_schema: &'a EnumSchema,
+        validation: Option<EnumSchemaValidationInfo<'a>>,
+    ) -> Self {
+        Self::Enum {
+            variants: enum_schema
+                .iter()
+                .map(|name| VariantModel {
+                    name,
+                    aliases: aliases(
+                        validation.as_ref().map(|validation| validation.aliases),
+                        name,
+                    ),
+                })
+                .collect(),
+            default: validation.and_then(|validation| validation.default),
+        }
+    }
+}
+
+fn aliases<'a>(aliases: Option<&'a HashMap<String, Vec<String>>>, name: &String) -> &'a [String] {
+    aliases
+        .and_then(|aliases| aliases.get(name))
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+}
+
+impl TypeModel {
+    fn new(member_type: &SchemaMemberType, registry: &Registry) -> PluginResult<Self> {
+        Ok(match member_type {
+            SchemaMemberType::Num => Self::Num,
+            SchemaMemberType::Str => Self::Str,
+            SchemaMemberType::Bool => Self::Bool,
+            SchemaMemberType::Arr(arr_ty) => Self::Arr {
+                item: Box::new(Self::new(arr_ty, registry)?),
+            },
+            SchemaMemberType::Map(map_key_ty, map_val_ty) => Self::Map {
+                key: Box::new(Self::new(&map_key_ty.clone().into(), registry)?),
+                value: Box::new(Self::new(map_val_ty, registry)?),
+            },
+            SchemaMemberType::Opt(opt_ty) => Self::Opt {
+                inner: Box::new(Self::new(&opt_ty.clone().into(), registry)?),
+            },
+            SchemaMemberType::DefClass(id) => Self::DefClass {
+                id: id.to_string(),
+                qualified_mod_path: registry.get(id)?.qualified_mod_path().to_string(),
+            },
+            SchemaMemberType::DefEnum(id) => Self::DefEnum {
+                id: id.to_string(),
+                qualified_mod_path: registry.get(id)?.qualified_mod_path().to_string(),
+            },
+        })
+    }
+}
+
+pub type PluginResult<T> = Result<T, PluginError>;
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Error, Diagnostic)]
+pub enum PluginError {
+    #[error("[Plugin] {0}")]
+    Registry(#[from] RegistryError),
+    #[error("[Plugin] Couldn't start '{0}' ({1:?}): {2}")]
+    Spawn(String, PathBuf, io::Error),
+    #[error("[Plugin] [IO ({0})] {1}")]
+    IO(String, io::Error),
+    #[error("[Plugin] '{0}' failed with {1}")]
+    Failed(String, ExitStatus),
+    #[error("[Plugin] '{0}' didn't answer with a JSON array of {{path, contents}} files: {1}")]
+    InvalidOutput(String, serde_json::Error),
+    #[error("[Plugin] '{0}' wants to write {1:?}, paths have to be relative and stay within the output directory")]
+    InvalidPath(String, PathBuf),
+    #[error("[Plugin] '{0}' wants to write into the unknown root {1:?}")]
+    UnknownRoot(String, Option<String>),
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::test_utils::{registry, temp_dir};
+
+    fn schemas() -> Registry {
+        registry(&[
+            (
+                "order",
+                r#"(
+                    schema: Class({ "total": Opt(Num), "state": DefEnum("state") }),
+                    validation: Some((
+                        aliases: { "total": ["sum"] },
+                        defaults: Some(Class({ "state": "open" })),
+                    )),
+                )"#,
+            ),
+            ("state", r#"(schema: Enum(["open", "closed"]))"#),
+        ])
+        .unwrap()
+    }
+
+    fn out() -> OutputRoots {
+        OutputRoots::from_iter([(None, PathBuf::from("out"))])
+    }
+
+    /// Runs `script` as a plugin with `sh`
+    fn run(dir: &TempDir, script: &str) -> PluginResult<Vec<GeneratedFile>> {
+        let path = dir.path().join("plugin.sh");
+        fs::write(&path, script).unwrap();
+
+        Plugin {
+            name: "test",
+            command: "sh".into(),
+            args: &[path.to_string_lossy().into_owned()],
+        }
+        .run(&schemas(), &out())
+    }
+
+    #[test]
+    fn request() {
+        let registry = schemas();
+        let outputs = out();
+        let request =
+            serde_json::to_value(PluginRequest::new("test", &registry, &outputs).unwrap()).unwrap();
+        let state_id = &request["schemas"][1]["id"];
+
+        assert_eq!(request["version"], PROTOCOL_VERSION);
+        assert_eq!(request["outputs"], json!([{ "root": null, "dir": "out" }]));
+        assert_eq!(request["schemas"][0]["kind"], "class");
+        assert_eq!(request["schemas"][0]["references"], json!([state_id]));
+        assert_eq!(
+            request["schemas"][0]["members"],
+            json!([
+                {
+                    "name": "total",
+                    "property": "total",
+                    "type": { "kind": "Opt", "inner": { "kind": "Num" } },
+                    "allow_undefined": false,
+                    "aliases": ["sum"],
+                    "default": null,
+                },
+                {
+                    "name": "state",
+                    "property": "state",
+                    "type": { "kind": "DefEnum", "id": state_id, "qualified_mod_path": "state" },
+                    "allow_undefined": false,
+                    "aliases": [],
+                    "default": "open",
+                },
+            ])
+        );
+        assert_eq!(request["schemas"][1]["kind"], "enum");
+        assert_eq!(request["schemas"][1]["default"], json!(null));
+        assert_eq!(
+            request["schemas"][1]["variants"][1],
+            json!({ "name": "closed", "aliases": [] })
+        );
+    }
+
+    #[test]
+    fn runs_plugins() {
+        let dir = temp_dir();
+        let request = dir.path().join("request.json");
+        let files = run(
+            &dir,
+            &format!(
+                r#"cat > {request:?}
+echo '[{{ "path": "nested/a.txt", "contents": "a" }}]'"#
+            ),
+        )
+        .unwrap();
+
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path, Path::new("out/nested/a.txt"));
+        assert_eq!(files[0].contents, "a");
+        // The whole request reached the plugin
+        let request: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(request).unwrap()).unwrap();
+        assert_eq!(request["target"], "test");
+    }
+
+    #[test]
+    fn plugin_errors() {
+        let dir = temp_dir();
+        let run = |script: &str| run(&dir, script);
+
+        assert!(matches!(run("exit 3"), Err(PluginError::Failed(..))));
+        assert!(matches!(
+            run("echo nope"),
+            Err(PluginError::InvalidOutput(..))
+        ));
+        assert!(matches!(
+            run(r#"echo '[{ "path": "../a.txt", "contents": "" }]'"#),
+            Err(PluginError::InvalidPath(..))
+        ));
+        assert!(matches!(
+            run(r#"echo '[{ "path": "/a.txt", "contents": "" }]'"#),
+            Err(PluginError::InvalidPath(..))
+        ));
+        assert!(matches!(
+            run(r#"echo '[{ "path": "a.txt", "root": "shared", "contents": "" }]'"#),
+            Err(PluginError::UnknownRoot(_, Some(root))) if root == "shared"
+        ));
+        assert!(matches!(
+            Plugin {
+                name: "test",
+                command: dir.path().join("missing"),
+                args: &[],
+            }
+            .run(&schemas(), &out()),
+            Err(PluginError::Spawn(..))
+        ));
+    }
+}
@@ -0,0 +1,689 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    path::PathBuf,
+};
+
+use convert_case::{Case, Casing};
+use miette::Diagnostic;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::schema::{
+    info::SchemaInfo,
+    module_path::ModulePath,
+    registry::{Registry, RegistryError},
+    unknown_keys::{UnknownKeys, PASSTHROUGH_PROPERTY},
+    validation_info::{ClassSchemaValidationInfo, DefaultValue, EnumSchemaValidationInfo},
+    ClassSchema, EnumSchema, MapKeyType, Schema, SchemaMemberType,
+};
+
+use super::{
+    backend::{Backend, Imports, SchemaContext},
+    default_variant, GeneratorResult,
+};
+
+const PYTHON_RUNTIME_SRC: &str = include_str!("python/runtime.py");
+const PYTHON_RUNTIME_MODULE: &str = "_runtime";
+
+const KEYWORDS: [&str; 35] = [
+    "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class", "continue",
+    "def", "del", "elif", "else", "except", "finally", "for", "from", "global", "if", "import",
+    "in", "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return", "try", "while",
+    "with", "yield",
+];
+
+/// One module per schema with a dataclass (`from_dict`/`to_dict`) or an enum,
+/// laid out as packages following the module paths.
+/// References across roots import every named root as a top level package
+pub struct PythonBackend;
+
+impl Backend for PythonBackend {
+    fn name(&self) -> &'static str {
+        "python"
+    }
+
+    fn file(&self, schema_info: &SchemaInfo) -> PathBuf {
+        py_file(&python_module(schema_info))
+    }
+
+    fn support_files(&self, schema_files: &[PathBuf]) -> Vec<(PathBuf, String)> {
+        let packages = schema_files
+            .iter()
+            .flat_map(|schema_file| schema_file.ancestors().skip(1))
+            .map(|package| package.join("__init__.py"))
+            .collect::<BTreeSet<_>>();
+
+        [(
+            py_file(&[PYTHON_RUNTIME_MODULE.into()]),
+            PYTHON_RUNTIME_SRC.into(),
+        )]
+        .into_iter()
+        .chain(packages.into_iter().map(|package| (package, String::new())))
+        .collect()
+    }
+
+    fn class(
+        &self,
+        ctx: &SchemaContext,
+        class_schema: &ClassSchema,
+        imports: &mut Imports,
+    ) -> GeneratorResult<String> {
+        let SchemaInfo {
+            name, validation, ..
+        } = ctx.schema_info;
+
+        Ok(PythonGenerator::new(ctx, imports).class(
+            name,
+            class_schema,
+            validation.as_ref().map(|validation| validation.class()),
+        )?)
+    }
+
+    fn enum_(
+        &self,
+        ctx: &SchemaContext,
+        enum_schema: &EnumSchema,
+        imports: &mut Imports,
+    ) -> GeneratorResult<String> {
+        let SchemaInfo {
+            name, validation, ..
+        } = ctx.schema_info;
+
+        Ok(PythonGenerator::new(ctx, imports).enum_(
+            name,
+            enum_schema,
+            validation.as_ref().map(|validation| validation.enum_()),
+            default_variant(ctx.schema_info, enum_schema)?,
+        )?)
+    }
+
+    fn imports(&self, ctx: &SchemaContext, imports: &Imports) -> GeneratorResult<String> {
+        let modules = match ctx.schema_info.schema {
+            Schema::Class(_) => "import dataclasses\nimport typing",
+            Schema::Enum(_) => "import enum\nimport typing",
+        };
+        let imports = imports
+            .modules()
+            .map(|(from, names)| format!("from {from} import {}\n", names.join(", ")))
+            .collect::<String>();
+
+        Ok(format!(
+            "from __future__ import annotations\n\n{modules}\n\n{imports}\n\n"
+        ))
+    }
+}
+
+/// Snake cased module path of a schema within its root
+fn python_module(schema_info: &SchemaInfo) -> Vec<String> {
+    schema_info
+        .mod_path
+        .parents()
+        .iter()
+        .chain([&schema_info.file_name])
+        .map(|component| identifier(component, Case::Snake))
+        .collect()
+}
+
+fn py_file(module: &[String]) -> PathBuf {
+    let mut path = module.iter().collect::<PathBuf>();
+    path.set_extension("py");
+
+    path
+}
+
+/// Python identifier in the given case, with a trailing `_` if it's a keyword
+fn identifier(name: &str, case: Case) -> String {
+    let ident = name.to_case(case);
+
+    match ident.starts_with(|char: char| char.is_ascii_digit()) || ident.is_empty() {
+        true => format!("_{ident}"),
+        false if KEYWORDS.contains(&ident.as_str()) => format!("{ident}_"),
+        false => ident,
+    }
+}
+
+/// Fails when two names end up with the same identifier
+fn ensure_distinct_idents<'n>(
+    mod_path: &ModulePath,
+    names: impl IntoIterator<Item = &'n str>,
+    ident: fn(&str) -> String,
+) -> PythonGeneratorResult<()> {
+    let mut idents = HashMap::<String, &str>::new();
+
+    for name in names {
+        let name_ident = ident(name);
+
+        if let Some(other) = idents.insert(name_ident.clone(), name) {
+            return Err(PythonGeneratorError::IdentClash(
+                mod_path.clone(),
+                name_ident,
+                other.into(),
+                name.into(),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+fn field_ident(field_name: &str) -> String {
+    identifier(field_name, Case::Snake)
+}
+
+fn default_fn_name(field_name: &str) -> String {
+    format!(
+        "_default_{}",
+        identifier(field_name, Case::Snake).trim_start_matches('_')
+    )
+}
+
+fn num_literal(num: f64) -> String {
+    match num {
+        num if num.is_nan() => "float(\"nan\")".into(),
+        num if num.is_infinite() => format!("float(\"{num}\")"),
+        num if num.fract() == 0.0 && num.abs() < 1e15 => format!("{}", num as i64),
+        num => format!("{num:?}"),
+    }
+}
+
+struct PythonGenerator<'a, 'c> {
+    schema_info: &'a SchemaInfo,
+    module: Vec<String>,
+    registry: &'a Registry,
+    /// Modules imported from packages
+    imports: &'c mut Imports,
+}
+
+impl<'a, 'c> PythonGenerator<'a, 'c> {
+    fn new(ctx: &SchemaContext<'a>, imports: &'c mut Imports) -> Self {
+        let module = python_module(ctx.schema_info);
+
+        imports.add(".".repeat(module.len()), PYTHON_RUNTIME_MODULE);
+
+        Self {
+            schema_info: ctx.schema_info,
+            module,
+            registry: ctx.registry,
+            imports,
+        }
+    }
+
+    fn class(
+        &mut self,
+        name: &str,
+        class_schema: &ClassSchema,
+        validation: Option<ClassSchemaValidationInfo>,
+    ) -> PythonGeneratorResult<String> {
+        let mut default_fns = String::new();
+        let mut fields = String::new();
+        let mut arguments = String::new();
+        let mut serialized_fields = String::new();
+        let mut known_keys = Vec::with_capacity(class_schema.len());
+
+        let passthrough = (self.schema_info.unknown_keys == UnknownKeys::Passthrough)
+            .then_some(PASSTHROUGH_PROPERTY);
+        ensure_distinct_idents(
+            &self.schema_info.qualified_mod_path(),
+            class_schema.keys().map(String::as_str).chain(passthrough),
+            field_ident,
+        )?;
+
+        for (field_name, field_ty) in class_schema.iter() {
+            let (ty, is_opt) = match field_ty {
+                SchemaMemberType::Opt(opt_ty) => (opt_ty.clone().into(), true),
+                field_ty => (field_ty.clone(), false),
+            };
+            let default = validation
+                .as_ref()
+                .and_then(|ClassSchemaValidationInfo { defaults, .. }| defaults.as_ref())
+                .and_then(|defaults| defaults.get(field_name));
+            let allow_undefined = validation.as_ref().is_some_and(
+                |ClassSchemaValidationInfo {
+                     allow_undefined, ..
+                 }| allow_undefined.contains(field_name),
+            );
+            let aliases = validation
+                .as_ref()
+                .and_then(|ClassSchemaValidationInfo { aliases, .. }| aliases.get(field_name));
+
+            let ident = field_ident(field_name);
+            let type_hint = self.type_hint(&ty)?;
+            let is_none_able = is_opt || (allow_undefined && default.is_none());
+
+            let (type_hint, field_default) = match (default, is_none_able) {
+                (Some(default), _) => {
+                    let default_fn = default_fn_name(field_name);
+
+                    default_fns += &format!(
+                        "def {default_fn}() -> {type_hint}:\n    return {}\n\n\n",
+                        self.literal(default)?
+                    );
+
+                    let type_hint = match is_opt {
+                        true => format!("typing.Optional[{type_hint}]"),
+                        false => type_hint,
+                    };
+
+                    (
+                        type_hint,
+                        format!(" = dataclasses.field(default_factory={default_fn})"),
+                    )
+                }
+                (None, true) => (format!("typing.Optional[{type_hint}]"), " = None".into()),
+                (None, false) => (type_hint, String::new()),
+            };
+
+            fields += &format!("    {ident}: {type_hint}{field_default}\n");
+
+            known_keys.extend(
+                [field_name]
+                    .into_iter()
+                    .chain(aliases.into_iter().flatten())
+                    .map(quoted),
+            );
+
+            let input = match aliases {
+                Some(aliases) => format!(
+                    "_runtime.pick(obj, [{}])",
+                    [field_name]
+                        .into_iter()
+                        .chain(aliases)
+                        .map(quoted)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+                None => format!("obj.get({}, _runtime.MISSING)", quoted(field_name)),
+            };
+            let path = format!("path + {}", quoted(format!(".{field_name}")));
+
+            let deserialized = match (is_opt, allow_undefined || default.is_some()) {
+                (false, true) => format!(
+                    "_runtime.expect_maybe({input}, {path}, {})",
+                    self.deserializer(&ty)?
+                ),
+                _ => self.deserializer_call(field_ty, &input, &path)?,
+            };
+            let deserialized = match default {
+                Some(_) => format!(
+                    "_runtime.or_else({deserialized}, {})",
+                    default_fn_name(field_name)
+                ),
+                None => deserialized,
+            };
+
+            arguments += &format!("            {ident}={deserialized},\n");
+
+            let serialized = format!(
+                "obj[{}] = _runtime.serialize(self.{ident})",
+                quoted(field_name)
+            );
+
+            serialized_fields += &match is_none_able {
+                true => format!("        if self.{ident} is not None:\n            {serialized}\n"),
+                false => format!("        {serialized}\n"),
+            };
+        }
+
+        let known_keys = format!("[{}]", known_keys.join(", "));
+        let unknown_keys = self.schema_info.unknown_keys;
+
+        if unknown_keys == UnknownKeys::Passthrough {
+            fields += &format!(
+                "    {PASSTHROUGH_PROPERTY}: dict[str, typing.Any] = dataclasses.field(default_factory=dict)\n"
+            );
+            arguments += &format!(
+                "            {PASSTHROUGH_PROPERTY}=_runtime.collect_unknown_keys(obj, {known_keys}),\n"
+            );
+        }
+
+        let fields = match fields.is_empty() {
+            true => "    pass\n".into(),
+            false => fields,
+        };
+
+        let strict = match unknown_keys {
+            UnknownKeys::Strict => {
+                format!("        _runtime.expect_no_unknown_keys(obj, path, {known_keys})\n")
+            }
+            _ => String::new(),
+        };
+        let construction = match arguments.is_empty() {
+            true => "        return cls()\n".into(),
+            false => format!("        return cls(\n{arguments}        )\n"),
+        };
+        let obj = match unknown_keys {
+            UnknownKeys::Passthrough => format!("dict(self.{PASSTHROUGH_PROPERTY})"),
+            _ => "{}".into(),
+        };
+
+        Ok(format!(
+            "{default_fns}@dataclasses.dataclass(kw_only=True)
+class {name}:
+{fields}
+    @classmethod
+    def from_dict(cls, value: typing.Any, path: str = {path}) -> {name}:
+        obj = _runtime.expect_obj(value, path)
+{strict}
+{construction}
+    def to_dict(self) -> dict[str, typing.Any]:
+        obj: dict[str, typing.Any] = {obj}
+{serialized_fields}
+        return obj
+",
+            path = quoted(name)
+        ))
+    }
+
+    fn enum_(
+        &mut self,
+        name: &str,
+        variants: &EnumSchema,
+        validation: Option<EnumSchemaValidationInfo>,
+        default: &str,
+    ) -> PythonGeneratorResult<String> {
+        ensure_distinct_idents(
+            &self.schema_info.qualified_mod_path(),
+            variants.iter().map(String::as_str),
+            member_ident,
+        )?;
+
+        let members = variants
+            .iter()
+            .map(|variant| format!("    {} = {}\n", member_ident(variant), quoted(variant)))
+            .collect::<String>();
+
+        let aliases = variants
+            .iter()
+            .flat_map(|variant| {
+                validation
+                    .as_ref()
+                    .and_then(|EnumSchemaValidationInfo { aliases, .. }| aliases.get(variant))
+                    .into_iter()
+                    .flatten()
+                    .map(move |alias| format!("{}: cls.{}", quoted(alias), member_ident(variant)))
+            })
+            .collect::<Vec<_>>();
+
+        let missing = match aliases.is_empty() {
+            true => String::new(),
+            false => format!(
+                "
+    @classmethod
+    def _missing_(cls, value: object) -> typing.Optional[{name}]:
+        return {{{}}}.get(value)
+",
+                aliases.join(", ")
+            ),
+        };
+
+        Ok(format!(
+            "class {name}(enum.Enum):
+{members}{missing}
+    @classmethod
+    def from_value(cls, value: typing.Any, path: str = {path}) -> {name}:
+        return _runtime.expect_enum(value, path, cls)
+
+
+{default_name} = {name}.{default_member}
+",
+            path = quoted(name),
+            default_name = name.to_case(Case::UpperSnake) + "_DEFAULT",
+            default_member = member_ident(default),
+        ))
+    }
+
+    fn type_hint(
+        &mut self,
+        schema_member_type: &SchemaMemberType,
+    ) -> PythonGeneratorResult<String> {
+        Ok(match schema_member_type {
+            SchemaMemberType::Num => "float".into(),
+            SchemaMemberType::Str => "str".into(),
+            SchemaMemberType::Bool => "bool".into(),
+            SchemaMemberType::Arr(arr_ty) => format!("list[{}]", self.type_hint(arr_ty)?),
+            SchemaMemberType::Map(map_key_ty, map_val_ty) => format!(
+                "dict[{}, {}]",
+                self.type_hint(&map_key_ty.clone().into())?,
+                self.type_hint(map_val_ty)?
+            ),
+            SchemaMemberType::Opt(opt_ty) => {
+                format!(
+                    "typing.Optional[{}]",
+                    self.type_hint(&opt_ty.clone().into())?
+                )
+            }
+            SchemaMemberType::DefClass(id) | SchemaMemberType::DefEnum(id) => self.reference(id)?,
+        })
+    }
+
+    /// Expression deserializing `value` at `path` into the member type
+    fn deserializer_call(
+        &mut self,
+        schema_member_type: &SchemaMemberType,
+        value: &str,
+        path: &str,
+    ) -> PythonGeneratorResult<String> {
+        Ok(match schema_member_type {
+            SchemaMemberType::Arr(arr_ty) => format!(
+                "_runtime.expect_arr({value}, {path}, {})",
+                self.deserializer(arr_ty)?
+            ),
+            SchemaMemberType::Map(map_key_ty, map_val_ty) => format!(
+                "_runtime.expect_map({value}, {path}, {}, {})",
+                match map_key_ty {
+                    MapKeyType::Num => "_runtime.expect_num_key".into(),
+                    map_key_ty => self.deserializer(&map_key_ty.clone().into())?,
+                },
+                self.deserializer(map_val_ty)?
+            ),
+            SchemaMemberType::Opt(opt_ty) => format!(
+                "_runtime.expect_opt({value}, {path}, {})",
+                self.deserializer(&opt_ty.clone().into())?
+            ),
+            schema_member_type => format!(
+                "{}({value}, {path})",
+                self.deserializer(schema_member_type)?
+            ),
+        })
+    }
+
+    /// Function deserializing into the member type, suitable for `_runtime.Deserializer`
+    fn deserializer(
+        &mut self,
+        schema_member_type: &SchemaMemberType,
+    ) -> PythonGeneratorResult<String> {
+        Ok(match schema_member_type {
+            SchemaMemberType::Num => "_runtime.expect_num".into(),
+            SchemaMemberType::Str => "_runtime.expect_str".into(),
+            SchemaMemberType::Bool => "_runtime.expect_bool".into(),
+            SchemaMemberType::DefClass(id) => format!("{}.from_dict", self.reference(id)?),
+            SchemaMemberType::DefEnum(id) => format!("{}.from_value", self.reference(id)?),
+            schema_member_type => format!(
+                "lambda value, path: {}",
+                self.deserializer_call(schema_member_type, "value", "path")?
+            ),
+        })
+    }
+
+    fn literal(&mut self, default: &DefaultValue) -> PythonGeneratorResult<String> {
+        Ok(match default {
+            DefaultValue::Num(num) => num_literal(*num),
+            DefaultValue::Str(str) => quoted(str),
+            DefaultValue::Bool(true) => "True".into(),
+            DefaultValue::Bool(false) => "False".into(),
+            DefaultValue::Arr(items) => format!(
+                "[{}]",
+                items
+                    .iter()
+                    .map(|item| self.literal(item))
+                    .collect::<PythonGeneratorResult<Vec<_>>>()?
+                    .join(", ")
+            ),
+            DefaultValue::Map(entries) => format!(
+                "{{{}}}",
+                entries
+                    .iter()
+                    .map(|(key, val)| Ok(format!("{}: {}", self.literal(key)?, self.literal(val)?)))
+                    .collect::<PythonGeneratorResult<Vec<_>>>()?
+                    .join(", ")
+            ),
+            DefaultValue::DefClass(id, fields) => format!(
+                "{}({})",
+                self.reference(id)?,
+                fields
+                    .iter()
+                    .map(|(field_name, val)| Ok(format!(
+                        "{}={}",
+                        field_ident(field_name),
+                        self.literal(val)?
+                    )))
+                    .collect::<PythonGeneratorResult<Vec<_>>>()?
+                    .join(", ")
+            ),
+            DefaultValue::DefEnum(id, variant) => {
+                format!("{}.{}", self.reference(id)?, member_ident(variant))
+            }
+        })
+    }
+
+    /// Name a referenced schema is reachable by, importing its module if it's another one.
+    /// Modules are imported rather than names so cyclic references resolve at call time
+    fn reference(&mut self, id: &Uuid) -> PythonGeneratorResult<String> {
+        let schema_info = self.registry.get(id)?;
+        let module = python_module(schema_info);
+
+        if schema_info.root == self.schema_info.root && module == self.module {
+            return Ok(schema_info.name.clone());
+        }
+
+        let (alias, from) = match &schema_info.root {
+            root if *root == self.schema_info.root => {
+                let from_dir = &self.module[..self.module.len() - 1];
+                let common = from_dir
+                    .iter()
+                    .zip(&module[..module.len() - 1])
+                    .take_while(|(from, to)| from == to)
+                    .count();
+
+                (
+                    module.join("_"),
+                    format!(
+                        "{}{}",
+                        ".".repeat(from_dir.len() - common + 1),
+                        module[common..module.len() - 1].join(".")
+                    ),
+                )
+            }
+            Some(root) => {
+                let root = identifier(root, Case::Snake);
+
+                (
+                    format!("{root}_{}", module.join("_")),
+                    [root]
+                        .iter()
+                        .chain(&module[..module.len() - 1])
+                        .cloned()
+                        .collect::<Vec<_>>()
+                        .join("."),
+                )
+            }
+            None => {
+                return Err(PythonGeneratorError::UnnamedRoot(
+                    schema_info.qualified_mod_path(),
+                ))
+            }
+        };
+
+        let last = &module[module.len() - 1];
+
+        self.imports.add(
+            from,
+            match alias == *last {
+                true => last.clone(),
+                false => format!("{last} as {alias}"),
+            },
+        );
+
+        Ok(format!("{alias}.{}", schema_info.name))
+    }
+}
+
+/// String literal with all the necessary escaping, JSON's being valid in Python as well
+fn quoted(str: impl AsRef<str>) -> String {
+    serde_json::to_string(str.as_ref()).unwrap_or_default()
+}
+
+fn member_ident(variant: &str) -> String {
+    identifier(variant, Case::UpperSnake)
+}
+
+pub type PythonGeneratorResult<T> = Result<T, PythonGeneratorError>;
+
+#[derive(Debug, Error, Diagnostic)]
+pub enum PythonGeneratorError {
+    #[error("[Python] {0}")]
+    Registry(#[from] RegistryError),
+    #[error("[Python] '{0}' lives in the unnamed root, which has no package to be imported from")]
+    UnnamedRoot(ModulePath),
+    #[error("[Python] Names '{2}' and '{3}' of '{0}' both become the identifier '{1}'")]
+    IdentClash(ModulePath, String, String, String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{generator::GeneratorError, test_utils::generated};
+
+    fn python(schema: &str) -> GeneratorResult<String> {
+        generated(&PythonBackend, &[("thing", schema)], "thing")
+    }
+
+    fn python_error(schema: &str) -> PythonGeneratorError {
+        match python(schema) {
+            Err(GeneratorError::Python(error)) => error,
+            other => panic!("expected a Python generator error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn rejects_clashing_identifiers() {
+        assert!(matches!(
+            python_error(r#"(schema: Class({ "fooBar": Num, "foo_bar": Num }))"#),
+            PythonGeneratorError::IdentClash(_, ident, ..) if ident == "foo_bar"
+        ));
+        assert!(matches!(
+            python_error(r#"(schema: Class({ "class": Num, "class_": Num }))"#),
+            PythonGeneratorError::IdentClash(_, ident, ..) if ident == "class_"
+        ));
+        assert!(matches!(
+            python_error(r#"(schema: Class({ "Extra": Num }), unknown_keys: Some("passthrough"))"#),
+            PythonGeneratorError::IdentClash(..)
+        ));
+        assert!(matches!(
+            python_error(r#"(schema: Enum(["in-progress", "in_progress"]))"#),
+            PythonGeneratorError::IdentClash(_, ident, ..) if ident == "IN_PROGRESS"
+        ));
+    }
+
+    #[test]
+    fn default_map_keys() {
+        let contents = python(
+            r#"(
+                schema: Class({ "counts": Map(Num, Bool) }),
+                validation: Some((defaults: Some(Class({ "counts": { "1.5": true, 2: false } })))),
+            )"#,
+        )
+        .unwrap();
+
+        assert!(contents.contains("return {1.5: True, 2: False}"));
+    }
+
+    #[test]
+    fn num_literals() {
+        assert_eq!(num_literal(2.0), "2");
+        assert_eq!(num_literal(-0.5), "-0.5");
+        assert_eq!(num_literal(1e20), "1e20");
+        assert_eq!(num_literal(f64::NAN), "float(\"nan\")");
+        assert_eq!(num_literal(f64::NEG_INFINITY), "float(\"-inf\")");
+    }
+}
@@ -0,0 +1,185 @@
+import enum
+import json
+import re
+import typing
+
+T = typing.TypeVar("T")
+K = typing.TypeVar("K")
+V = typing.TypeVar("V")
+
+Deserializer = typing.Callable[[typing.Any, str], T]
+
+# Keys of numeric maps as they appear on the wire, decimal numbers with an optional exponent
+_NUM_KEY = re.compile(r"^[+-]?([0-9]+(\.[0-9]*)?|\.[0-9]+)([eE][+-]?[0-9]+)?\Z")
+
+
+class Missing:
+    """Stands for keys absent from the input, as opposed to `null` ones"""
+
+    def __repr__(self) -> str:
+        return "MISSING"
+
+
+MISSING = Missing()
+
+
+class DeserializationError(ValueError):
+    def __init__(self, path: str, message: str) -> None:
+        super().__init__(f"{path}: {message}")
+        self.path = path
+
+
+def _describe(value: typing.Any) -> str:
+    if value is None:
+        return "null"
+
+    if value is MISSING:
+        return "undefined"
+
+    if isinstance(value, bool):
+        return "boolean"
+
+    if isinstance(value, (int, float)):
+        return "number"
+
+    if isinstance(value, str):
+        return "string"
+
+    if isinstance(value, list):
+        return "array"
+
+    if isinstance(value, dict):
+        return "object"
+
+    return type(value).__name__
+
+
+def _mismatch(path: str, expected: str, value: typing.Any) -> DeserializationError:
+    return DeserializationError(path, f"expected {expected}, found {_describe(value)}")
+
+
+def expect_num(value: typing.Any, path: str) -> float:
+    if isinstance(value, bool) or not isinstance(value, (int, float)):
+        raise _mismatch(path, "number", value)
+
+    return value
+
+
+def expect_str(value: typing.Any, path: str) -> str:
+    if not isinstance(value, str):
+        raise _mismatch(path, "string", value)
+
+    return value
+
+
+def expect_bool(value: typing.Any, path: str) -> bool:
+    if not isinstance(value, bool):
+        raise _mismatch(path, "boolean", value)
+
+    return value
+
+
+def expect_obj(value: typing.Any, path: str) -> dict[str, typing.Any]:
+    if not isinstance(value, dict):
+        raise _mismatch(path, "object", value)
+
+    return value
+
+
+def expect_arr(value: typing.Any, path: str, item: Deserializer[T]) -> list[T]:
+    if not isinstance(value, list):
+        raise _mismatch(path, "array", value)
+
+    return [item(value, f"{path}[{index}]") for index, value in enumerate(value)]
+
+
+def expect_map(
+    value: typing.Any,
+    path: str,
+    key: Deserializer[K],
+    val: Deserializer[V],
+) -> dict[K, V]:
+    obj = expect_obj(value, path)
+    result: dict[K, V] = {}
+
+    for obj_key, obj_val in obj.items():
+        entry_path = f"{path}[{json.dumps(obj_key)}]"
+        result[key(obj_key, entry_path)] = val(obj_val, entry_path)
+
+    return result
+
+
+def expect_num_key(value: typing.Any, path: str) -> float:
+    if not isinstance(value, str) or not _NUM_KEY.match(value):
+        raise _mismatch(path, "numeric key", value)
+
+    return float(value)
+
+
+def expect_enum(value: typing.Any, path: str, enum_type: type[T]) -> T:
+    try:
+        return enum_type(expect_str(value, path))
+    except ValueError:
+        raise DeserializationError(path, f"unknown variant {json.dumps(value)}") from None
+
+
+def expect_opt(value: typing.Any, path: str, inner: Deserializer[T]) -> typing.Optional[T]:
+    return None if value is MISSING or value is None else inner(value, path)
+
+
+def expect_maybe(value: typing.Any, path: str, inner: Deserializer[T]) -> typing.Optional[T]:
+    return None if value is MISSING else inner(value, path)
+
+
+def or_else(value: typing.Optional[T], default: typing.Callable[[], T]) -> T:
+    return default() if value is None else value
+
+
+def pick(obj: dict[str, typing.Any], keys: list[str]) -> typing.Any:
+    for key in keys:
+        if obj.get(key, MISSING) is not MISSING:
+            return obj[key]
+
+    return MISSING
+
+
+def _unknown_keys(obj: dict[str, typing.Any], known: list[str]) -> list[str]:
+    return [key for key in obj if key not in known]
+
+
+def expect_no_unknown_keys(obj: dict[str, typing.Any], path: str, known: list[str]) -> None:
+    unknown = _unknown_keys(obj, known)
+
+    if unknown:
+        raise DeserializationError(path, f"unknown keys {', '.join(json.dumps(key) for key in unknown)}")
+
+
+def collect_unknown_keys(obj: dict[str, typing.Any], known: list[str]) -> dict[str, typing.Any]:
+    return {key: obj[key] for key in _unknown_keys(obj, known)}
+
+
+def _serialize_key(key: typing.Any) -> str:
+    if isinstance(key, enum.Enum):
+        return key.value
+
+    if isinstance(key, float) and key.is_integer():
+        return str(int(key))
+
+    return str(key)
+
+
+def serialize(value: typing.Any) -> typing.Any:
+    """Wire format of any generated value"""
+    if isinstance(value, enum.Enum):
+        return value.value
+
+    if hasattr(value, "to_dict"):
+        return value.to_dict()
+
+    if isinstance(value, list):
+        return [serialize(item) for item in value]
+
+    if isinstance(value, dict):
+        return {_serialize_key(key): serialize(val) for key, val in value.items()}
+
+    return value
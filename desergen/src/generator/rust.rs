@@ -0,0 +1,673 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    path::PathBuf,
+};
+
+use convert_case::{Case, Casing};
+use miette::Diagnostic;
+use serde::Deserialize;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::schema::{
+    info::SchemaInfo,
+    module_path::ModulePath,
+    registry::{Registry, RegistryError},
+    unknown_keys::{UnknownKeys, PASSTHROUGH_PROPERTY},
+    validation_info::{ClassSchemaValidationInfo, DefaultValue, EnumSchemaValidationInfo},
+    ClassSchema, EnumSchema, MapKeyType, Schema, SchemaMemberType,
+};
+
+use super::{
+    backend::{Backend, Imports, SchemaContext},
+    default_variant, GeneratorResult,
+};
+
+/// Settings of the `rust` target
+#[derive(Debug, Default, Clone, Deserialize)]
+pub struct RustOptions {
+    #[serde(default)]
+    pub map: RustMap,
+}
+
+/// Collection type of `Map` members
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+pub enum RustMap {
+    #[default]
+    HashMap,
+    BTreeMap,
+}
+
+impl RustMap {
+    fn name(self) -> &'static str {
+        match self {
+            RustMap::HashMap => "HashMap",
+            RustMap::BTreeMap => "BTreeMap",
+        }
+    }
+}
+
+const KEYWORDS: [&str; 51] = [
+    "as", "break", "const", "continue", "crate", "else", "enum", "extern", "false", "fn", "for",
+    "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return",
+    "self", "Self", "static", "struct", "super", "trait", "true", "type", "unsafe", "use", "where",
+    "while", "async", "await", "dyn", "abstract", "become", "box", "do", "final", "macro",
+    "override", "priv", "typeof", "unsized", "virtual", "yield", "try",
+];
+
+/// One module per schema with serde-derived structs and enums, and a `mod.rs`
+/// declaring the modules of every directory.
+/// References across roots expect every named root to be mounted as `crate::<root>`
+pub struct RustBackend {
+    pub options: RustOptions,
+}
+
+impl Backend for RustBackend {
+    fn name(&self) -> &'static str {
+        "rust"
+    }
+
+    fn file(&self, schema_info: &SchemaInfo) -> PathBuf {
+        rs_file(&rust_module(schema_info))
+    }
+
+    fn support_files(&self, schema_files: &[PathBuf]) -> Vec<(PathBuf, String)> {
+        // Module directory -> declared submodules
+        let mut mod_dirs = BTreeMap::<Vec<String>, BTreeSet<String>>::new();
+
+        for schema_file in schema_files {
+            let module = schema_file
+                .with_extension("")
+                .iter()
+                .map(|component| component.to_string_lossy().into_owned())
+                .collect::<Vec<_>>();
+
+            for depth in 0..module.len() {
+                mod_dirs
+                    .entry(module[..depth].to_vec())
+                    .or_default()
+                    .insert(module[depth].clone());
+            }
+        }
+
+        mod_dirs
+            .into_iter()
+            .map(|(module, submodules)| {
+                (
+                    rs_file(&[module, vec!["mod".into()]].concat()),
+                    submodules
+                        .iter()
+                        .map(|submodule| format!("pub mod {};\n", escape_keyword(submodule)))
+                        .collect(),
+                )
+            })
+            .collect()
+    }
+
+    fn class(
+        &self,
+        ctx: &SchemaContext,
+        class_schema: &ClassSchema,
+        imports: &mut Imports,
+    ) -> GeneratorResult<String> {
+        let SchemaInfo {
+            name, validation, ..
+        } = ctx.schema_info;
+
+        Ok(RustGenerator::new(ctx, &self.options, imports).class(
+            name,
+            class_schema,
+            validation.as_ref().map(|validation| validation.class()),
+        )?)
+    }
+
+    fn enum_(
+        &self,
+        ctx: &SchemaContext,
+        enum_schema: &EnumSchema,
+        imports: &mut Imports,
+    ) -> GeneratorResult<String> {
+        let SchemaInfo {
+            name, validation, ..
+        } = ctx.schema_info;
+
+        Ok(RustGenerator::new(ctx, &self.options, imports).enum_(
+            name,
+            enum_schema,
+            validation.as_ref().map(|validation| validation.enum_()),
+            default_variant(ctx.schema_info, enum_schema)?,
+        )?)
+    }
+
+    fn imports(&self, _ctx: &SchemaContext, imports: &Imports) -> GeneratorResult<String> {
+        let uses = imports
+            .modules()
+            .map(|(from, names)| match names.as_slice() {
+                [name] => format!("use {from}::{name};\n"),
+                names => format!("use {from}::{{{}}};\n", names.join(", ")),
+            })
+            .collect::<String>();
+
+        Ok(uses + "\n")
+    }
+}
+
+/// Snake cased module path of a schema within its root
+fn rust_module(schema_info: &SchemaInfo) -> Vec<String> {
+    schema_info
+        .mod_path
+        .parents()
+        .iter()
+        .chain([&schema_info.file_name])
+        .map(|component| identifier(component, Case::Snake))
+        .collect()
+}
+
+fn rs_file(module: &[String]) -> PathBuf {
+    let mut path = module.iter().collect::<PathBuf>();
+    path.set_extension("rs");
+
+    path
+}
+
+/// Rust identifier in the given case, escaped if it's a keyword
+fn identifier(name: &str, case: Case) -> String {
+    let ident = name.to_case(case);
+
+    match ident.starts_with(|char: char| char.is_ascii_digit()) || ident.is_empty() {
+        true => format!("_{ident}"),
+        false => ident,
+    }
+}
+
+fn escape_keyword(ident: &str) -> String {
+    match KEYWORDS.contains(&ident) {
+        // `self`, `super`, `crate` and `Self` can't be raw identifiers
+        true if matches!(ident, "self" | "super" | "crate" | "Self") => format!("{ident}_"),
+        true => format!("r#{ident}"),
+        false => ident.into(),
+    }
+}
+
+pub fn field_ident(field_name: &str) -> String {
+    escape_keyword(&identifier(field_name, Case::Snake))
+}
+
+pub fn variant_ident(variant: &str) -> String {
+    escape_keyword(&identifier(variant, Case::Pascal))
+}
+
+/// Fails when two names end up with the same identifier
+fn ensure_distinct_idents<'n>(
+    mod_path: &ModulePath,
+    names: impl IntoIterator<Item = &'n str>,
+    ident: fn(&str) -> String,
+) -> RustGeneratorResult<()> {
+    let mut idents = HashMap::<String, &str>::new();
+
+    for name in names {
+        let name_ident = ident(name);
+
+        if let Some(other) = idents.insert(name_ident.clone(), name) {
+            return Err(RustGeneratorError::IdentClash(
+                mod_path.clone(),
+                name_ident,
+                other.into(),
+                name.into(),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+fn default_fn_name(field_name: &str) -> String {
+    format!("default_{}", identifier(field_name, Case::Snake))
+}
+
+struct RustGenerator<'a, 'c> {
+    ctx: &'c SchemaContext<'a>,
+    schema_info: &'a SchemaInfo,
+    module: Vec<String>,
+    registry: &'a Registry,
+    options: &'c RustOptions,
+    /// Names brought into scope with `use`
+    imports: &'c mut Imports,
+}
+
+impl<'a, 'c> RustGenerator<'a, 'c> {
+    fn new(ctx: &'c SchemaContext<'a>, options: &'c RustOptions, imports: &'c mut Imports) -> Self {
+        imports.add("serde", "Deserialize");
+        imports.add("serde", "Serialize");
+
+        Self {
+            ctx,
+            schema_info: ctx.schema_info,
+            module: rust_module(ctx.schema_info),
+            registry: ctx.registry,
+            options,
+            imports,
+        }
+    }
+
+    fn class(
+        &mut self,
+        name: &str,
+        class_schema: &ClassSchema,
+        validation: Option<ClassSchemaValidationInfo>,
+    ) -> RustGeneratorResult<String> {
+        let mut fields = String::new();
+        let mut default_fns = String::new();
+
+        let passthrough = (self.schema_info.unknown_keys == UnknownKeys::Passthrough)
+            .then_some(PASSTHROUGH_PROPERTY);
+        ensure_distinct_idents(
+            &self.schema_info.qualified_mod_path(),
+            class_schema.keys().map(String::as_str).chain(passthrough),
+            field_ident,
+        )?;
+
+        for (field_name, field_ty) in class_schema.iter() {
+            let default = validation
+                .as_ref()
+                .and_then(|ClassSchemaValidationInfo { defaults, .. }| defaults.as_ref())
+                .and_then(|defaults| defaults.get(field_name));
+            let allow_undefined = validation.as_ref().is_some_and(
+                |ClassSchemaValidationInfo {
+                     allow_undefined, ..
+                 }| allow_undefined.contains(field_name),
+            );
+            let aliases = validation
+                .as_ref()
+                .and_then(|ClassSchemaValidationInfo { aliases, .. }| aliases.get(field_name));
+
+            let is_opt = matches!(field_ty, SchemaMemberType::Opt(_));
+            let field_ty = match allow_undefined && !is_opt && default.is_none() {
+                true => SchemaMemberType::Opt(field_ty.clone().try_into()?),
+                false => field_ty.clone(),
+            };
+
+            let ident = field_ident(field_name);
+            let mut attrs = Vec::new();
+
+            if ident.trim_start_matches("r#") != field_name {
+                attrs.push(format!("rename = {field_name:?}"));
+            }
+
+            for alias in aliases.into_iter().flatten() {
+                attrs.push(format!("alias = {alias:?}"));
+            }
+
+            let ty = self.ty(&field_ty, true)?;
+
+            if let Some(default) = default {
+                let default_fn = default_fn_name(field_name);
+
+                attrs.push(format!("default = {default_fn:?}"));
+                default_fns += &format!(
+                    "\nfn {default_fn}() -> {ty} {{\n    {}\n}}\n",
+                    self.literal(&field_ty, default)?
+                );
+            } else if allow_undefined && !is_opt {
+                attrs.push("default, skip_serializing_if = \"Option::is_none\"".into());
+            }
+
+            if !attrs.is_empty() {
+                fields += &format!("    #[serde({})]\n", attrs.join(", "));
+            }
+
+            fields += &format!("    pub {ident}: {ty},\n");
+        }
+
+        let mut serde_attrs = String::new();
+
+        match self.schema_info.unknown_keys {
+            UnknownKeys::Strict => serde_attrs += "#[serde(deny_unknown_fields)]\n",
+            UnknownKeys::Strip => {}
+            UnknownKeys::Passthrough => {
+                fields += &format!(
+                    "    #[serde(flatten)]\n    pub {PASSTHROUGH_PROPERTY}: serde_json::Map<String, serde_json::Value>,\n"
+                );
+            }
+        }
+
+        Ok(format!(
+            "#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]\n{serde_attrs}pub struct {name} {{\n{fields}}}\n{default_fns}"
+        ))
+    }
+
+    fn enum_(
+        &mut self,
+        name: &str,
+        variants: &EnumSchema,
+        validation: Option<EnumSchemaValidationInfo>,
+        default: &str,
+    ) -> RustGeneratorResult<String> {
+        ensure_distinct_idents(
+            &self.schema_info.qualified_mod_path(),
+            variants.iter().map(String::as_str),
+            variant_ident,
+        )?;
+
+        let variants = variants
+            .iter()
+            .map(|variant| {
+                let ident = variant_ident(variant);
+                let mut attrs = Vec::new();
+
+                if ident.trim_start_matches("r#") != variant {
+                    attrs.push(format!("rename = {variant:?}"));
+                }
+
+                for alias in validation
+                    .as_ref()
+                    .and_then(|EnumSchemaValidationInfo { aliases, .. }| aliases.get(variant))
+                    .into_iter()
+                    .flatten()
+                {
+                    attrs.push(format!("alias = {alias:?}"));
+                }
+
+                let mut lines = String::new();
+
+                if variant == default {
+                    lines += "    #[default]\n";
+                }
+
+                if !attrs.is_empty() {
+                    lines += &format!("    #[serde({})]\n", attrs.join(", "));
+                }
+
+                lines + &format!("    {ident},\n")
+            })
+            .collect::<String>();
+
+        Ok(format!(
+            "#[derive(\n    Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default, Serialize, Deserialize,\n)]\npub enum {name} {{\n{variants}}}\n"
+        ))
+    }
+
+    /// Rust type of the member, `boxed` where a reference back to this schema would
+    /// make the type infinitely sized
+    fn ty(
+        &mut self,
+        schema_member_type: &SchemaMemberType,
+        boxed: bool,
+    ) -> RustGeneratorResult<String> {
+        Ok(match schema_member_type {
+            SchemaMemberType::Num => "f64".into(),
+            SchemaMemberType::Str => "String".into(),
+            SchemaMemberType::Bool => "bool".into(),
+            SchemaMemberType::Arr(arr_ty) => format!("Vec<{}>", self.ty(arr_ty, false)?),
+            SchemaMemberType::Map(map_key_ty, map_val_ty) => {
+                let map = self.options.map.name();
+                self.imports.add("std::collections", map);
+
+                let key = match map_key_ty {
+                    // Floats can't be hashed or ordered
+                    MapKeyType::Num => "i64".into(),
+                    map_key_ty => self.ty(&map_key_ty.clone().into(), false)?,
+                };
+
+                format!("{map}<{key}, {}>", self.ty(map_val_ty, false)?)
+            }
+            SchemaMemberType::Opt(opt_ty) => {
+                format!("Option<{}>", self.ty(&opt_ty.clone().into(), boxed)?)
+            }
+            SchemaMemberType::DefClass(id) | SchemaMemberType::DefEnum(id) => {
+                let name = self.reference(id)?.name.clone();
+
+                match boxed && self.ctx.in_cycle(id) {
+                    true => format!("Box<{name}>"),
+                    false => name,
+                }
+            }
+        })
+    }
+
+    /// Expression constructing the default value of the member type
+    fn literal(
+        &mut self,
+        schema_member_type: &SchemaMemberType,
+        default: &DefaultValue,
+    ) -> RustGeneratorResult<String> {
+        self.boxed_literal(schema_member_type, default, true)
+    }
+
+    fn boxed_literal(
+        &mut self,
+        schema_member_type: &SchemaMemberType,
+        default: &DefaultValue,
+        boxed: bool,
+    ) -> RustGeneratorResult<String> {
+        Ok(match (schema_member_type, default) {
+            (SchemaMemberType::Opt(opt_ty), default) => format!(
+                "Some({})",
+                self.boxed_literal(&opt_ty.clone().into(), default, boxed)?
+            ),
+            (_, DefaultValue::Num(num)) => format!("{num:?}"),
+            (_, DefaultValue::Str(str)) => format!("{str:?}.to_string()"),
+            (_, DefaultValue::Bool(bool)) => bool.to_string(),
+            (SchemaMemberType::Arr(arr_ty), DefaultValue::Arr(items)) => format!(
+                "vec![{}]",
+                items
+                    .iter()
+                    .map(|item| self.boxed_literal(arr_ty, item, false))
+                    .collect::<RustGeneratorResult<Vec<_>>>()?
+                    .join(", ")
+            ),
+            (SchemaMemberType::Map(map_key_ty, map_val_ty), DefaultValue::Map(entries)) => {
+                let map = self.options.map.name();
+                self.imports.add("std::collections", map);
+
+                format!(
+                    "{map}::from([{}])",
+                    entries
+                        .iter()
+                        .map(|(key, val)| Ok(format!(
+                            "({}, {})",
+                            match key {
+                                DefaultValue::Num(num) => self.int_key(*num)?.to_string(),
+                                key =>
+                                    self.boxed_literal(&map_key_ty.clone().into(), key, false)?,
+                            },
+                            self.boxed_literal(map_val_ty, val, false)?
+                        )))
+                        .collect::<RustGeneratorResult<Vec<_>>>()?
+                        .join(", ")
+                )
+            }
+            (_, DefaultValue::DefClass(id, fields)) => {
+                let schema_info = self.reference(id)?;
+                let Schema::Class(class_schema) = &schema_info.schema else {
+                    return Err(RustGeneratorError::Mismatch(
+                        schema_info.qualified_mod_path(),
+                    ));
+                };
+                let allow_undefined = schema_info
+                    .validation
+                    .as_ref()
+                    .map(|validation| validation.allow_undefined.as_slice())
+                    .unwrap_or_default();
+
+                let mut field_values = Vec::with_capacity(class_schema.len());
+
+                for (field_name, field_ty) in class_schema {
+                    let field_ty = match allow_undefined.contains(field_name)
+                        && !matches!(field_ty, SchemaMemberType::Opt(_))
+                    {
+                        true => SchemaMemberType::Opt(field_ty.clone().try_into()?),
+                        false => field_ty.clone(),
+                    };
+                    let value = match fields.get(field_name) {
+                        Some(field) => self.boxed_literal(&field_ty, field, true)?,
+                        // Only optional fields can be left out of a default
+                        None => "None".into(),
+                    };
+
+                    field_values.push(format!("{}: {value}", field_ident(field_name)));
+                }
+
+                if schema_info.unknown_keys == UnknownKeys::Passthrough {
+                    field_values.push(format!("{PASSTHROUGH_PROPERTY}: Default::default()"));
+                }
+
+                let literal = format!("{} {{ {} }}", schema_info.name, field_values.join(", "));
+
+                match boxed && self.ctx.in_cycle(id) {
+                    true => format!("Box::new({literal})"),
+                    false => literal,
+                }
+            }
+            (_, DefaultValue::DefEnum(id, variant)) => {
+                format!("{}::{}", self.reference(id)?.name, variant_ident(variant))
+            }
+            (_, DefaultValue::Arr(_) | DefaultValue::Map(_)) => {
+                return Err(RustGeneratorError::Mismatch(
+                    self.schema_info.qualified_mod_path(),
+                ))
+            }
+        })
+    }
+
+    /// Numeric map key as the `i64` numeric maps are keyed by
+    fn int_key(&self, num: f64) -> RustGeneratorResult<i64> {
+        match num.fract() == 0.0 && (i64::MIN as f64..i64::MAX as f64).contains(&num) {
+            true => Ok(num as i64),
+            false => Err(RustGeneratorError::NonIntegerKey(
+                self.schema_info.qualified_mod_path(),
+                num,
+            )),
+        }
+    }
+
+    /// Looks up a referenced schema and brings its type into scope
+    fn reference(&mut self, id: &Uuid) -> RustGeneratorResult<&'a SchemaInfo> {
+        let schema_info = self.registry.get(id)?;
+        let module = rust_module(schema_info);
+
+        if schema_info.root != self.schema_info.root {
+            let root = schema_info
+                .root
+                .as_ref()
+                .ok_or_else(|| RustGeneratorError::UnnamedRoot(schema_info.qualified_mod_path()))?;
+
+            self.imports.add(
+                format!(
+                    "crate::{}::{}",
+                    identifier(root, Case::Snake),
+                    module.join("::")
+                ),
+                &schema_info.name,
+            );
+        } else if module != self.module {
+            let from_dir = &self.module[..self.module.len() - 1];
+            let common = from_dir
+                .iter()
+                .zip(&module[..module.len() - 1])
+                .take_while(|(from, to)| from == to)
+                .count();
+
+            self.imports.add(
+                format!(
+                    "{}{}",
+                    "super::".repeat(from_dir.len() - common + 1),
+                    module[common..].join("::")
+                ),
+                &schema_info.name,
+            );
+        }
+
+        Ok(schema_info)
+    }
+}
+
+pub type RustGeneratorResult<T> = Result<T, RustGeneratorError>;
+
+#[derive(Debug, Error, Diagnostic)]
+pub enum RustGeneratorError {
+    #[error("[Rust] {0}")]
+    Registry(#[from] RegistryError),
+    #[error("[Rust] {0}")]
+    Schema(#[from] crate::schema::SchemaError),
+    #[error(
+        "[Rust] '{0}' lives in the unnamed root, which has no module path to be referenced by"
+    )]
+    UnnamedRoot(ModulePath),
+    #[error("[Rust] Default value of '{0}' doesn't match its member type")]
+    Mismatch(ModulePath),
+    #[error("[Rust] Names '{2}' and '{3}' of '{0}' both become the identifier '{1}'")]
+    IdentClash(ModulePath, String, String, String),
+    #[error("[Rust] Default map key {1} of '{0}' isn't an integer, which the `i64` keys of numeric maps can't hold")]
+    NonIntegerKey(ModulePath, f64),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{generator::GeneratorError, test_utils::generated};
+
+    fn rust(schema: &str) -> GeneratorResult<String> {
+        generated(
+            &RustBackend {
+                options: RustOptions::default(),
+            },
+            &[("thing", schema)],
+            "thing",
+        )
+    }
+
+    fn rust_error(schema: &str) -> RustGeneratorError {
+        match rust(schema) {
+            Err(GeneratorError::Rust(error)) => error,
+            other => panic!("expected a Rust generator error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn renames_fields() {
+        let contents = rust(r#"(schema: Class({ "fooBar": Num, "type": Bool }))"#).unwrap();
+
+        assert!(contents.contains("#[serde(rename = \"fooBar\")]\n    pub foo_bar: f64,"));
+        assert!(contents.contains("\n    pub r#type: bool,"));
+        assert!(!contents.contains("rename = \"type\""));
+    }
+
+    #[test]
+    fn rejects_clashing_identifiers() {
+        assert!(matches!(
+            rust_error(r#"(schema: Class({ "fooBar": Num, "foo_bar": Num }))"#),
+            RustGeneratorError::IdentClash(_, ident, ..) if ident == "foo_bar"
+        ));
+        assert!(matches!(
+            rust_error(r#"(schema: Class({ "Extra": Num }), unknown_keys: Some("passthrough"))"#),
+            RustGeneratorError::IdentClash(..)
+        ));
+        assert!(matches!(
+            rust_error(r#"(schema: Enum(["in-progress", "in_progress"]))"#),
+            RustGeneratorError::IdentClash(_, ident, ..) if ident == "InProgress"
+        ));
+    }
+
+    #[test]
+    fn default_map_keys() {
+        let with_key = |key: &str| {
+            format!(
+                r#"(
+                    schema: Class({{ "counts": Map(Num, Bool) }}),
+                    validation: Some((defaults: Some(Class({{ "counts": {{ {key}: true }} }})))),
+                )"#
+            )
+        };
+
+        assert!(rust(&with_key("2")).unwrap().contains("(2, true)"));
+        assert!(matches!(
+            rust_error(&with_key("1.5")),
+            RustGeneratorError::NonIntegerKey(_, num) if num == 1.5
+        ));
+        assert!(matches!(
+            rust_error(&with_key("1e30")),
+            RustGeneratorError::NonIntegerKey(..)
+        ));
+    }
+}
@@ -0,0 +1,417 @@
+pub mod ast;
+pub mod class;
+pub mod enum_;
+pub mod printer;
+pub mod template;
+
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::schema::{
+    info::SchemaInfo,
+    registry::Registry,
+    rename_rule::RenameRule,
+    unknown_keys::UnknownKeys,
+    validation_info::{ClassSchemaValidationInfo, EnumSchemaValidationInfo},
+    ClassSchema, EnumSchema,
+};
+
+use self::{
+    ast::Decl,
+    class::class_model,
+    enum_::enum_model,
+    printer::{Printer, TsStyle},
+    template::{SchemaModel, TemplateResult, Templates},
+};
+
+use super::{
+    backend::{Backend, Imports, SchemaContext},
+    default_variant, import_path, module, ts_file, GeneratorError, GeneratorResult, OutputRoots,
+    RUNTIME_MODULE,
+};
+
+const RUNTIME_SRC: &str = include_str!("ts/runtime.ts");
+
+pub struct ClassSchemaGenInfo<'a> {
+    schema_info: &'a SchemaInfo,
+    module: PathBuf,
+    class_schema: &'a ClassSchema,
+    validation: Option<ClassSchemaValidationInfo<'a>>,
+    rename_all: Option<&'a RenameRule>,
+    unknown_keys: UnknownKeys,
+    registry: &'a Registry,
+    outputs: &'a OutputRoots,
+}
+
+pub struct EnumSchemaGenInfo<'a> {
+    schema_info: &'a SchemaInfo,
+    enum_schema: &'a EnumSchema,
+    validation: Option<EnumSchemaValidationInfo<'a>>,
+    default: &'a String,
+}
+
+/// Classes with hand-rolled (de)serializers, printed in `style` and rendered from [`Templates`]
+pub struct TsBackend {
+    templates: Templates,
+    printer: Printer,
+}
+
+impl TsBackend {
+    /// Uses the templates of `templates_dir` over the built-in ones
+    pub fn new(templates_dir: Option<&Path>, style: TsStyle) -> TemplateResult<Self> {
+        Ok(Self {
+            templates: Templates::new(templates_dir, style)?,
+            printer: Printer::new(style),
+        })
+    }
+}
+
+/// What the `header` and `imports` templates are rendered with
+#[derive(Serialize)]
+struct ImportsModel<'a> {
+    schema: SchemaModel<'a>,
+    /// Import path of the runtime, if it's used
+    runtime: Option<String>,
+    modules: Vec<ImportModel<'a>>,
+    /// The printed import declarations, the runtime first
+    declarations: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct ImportModel<'a> {
+    from: &'a String,
+    names: Vec<String>,
+}
+
+impl Backend for TsBackend {
+    fn name(&self) -> &'static str {
+        "ts"
+    }
+
+    fn file(&self, schema_info: &SchemaInfo) -> PathBuf {
+        ts_file(&module(schema_info))
+    }
+
+    fn support_files(&self, _schema_files: &[PathBuf]) -> Vec<(PathBuf, String)> {
+        vec![(ts_file(RUNTIME_MODULE.as_ref()), RUNTIME_SRC.into())]
+    }
+
+    fn class(
+        &self,
+        ctx: &SchemaContext,
+        class_schema: &ClassSchema,
+        imports: &mut Imports,
+    ) -> GeneratorResult<String> {
+        let schema_info = ctx.schema_info;
+        let SchemaInfo {
+            validation,
+            rename_all,
+            unknown_keys,
+            ..
+        } = schema_info;
+
+        let gen_info = ClassSchemaGenInfo {
+            schema_info,
+            module: schema_module(ctx)?,
+            class_schema,
+            validation: validation.as_ref().map(|validation| validation.class()),
+            rename_all: rename_all.as_ref(),
+            unknown_keys: *unknown_keys,
+            registry: ctx.registry,
+            outputs: ctx.outputs,
+        };
+
+        Ok(self
+            .templates
+            .render("class", class_model(&gen_info, imports, &self.printer)?)?)
+    }
+
+    fn enum_(
+        &self,
+        ctx: &SchemaContext,
+        enum_schema: &EnumSchema,
+        imports: &mut Imports,
+    ) -> GeneratorResult<String> {
+        let schema_info = ctx.schema_info;
+
+        let gen_info = EnumSchemaGenInfo {
+            schema_info,
+            enum_schema,
+            validation: schema_info
+                .validation
+                .as_ref()
+                .map(|validation| validation.enum_()),
+            default: default_variant(schema_info, enum_schema)?,
+        };
+
+        Ok(self
+            .templates
+            .render("enum", enum_model(&gen_info, imports, &self.printer)?)?)
+    }
+
+    fn imports(&self, ctx: &SchemaContext, imports: &Imports) -> GeneratorResult<String> {
+        let runtime = runtime_import(ctx, imports)?;
+        let declarations = import_decls(runtime.as_ref(), imports)
+            .iter()
+            .map(|decl| self.printer.decl(decl))
+            .collect();
+
+        let model = ImportsModel {
+            schema: ctx.schema_info.into(),
+            runtime,
+            modules: imports
+                .modules()
+                .map(|(from, names)| ImportModel { from, names })
+                .collect(),
+            declarations,
+        };
+
+        Ok(self.templates.render("header", &model)? + &self.templates.render("imports", &model)?)
+    }
+}
+
+/// Absolute module of the schema being generated, without an extension
+pub fn schema_module(ctx: &SchemaContext) -> GeneratorResult<PathBuf> {
+    ctx.outputs
+        .module(ctx.schema_info)
+        .ok_or_else(|| GeneratorError::NoOutputRoot(ctx.schema_info.qualified_mod_path()))
+}
+
+/// Import path of the runtime from the schema's module, if the schema uses it
+pub fn runtime_import(ctx: &SchemaContext, imports: &Imports) -> GeneratorResult<Option<String>> {
+    let module = schema_module(ctx)?;
+
+    Ok(match imports.runtime {
+        true => ctx
+            .outputs
+            .runtime_module(ctx.schema_info.root.as_ref())
+            .map(|runtime_module| import_path(&module, &runtime_module)),
+        false => None,
+    })
+}
+
+/// ES module import declarations, the runtime first
+pub fn import_decls(runtime: Option<&String>, imports: &Imports) -> Vec<Decl> {
+    runtime
+        .map(|runtime| Decl::ImportAll {
+            alias: "runtime".into(),
+            from: runtime.clone(),
+        })
+        .into_iter()
+        .chain(imports.modules().map(|(from, names)| Decl::Import {
+            names,
+            from: from.clone(),
+        }))
+        .collect()
+}
+
+pub fn deserializer_name(name: impl AsRef<str>) -> String {
+    format!("deserialize{}", name.as_ref())
+}
+
+pub fn serializer_name(name: impl AsRef<str>) -> String {
+    format!("serialize{}", name.as_ref())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    use crate::{
+        generator::generate,
+        test_utils::{generated, rooted_registry, temp_dir},
+    };
+
+    fn ts(style: TsStyle) -> TsBackend {
+        TsBackend::new(None, style).unwrap()
+    }
+
+    #[test]
+    fn quotes_enum_members() {
+        let contents = generated(
+            &ts(TsStyle::default()),
+            &[(
+                "status",
+                r#"(
+                    schema: Enum(["in-progress", "done"]),
+                    validation: Some((defaults: Some(Enum("in-progress")))),
+                )"#,
+            )],
+            "status",
+        )
+        .unwrap();
+
+        assert!(contents.contains("export enum Status {\n\t\"in-progress\",\n\tdone,\n}"));
+        assert!(contents.contains("export const STATUS_DEFAULT: Status = Status[\"in-progress\"];"));
+        assert!(
+            contents.contains("\t\tcase \"in-progress\":\n\t\t\treturn Status[\"in-progress\"];")
+        );
+    }
+
+    #[test]
+    fn breaks_long_signatures() {
+        let style = TsStyle {
+            line_width: 60,
+            ..TsStyle::default()
+        };
+        let contents = generated(
+            &ts(style),
+            &[(
+                "aRatherLongSchemaName",
+                r#"(schema: Class({ "value": Num }), unknown_keys: Some("strict"))"#,
+            )],
+            "aRatherLongSchemaName",
+        )
+        .unwrap();
+
+        assert!(contents.contains(concat!(
+            "export function deserializeARatherLongSchemaName(\n",
+            "\tvalue: unknown,\n",
+            "\tpath = \"ARatherLongSchemaName\",\n",
+            "): ARatherLongSchemaName {\n",
+        )));
+        assert!(contents
+            .lines()
+            .all(|line| line.replace('\t', "    ").chars().count() <= 60));
+    }
+
+    #[test]
+    fn unknown_keys() {
+        let class = |policy: &str| {
+            generated(
+                &ts(TsStyle::default()),
+                &[(
+                    "a",
+                    &format!(
+                        r#"(schema: Class({{ "value": Num }}), unknown_keys: Some("{policy}"))"#
+                    ),
+                )],
+                "a",
+            )
+            .unwrap()
+        };
+
+        let strict = class("strict");
+        assert!(strict.contains("\truntime.expectNoUnknownKeys(obj, path, [\"value\"]);\n"));
+        assert!(!strict.contains("extra"));
+
+        let strip = class("strip");
+        assert!(!strip.contains("expectNoUnknownKeys"));
+        assert!(!strip.contains("extra"));
+
+        let passthrough = class("passthrough");
+        assert!(passthrough.contains("\textra: Record<string, unknown>;\n"));
+        assert!(passthrough.contains("\t\textra: runtime.collectUnknownKeys(obj, [\"value\"]),\n"));
+        assert!(passthrough.contains("const obj: Record<string, unknown> = { ...value.extra };"));
+    }
+
+    #[test]
+    fn serializers() {
+        let schemas = [
+            (
+                "a",
+                r#"(
+                    schema: Class({
+                        "user_name": Str,
+                        "nick": Opt(Str),
+                        "b": DefClass("b"),
+                        "bs": Map(Str, DefClass("b")),
+                        "cs": Arr(DefEnum("c")),
+                    }),
+                    validation: Some((aliases: { "user_name": ["login"] })),
+                )"#,
+            ),
+            ("b", r#"(schema: Class({ "value": Num }))"#),
+            ("c", r#"(schema: Enum(["x", "y"]))"#),
+        ];
+        let class = generated(&ts(TsStyle::default()), &schemas, "a").unwrap();
+        let enum_ = generated(&ts(TsStyle::default()), &schemas, "c").unwrap();
+
+        assert!(class.contains(
+            "export function serializeA(value: A): Record<string, unknown> {
+\tconst obj: Record<string, unknown> = {};
+
+\tobj[\"user_name\"] = value.user_name;
+\tif (value.nick !== undefined) {
+\t\tobj[\"nick\"] = value.nick;
+\t}
+\tobj[\"b\"] = serializeB(value.b);
+\tobj[\"bs\"] = runtime.serializeMap(value.bs, runtime.identity, serializeB);
+\tobj[\"cs\"] = value.cs.map(serializeC);
+
+\treturn obj;
+}"
+        ));
+        assert!(
+            enum_.contains("export function serializeC(value: C): string {\n\treturn C[value];\n}")
+        );
+
+        let renamed = generated(
+            &ts(TsStyle::default()),
+            &[(
+                "a",
+                r#"(schema: Class({ "user_name": Str }), rename_all: Some("camelCase"))"#,
+            )],
+            "a",
+        )
+        .unwrap();
+        assert!(renamed.contains("\tobj[\"user_name\"] = value.userName;\n"));
+    }
+
+    #[test]
+    fn cross_root_imports() {
+        let registry = rooted_registry(&[
+            (
+                None,
+                "api::order",
+                r#"(schema: Class({ "total": DefClass("shared::common::money") }))"#,
+            ),
+            (
+                Some("shared"),
+                "common::money",
+                r#"(schema: Class({ "cents": Num }))"#,
+            ),
+        ])
+        .unwrap();
+        let outputs = OutputRoots::from_iter([
+            (None, PathBuf::from("app/src")),
+            (Some("shared".into()), PathBuf::from("shared/src")),
+        ]);
+        let files = generate(&ts(TsStyle::default()), &registry, &outputs, None).unwrap();
+        let order = files
+            .iter()
+            .find(|file| file.path == Path::new("app/src/api/order.ts"))
+            .unwrap();
+
+        assert!(files
+            .iter()
+            .any(|file| file.path == Path::new("shared/src/common/money.ts")));
+        assert!(order.contents.contains(
+            "import { Money, deserializeMoney, serializeMoney } from \"../../../shared/src/common/money\";"
+        ));
+    }
+
+    #[test]
+    fn user_templates() {
+        let dir = temp_dir();
+        fs::write(
+            dir.path().join("class.jinja"),
+            "@Entity({{ schema.qualified_mod_path | quoted }})\n{{ declarations.class }}\n",
+        )
+        .unwrap();
+        let backend = TsBackend::new(Some(dir.path()), TsStyle::default()).unwrap();
+        let contents = generated(
+            &backend,
+            &[("api::thing", r#"(schema: Class({ "value": Num }))"#)],
+            "api::thing",
+        )
+        .unwrap();
+
+        assert!(
+            contents.contains("@Entity(\"api::thing\")\nexport class Thing {\n\tvalue: number;\n")
+        );
+        assert!(!contents.contains("deserializeThing"));
+    }
+}
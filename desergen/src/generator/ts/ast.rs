@@ -0,0 +1,213 @@
+//! The subset of TypeScript the generated modules are made of, turned into text by the [`super::printer`]
+
+pub enum Decl {
+    Export(Box<Decl>),
+    Import {
+        names: Vec<String>,
+        from: String,
+    },
+    ImportAll {
+        alias: String,
+        from: String,
+    },
+    Interface {
+        name: String,
+        fields: Vec<Field>,
+    },
+    Class {
+        name: String,
+        fields: Vec<Field>,
+        constructor: Option<Constructor>,
+    },
+    Enum {
+        name: String,
+        variants: Vec<String>,
+    },
+    Const {
+        name: String,
+        ty: Option<Type>,
+        value: Expr,
+    },
+    TypeAlias {
+        name: String,
+        ty: Type,
+    },
+    Function {
+        name: String,
+        params: Vec<Param>,
+        ret: Type,
+        body: Vec<Stmt>,
+    },
+}
+
+impl Decl {
+    pub fn export(self) -> Self {
+        Self::Export(Box::new(self))
+    }
+}
+
+/// Property declaration of a class, an interface or an object type
+#[derive(Clone)]
+pub struct Field {
+    pub name: String,
+    pub ty: Type,
+    pub optional: bool,
+}
+
+pub struct Constructor {
+    pub params: Vec<Param>,
+    pub body: Vec<Stmt>,
+}
+
+#[derive(Clone)]
+pub struct Param {
+    pub name: String,
+    pub ty: Option<Type>,
+    pub default: Option<Expr>,
+}
+
+impl Param {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ty: None,
+            default: None,
+        }
+    }
+
+    pub fn typed(name: impl Into<String>, ty: Type) -> Self {
+        Self {
+            ty: Some(ty),
+            ..Self::new(name)
+        }
+    }
+}
+
+#[derive(Clone)]
+pub enum Type {
+    Named(String),
+    Generic(String, Vec<Type>),
+    Union(Vec<Type>),
+    Intersection(Vec<Type>),
+    /// Object type literal
+    Object(Vec<Field>),
+}
+
+impl Type {
+    pub fn named(name: impl Into<String>) -> Self {
+        Self::Named(name.into())
+    }
+
+    pub fn generic(name: impl Into<String>, args: impl IntoIterator<Item = Type>) -> Self {
+        Self::Generic(name.into(), args.into_iter().collect())
+    }
+
+    /// `Record<string, unknown>`, the type of plain objects
+    pub fn record() -> Self {
+        Self::generic("Record", [Self::named("string"), Self::named("unknown")])
+    }
+}
+
+#[derive(Clone)]
+pub enum Stmt {
+    Const {
+        name: String,
+        ty: Option<Type>,
+        value: Expr,
+    },
+    Expr(Expr),
+    Assign(Expr, Expr),
+    Return(Expr),
+    Throw(Expr),
+    If(Expr, Vec<Stmt>),
+    Switch(Expr, Vec<Case>),
+    /// Empty line between groups of statements
+    Blank,
+}
+
+/// Labels falling through to the same body
+#[derive(Clone)]
+pub struct Case {
+    pub labels: Vec<Expr>,
+    pub body: Vec<Stmt>,
+}
+
+#[derive(Clone)]
+pub enum Expr {
+    Ident(String),
+    Str(String),
+    /// Numeric, boolean or other literal written as is
+    Lit(String),
+    /// Template literal of text and interpolated expressions
+    Template(Vec<TemplatePart>),
+    /// `object.property`, or `object["property"]` when the property isn't an identifier
+    Member(Box<Expr>, String),
+    /// `object[index]`
+    Index(Box<Expr>, Box<Expr>),
+    Call(Box<Expr>, Vec<Expr>),
+    New(Box<Expr>, Vec<Expr>),
+    Arrow(Vec<String>, Box<Expr>),
+    Binary(Box<Expr>, &'static str, Box<Expr>),
+    Array(Vec<Expr>),
+    Object(Vec<Prop>),
+}
+
+#[derive(Clone)]
+pub enum TemplatePart {
+    Text(String),
+    Expr(Expr),
+}
+
+#[derive(Clone)]
+pub enum Prop {
+    KeyValue(String, Expr),
+    Spread(Expr),
+}
+
+impl Expr {
+    pub fn ident(name: impl Into<String>) -> Self {
+        Self::Ident(name.into())
+    }
+
+    pub fn str(str: impl Into<String>) -> Self {
+        Self::Str(str.into())
+    }
+
+    /// Call of a function of the runtime module
+    pub fn runtime(name: &str, args: impl IntoIterator<Item = Expr>) -> Self {
+        Self::ident("runtime").member(name).call(args)
+    }
+
+    pub fn arrow<P: Into<String>>(params: impl IntoIterator<Item = P>, body: Expr) -> Self {
+        Self::Arrow(params.into_iter().map(Into::into).collect(), Box::new(body))
+    }
+
+    pub fn member(self, property: impl Into<String>) -> Self {
+        Self::Member(Box::new(self), property.into())
+    }
+
+    pub fn index(self, index: Expr) -> Self {
+        Self::Index(Box::new(self), Box::new(index))
+    }
+
+    pub fn call(self, args: impl IntoIterator<Item = Expr>) -> Self {
+        Self::Call(Box::new(self), args.into_iter().collect())
+    }
+
+    pub fn new_(self, args: impl IntoIterator<Item = Expr>) -> Self {
+        Self::New(Box::new(self), args.into_iter().collect())
+    }
+
+    pub fn binary(self, op: &'static str, right: Expr) -> Self {
+        Self::Binary(Box::new(self), op, Box::new(right))
+    }
+}
+
+pub fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+
+    chars
+        .next()
+        .is_some_and(|first| first.is_alphabetic() || first == '_' || first == '$')
+        && chars.all(|char| char.is_alphanumeric() || char == '_' || char == '$')
+}
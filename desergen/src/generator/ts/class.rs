@@ -0,0 +1,674 @@
+use miette::Diagnostic;
+use serde::Serialize;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::schema::{
+    info::SchemaInfo,
+    module_path::ModulePath,
+    registry::RegistryError,
+    rename_rule::property_name,
+    unknown_keys::{UnknownKeys, PASSTHROUGH_PROPERTY},
+    validation_info::{ClassSchemaValidationInfo, DefaultValue},
+    MapKeyType, SchemaMemberType,
+};
+
+use crate::generator::{backend::Imports, import_path};
+
+use super::{
+    ast::{Constructor, Decl, Expr, Field, Param, Prop, Stmt, Type},
+    deserializer_name,
+    printer::Printer,
+    serializer_name,
+    template::SchemaModel,
+    ClassSchemaGenInfo,
+};
+
+pub fn class_model<'a>(
+    gen_info: &'a ClassSchemaGenInfo<'a>,
+    imports: &mut Imports,
+    printer: &Printer,
+) -> ClassGeneratorResult<ClassModel<'a>> {
+    ClassGenerator {
+        gen_info,
+        imports,
+        printer,
+    }
+    .model()
+}
+
+/// What the `class` template is rendered with
+#[derive(Serialize)]
+pub struct ClassModel<'a> {
+    schema: SchemaModel<'a>,
+    name: &'a String,
+    init_name: String,
+    deserializer: String,
+    serializer: String,
+    fields: Vec<FieldModel<'a>>,
+    unknown_keys: UnknownKeys,
+    passthrough_property: &'static str,
+    /// Array literal of all accepted input keys, aliases included
+    known_keys: String,
+    /// Statement of the deserializer failing on unknown keys, for `strict` schemas
+    unknown_keys_check: String,
+    /// Last property of the deserialized constructor argument collecting the unknown keys,
+    /// for `passthrough` schemas
+    unknown_keys_property: String,
+    passthrough: PassthroughModel,
+    /// Statement of the deserializer only checking that the value is an object
+    expect_obj: String,
+    /// Statement of the deserializer reading the value into `obj`
+    obj: String,
+    /// Statement of the deserializer returning an instance without properties
+    construct_empty: String,
+    /// Statement of the serializer starting `obj`
+    serialized_obj: String,
+    heads: ClassHeads,
+    declarations: ClassDeclarations,
+}
+
+/// The printed declarations up to the brace opening their bodies
+#[derive(Serialize)]
+struct ClassHeads {
+    init: String,
+    class: String,
+    /// Head of the constructor inside the class
+    constructor: String,
+    deserializer: String,
+    serializer: String,
+}
+
+/// Fragments of the property keeping unknown keys, for `passthrough` schemas
+#[derive(Serialize)]
+struct PassthroughModel {
+    declaration: String,
+    init_declaration: String,
+    assignment: String,
+}
+
+/// The printed declarations making up the built-in output
+#[derive(Serialize)]
+struct ClassDeclarations {
+    init: String,
+    class: String,
+    deserializer: String,
+    serializer: String,
+}
+
+#[derive(Serialize)]
+struct FieldModel<'a> {
+    /// Name on the wire
+    name: &'a String,
+    /// Name after `rename_all`
+    property: String,
+    /// Property name as written in a declaration
+    key: String,
+    /// Property access as written after an object, `.name` or `["name"]`
+    access: String,
+    #[serde(rename = "type")]
+    ty: String,
+    /// Whether the class property may be `undefined`
+    optional: bool,
+    /// Whether the constructor can be called without the property
+    init_optional: bool,
+    /// Literal the constructor falls back to
+    default: Option<String>,
+    aliases: &'a [String],
+    allow_undefined: bool,
+    /// Whether the member is an `Opt`
+    nullable: bool,
+    /// Expression deserializing the member from `obj`
+    deserialize: String,
+    /// Expression serializing the member of `value`
+    serialize: String,
+    /// Whether the member is only written when it's defined
+    serialize_if_defined: bool,
+    /// Property declaration of the class
+    declaration: String,
+    /// Property declaration of the constructor argument's interface
+    init_declaration: String,
+    /// Statement of the constructor assigning the property
+    assignment: String,
+    /// Property of the deserialized constructor argument, followed by its comma
+    deserialization: String,
+    /// Statement of the serializer writing the member
+    serialization: String,
+}
+
+struct ClassGenerator<'a, 'c> {
+    gen_info: &'a ClassSchemaGenInfo<'a>,
+    imports: &'c mut Imports,
+    printer: &'c Printer,
+}
+
+impl<'a> ClassGenerator<'a, '_> {
+    fn model(&mut self) -> ClassGeneratorResult<ClassModel<'a>> {
+        let ClassSchemaGenInfo {
+            schema_info,
+            class_schema,
+            validation,
+            rename_all,
+            unknown_keys,
+            ..
+        } = self.gen_info;
+        let name = &schema_info.name;
+        let init_name = format!("{name}Init");
+
+        let mut fields = Vec::with_capacity(class_schema.len());
+        let mut class_fields = Vec::with_capacity(class_schema.len());
+        let mut init_fields = Vec::with_capacity(class_schema.len());
+        let mut assignments = Vec::with_capacity(class_schema.len());
+        let mut deserialized_fields = Vec::with_capacity(class_schema.len());
+        let mut serialized_fields = Vec::with_capacity(class_schema.len());
+        let mut known_keys = Vec::with_capacity(class_schema.len());
+
+        for (i, (field_name, field_ty)) in class_schema.iter().enumerate() {
+            let (ty, is_opt) = match field_ty {
+                SchemaMemberType::Opt(opt_ty) => (self.ty(&opt_ty.clone().into())?, true),
+                field_ty => (self.ty(field_ty)?, false),
+            };
+            let default = validation
+                .as_ref()
+                .and_then(|ClassSchemaValidationInfo { defaults, .. }| defaults.as_ref())
+                .and_then(|defaults| defaults.get(field_name))
+                .map(|default| self.default_literal(default))
+                .transpose()?;
+            let allow_undefined = validation.as_ref().is_some_and(
+                |ClassSchemaValidationInfo {
+                     allow_undefined, ..
+                 }| allow_undefined.contains(field_name),
+            );
+
+            let property_name = property_name(*rename_all, field_name);
+
+            let aliases = validation
+                .as_ref()
+                .and_then(|ClassSchemaValidationInfo { aliases, .. }| aliases.get(field_name));
+
+            known_keys.extend(
+                [field_name]
+                    .into_iter()
+                    .chain(aliases.into_iter().flatten())
+                    .map(Expr::str),
+            );
+
+            let input = match aliases {
+                Some(aliases) => Expr::runtime(
+                    "pick",
+                    [
+                        Expr::ident("obj"),
+                        Expr::Array(
+                            [field_name]
+                                .into_iter()
+                                .chain(aliases)
+                                .map(Expr::str)
+                                .collect(),
+                        ),
+                    ],
+                ),
+                None => Expr::ident("obj").index(Expr::str(field_name)),
+            };
+            let path = Expr::ident("path").binary("+", Expr::str(format!(".{field_name}")));
+
+            let deserialize = match !is_opt && (allow_undefined || default.is_some()) {
+                true => Expr::runtime("expectMaybe", [input, path, self.deserializer(field_ty)?]),
+                false => self.deserializer_call(field_ty, input, path)?,
+            };
+
+            let property = Expr::ident("value").member(&property_name);
+            let serialize_if_defined = is_opt || (allow_undefined && default.is_none());
+            let serialize = match serialize_if_defined {
+                true => self.serializer_call(
+                    &match field_ty {
+                        SchemaMemberType::Opt(opt_ty) => opt_ty.clone().into(),
+                        field_ty => field_ty.clone(),
+                    },
+                    property.clone(),
+                )?,
+                false => self.serializer_call(field_ty, property.clone())?,
+            };
+
+            let output = Expr::ident("obj").index(Expr::str(field_name));
+            let optional = is_opt || (allow_undefined && default.is_none());
+            let init_optional = is_opt || allow_undefined || default.is_some();
+            let init_property = Expr::ident("init").member(&property_name);
+
+            let class_field = Field {
+                name: property_name.clone(),
+                ty: ty.clone(),
+                optional,
+            };
+            let init_field = Field {
+                name: property_name.clone(),
+                ty: ty.clone(),
+                optional: init_optional,
+            };
+            let assignment = Stmt::Assign(
+                Expr::ident("this").member(&property_name),
+                match &default {
+                    Some(default) => init_property.binary("??", default.clone()),
+                    None => init_property,
+                },
+            );
+            let deserialized = Prop::KeyValue(property_name.clone(), deserialize.clone());
+            let serialized = match serialize_if_defined {
+                true => Stmt::If(
+                    property.binary("!==", Expr::ident("undefined")),
+                    vec![Stmt::Assign(output, serialize.clone())],
+                ),
+                false => Stmt::Assign(output, serialize.clone()),
+            };
+            let is_last = i + 1 == class_schema.len() && *unknown_keys != UnknownKeys::Passthrough;
+
+            fields.push(FieldModel {
+                name: field_name,
+                key: self.printer.property_key(&property_name),
+                access: self.printer.property_access(&property_name),
+                ty: self.printer.ty(&ty),
+                optional,
+                init_optional,
+                default: default.as_ref().map(|default| self.printer.expr(default)),
+                aliases: aliases.map(Vec::as_slice).unwrap_or_default(),
+                allow_undefined,
+                nullable: is_opt,
+                deserialize: self.printer.expr(&deserialize),
+                serialize: self.printer.expr(&serialize),
+                serialize_if_defined,
+                property: property_name,
+                declaration: self.printer.field(&class_field, 1),
+                init_declaration: self.printer.field(&init_field, 1),
+                assignment: self.printer.stmt(&assignment, 2),
+                deserialization: self.printer.prop(&deserialized, is_last, 2),
+                serialization: self.printer.stmt(&serialized, 1),
+            });
+
+            class_fields.push(class_field);
+            init_fields.push(init_field);
+            assignments.push(assignment);
+            deserialized_fields.push(deserialized);
+            serialized_fields.push(serialized);
+        }
+
+        let known_keys = Expr::Array(known_keys);
+
+        let passthrough_field = Field {
+            name: PASSTHROUGH_PROPERTY.into(),
+            ty: Type::record(),
+            optional: false,
+        };
+        let passthrough_init_field = Field {
+            optional: true,
+            ..passthrough_field.clone()
+        };
+        let passthrough_assignment = Stmt::Assign(
+            Expr::ident("this").member(PASSTHROUGH_PROPERTY),
+            Expr::ident("init")
+                .member(PASSTHROUGH_PROPERTY)
+                .binary("??", Expr::Object(vec![])),
+        );
+        let passthrough = PassthroughModel {
+            declaration: self.printer.field(&passthrough_field, 1),
+            init_declaration: self.printer.field(&passthrough_init_field, 1),
+            assignment: self.printer.stmt(&passthrough_assignment, 2),
+        };
+
+        if *unknown_keys == UnknownKeys::Passthrough {
+            class_fields.push(passthrough_field);
+            init_fields.push(passthrough_init_field);
+            assignments.push(passthrough_assignment);
+        }
+
+        let unknown_keys_check = Stmt::Expr(Expr::runtime(
+            "expectNoUnknownKeys",
+            [Expr::ident("obj"), Expr::ident("path"), known_keys.clone()],
+        ));
+        let unknown_keys_property = Prop::KeyValue(
+            PASSTHROUGH_PROPERTY.into(),
+            Expr::runtime(
+                "collectUnknownKeys",
+                [Expr::ident("obj"), known_keys.clone()],
+            ),
+        );
+
+        if *unknown_keys == UnknownKeys::Passthrough {
+            deserialized_fields.push(unknown_keys_property.clone());
+        }
+
+        let value_path = || [Expr::ident("value"), Expr::ident("path")];
+        let construct = |props| Expr::ident(name).new_([Expr::Object(props)]);
+
+        let expect_obj = Stmt::Expr(Expr::runtime("expectObj", value_path()));
+        let obj = Stmt::Const {
+            name: "obj".into(),
+            ty: None,
+            value: Expr::runtime("expectObj", value_path()),
+        };
+        let construct_empty = Stmt::Return(construct(vec![]));
+
+        let deserializer_body = match (deserialized_fields.is_empty(), unknown_keys) {
+            (true, UnknownKeys::Strip) => {
+                vec![expect_obj.clone(), Stmt::Blank, construct_empty.clone()]
+            }
+            (_, unknown_keys) => {
+                let mut body = vec![obj.clone()];
+
+                if *unknown_keys == UnknownKeys::Strict {
+                    body.push(unknown_keys_check.clone());
+                }
+
+                body.extend([Stmt::Blank, Stmt::Return(construct(deserialized_fields))]);
+                body
+            }
+        };
+
+        let serialized_obj = Stmt::Const {
+            name: "obj".into(),
+            ty: Some(Type::record()),
+            value: Expr::Object(match unknown_keys {
+                UnknownKeys::Passthrough => vec![Prop::Spread(
+                    Expr::ident("value").member(PASSTHROUGH_PROPERTY),
+                )],
+                _ => vec![],
+            }),
+        };
+        let mut serializer_body = vec![serialized_obj.clone(), Stmt::Blank];
+
+        if !serialized_fields.is_empty() {
+            serializer_body.extend(serialized_fields);
+            serializer_body.push(Stmt::Blank);
+        }
+
+        serializer_body.push(Stmt::Return(Expr::ident("obj")));
+
+        let constructor_params = vec![Param::typed("init", Type::named(&init_name))];
+
+        let init = Decl::Interface {
+            name: init_name.clone(),
+            fields: init_fields,
+        }
+        .export();
+        let class = Decl::Class {
+            name: name.clone(),
+            fields: class_fields,
+            constructor: Some(Constructor {
+                params: constructor_params.clone(),
+                body: assignments,
+            }),
+        }
+        .export();
+        let deserializer = Decl::Function {
+            name: deserializer_name(name),
+            params: vec![
+                Param::typed("value", Type::named("unknown")),
+                Param {
+                    default: Some(Expr::str(name)),
+                    ..Param::new("path")
+                },
+            ],
+            ret: Type::named(name),
+            body: deserializer_body,
+        }
+        .export();
+        let serializer = Decl::Function {
+            name: serializer_name(name),
+            params: vec![Param::typed("value", Type::named(name))],
+            ret: Type::record(),
+            body: serializer_body,
+        }
+        .export();
+
+        let heads = ClassHeads {
+            init: self.printer.head(&init),
+            class: self.printer.head(&class),
+            constructor: self.printer.constructor_head(&constructor_params, 1),
+            deserializer: self.printer.head(&deserializer),
+            serializer: self.printer.head(&serializer),
+        };
+        let declarations = ClassDeclarations {
+            init: self.printer.decl(&init),
+            class: self.printer.decl(&class),
+            deserializer: self.printer.decl(&deserializer),
+            serializer: self.printer.decl(&serializer),
+        };
+
+        self.imports.runtime = true;
+
+        Ok(ClassModel {
+            schema: (*schema_info).into(),
+            name,
+            init_name,
+            deserializer: deserializer_name(name),
+            serializer: serializer_name(name),
+            fields,
+            unknown_keys: *unknown_keys,
+            passthrough_property: PASSTHROUGH_PROPERTY,
+            known_keys: self.printer.expr(&known_keys),
+            unknown_keys_check: self.printer.stmt(&unknown_keys_check, 1),
+            unknown_keys_property: self.printer.prop(&unknown_keys_property, true, 2),
+            passthrough,
+            expect_obj: self.printer.stmt(&expect_obj, 1),
+            obj: self.printer.stmt(&obj, 1),
+            construct_empty: self.printer.stmt(&construct_empty, 1),
+            serialized_obj: self.printer.stmt(&serialized_obj, 1),
+            heads,
+            declarations,
+        })
+    }
+
+    /// TS type of the member
+    fn ty(&mut self, schema_member_type: &SchemaMemberType) -> ClassGeneratorResult<Type> {
+        Ok(match schema_member_type {
+            SchemaMemberType::Num => Type::named("number"),
+            SchemaMemberType::Str => Type::named("string"),
+            SchemaMemberType::Bool => Type::named("boolean"),
+            SchemaMemberType::Arr(arr_ty) => Type::generic("Array", [self.ty(arr_ty)?]),
+            SchemaMemberType::Map(map_key_ty, map_val_ty) => Type::generic(
+                "Map",
+                [self.ty(&map_key_ty.clone().into())?, self.ty(map_val_ty)?],
+            ),
+            SchemaMemberType::Opt(opt_ty) => Type::Union(vec![
+                self.ty(&opt_ty.clone().into())?,
+                Type::named("undefined"),
+            ]),
+            SchemaMemberType::DefClass(id) | SchemaMemberType::DefEnum(id) => {
+                Type::named(&self.reference(id, true)?.name)
+            }
+        })
+    }
+
+    /// Expression deserializing `value` at `path` into the member type
+    fn deserializer_call(
+        &mut self,
+        schema_member_type: &SchemaMemberType,
+        value: Expr,
+        path: Expr,
+    ) -> ClassGeneratorResult<Expr> {
+        Ok(match schema_member_type {
+            SchemaMemberType::Arr(arr_ty) => {
+                Expr::runtime("expectArr", [value, path, self.deserializer(arr_ty)?])
+            }
+            SchemaMemberType::Map(map_key_ty, map_val_ty) => Expr::runtime(
+                "expectMap",
+                [
+                    value,
+                    path,
+                    match map_key_ty {
+                        MapKeyType::Num => Expr::ident("runtime").member("expectNumKey"),
+                        map_key_ty => self.deserializer(&map_key_ty.clone().into())?,
+                    },
+                    self.deserializer(map_val_ty)?,
+                ],
+            ),
+            SchemaMemberType::Opt(opt_ty) => Expr::runtime(
+                "expectOpt",
+                [value, path, self.deserializer(&opt_ty.clone().into())?],
+            ),
+            schema_member_type => self.deserializer(schema_member_type)?.call([value, path]),
+        })
+    }
+
+    /// Function deserializing into the member type, suitable for `runtime.Deserializer`
+    fn deserializer(
+        &mut self,
+        schema_member_type: &SchemaMemberType,
+    ) -> ClassGeneratorResult<Expr> {
+        Ok(match schema_member_type {
+            SchemaMemberType::Num => Expr::ident("runtime").member("expectNum"),
+            SchemaMemberType::Str => Expr::ident("runtime").member("expectStr"),
+            SchemaMemberType::Bool => Expr::ident("runtime").member("expectBool"),
+            SchemaMemberType::DefClass(id) | SchemaMemberType::DefEnum(id) => {
+                Expr::ident(deserializer_name(&self.reference(id, true)?.name))
+            }
+            schema_member_type => Expr::arrow(
+                ["value", "path"],
+                self.deserializer_call(
+                    schema_member_type,
+                    Expr::ident("value"),
+                    Expr::ident("path"),
+                )?,
+            ),
+        })
+    }
+
+    /// Expression serializing `value` of the member type into its wire format
+    fn serializer_call(
+        &mut self,
+        schema_member_type: &SchemaMemberType,
+        value: Expr,
+    ) -> ClassGeneratorResult<Expr> {
+        let identity = || Expr::ident("runtime").member("identity");
+
+        Ok(match schema_member_type {
+            SchemaMemberType::Arr(arr_ty) => match self.serializer(arr_ty)? {
+                Some(serializer) => value.member("map").call([serializer]),
+                None => value,
+            },
+            SchemaMemberType::Map(map_key_ty, map_val_ty) => Expr::runtime(
+                "serializeMap",
+                [
+                    value,
+                    self.serializer(&map_key_ty.clone().into())?
+                        .unwrap_or_else(identity),
+                    self.serializer(map_val_ty)?.unwrap_or_else(identity),
+                ],
+            ),
+            SchemaMemberType::Opt(opt_ty) => match self.serializer(&opt_ty.clone().into())? {
+                Some(serializer) => Expr::runtime("serializeOpt", [value, serializer]),
+                None => value.binary("??", Expr::Lit("null".into())),
+            },
+            schema_member_type => match self.serializer(schema_member_type)? {
+                Some(serializer) => serializer.call([value]),
+                None => value,
+            },
+        })
+    }
+
+    /// Function serializing the member type, [`None`] if the value can be written as is
+    fn serializer(
+        &mut self,
+        schema_member_type: &SchemaMemberType,
+    ) -> ClassGeneratorResult<Option<Expr>> {
+        Ok(match schema_member_type {
+            SchemaMemberType::Num | SchemaMemberType::Str | SchemaMemberType::Bool => None,
+            SchemaMemberType::DefClass(id) | SchemaMemberType::DefEnum(id) => Some(Expr::ident(
+                serializer_name(&self.reference(id, true)?.name),
+            )),
+            schema_member_type => {
+                match self.serializer_call(schema_member_type, Expr::ident("value"))? {
+                    Expr::Ident(ident) if ident == "value" => None,
+                    serialized => Some(Expr::arrow(["value"], serialized)),
+                }
+            }
+        })
+    }
+
+    fn default_literal(&mut self, default: &DefaultValue) -> ClassGeneratorResult<Expr> {
+        Ok(match default {
+            DefaultValue::Num(num) => Expr::Lit(num_literal(*num)),
+            DefaultValue::Str(str) => Expr::str(str),
+            DefaultValue::Bool(bool) => Expr::Lit(bool.to_string()),
+            DefaultValue::Arr(items) => Expr::Array(
+                items
+                    .iter()
+                    .map(|item| self.default_literal(item))
+                    .collect::<ClassGeneratorResult<_>>()?,
+            ),
+            DefaultValue::Map(entries) => Expr::ident("Map").new_([Expr::Array(
+                entries
+                    .iter()
+                    .map(|(key, val)| {
+                        Ok(Expr::Array(vec![
+                            self.default_literal(key)?,
+                            self.default_literal(val)?,
+                        ]))
+                    })
+                    .collect::<ClassGeneratorResult<_>>()?,
+            )]),
+            DefaultValue::DefClass(id, fields) => {
+                let schema_info = self.reference(id, false)?;
+
+                Expr::ident(&schema_info.name).new_([Expr::Object(
+                    fields
+                        .iter()
+                        .map(|(field_name, val)| {
+                            Ok(Prop::KeyValue(
+                                schema_info.property_name(field_name),
+                                self.default_literal(val)?,
+                            ))
+                        })
+                        .collect::<ClassGeneratorResult<_>>()?,
+                )])
+            }
+            DefaultValue::DefEnum(id, variant) => {
+                Expr::ident(&self.reference(id, false)?.name).member(variant)
+            }
+        })
+    }
+
+    /// Looks up a referenced schema and registers the import of its type
+    /// (and (de)serializer if requested)
+    fn reference(
+        &mut self,
+        id: &Uuid,
+        with_functions: bool,
+    ) -> ClassGeneratorResult<&'a SchemaInfo> {
+        let schema_info = self.gen_info.registry.get(id)?;
+
+        let module =
+            self.gen_info.outputs.module(schema_info).ok_or_else(|| {
+                ClassGeneratorError::NoOutputRoot(schema_info.qualified_mod_path())
+            })?;
+
+        if module != self.gen_info.module {
+            let from = import_path(&self.gen_info.module, &module);
+
+            self.imports.add(&from, &schema_info.name);
+
+            if with_functions {
+                self.imports
+                    .add(&from, deserializer_name(&schema_info.name));
+                self.imports.add(from, serializer_name(&schema_info.name));
+            }
+        }
+
+        Ok(schema_info)
+    }
+}
+
+fn num_literal(num: f64) -> String {
+    match num.fract() == 0.0 && num.abs() < 1e15 {
+        true => format!("{}", num as i64),
+        false => num.to_string(),
+    }
+}
+
+pub type ClassGeneratorResult<T> = Result<T, ClassGeneratorError>;
+
+#[derive(Debug, Error, Diagnostic)]
+pub enum ClassGeneratorError {
+    #[error("[Class] {0}")]
+    Registry(#[from] RegistryError),
+    #[error("[Class] No output directory for the root of '{0}'")]
+    NoOutputRoot(ModulePath),
+}
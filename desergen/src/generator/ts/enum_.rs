@@ -0,0 +1,195 @@
+use convert_case::Casing;
+use miette::Diagnostic;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::schema::validation_info::EnumSchemaValidationInfo;
+
+use crate::generator::backend::Imports;
+
+use super::{
+    ast::{Case, Decl, Expr, Param, Stmt, TemplatePart, Type},
+    deserializer_name,
+    printer::Printer,
+    serializer_name,
+    template::SchemaModel,
+    EnumSchemaGenInfo,
+};
+
+pub fn enum_model<'a>(
+    EnumSchemaGenInfo {
+        schema_info,
+        enum_schema,
+        validation,
+        default,
+    }: &'a EnumSchemaGenInfo<'a>,
+    imports: &mut Imports,
+    printer: &Printer,
+) -> EnumGeneratorResult<EnumModel<'a>> {
+    let name = &schema_info.name;
+    let default_name = format!("{}_DEFAULT", name.to_case(convert_case::Case::UpperSnake));
+    let aliases = |variant| {
+        validation
+            .as_ref()
+            .and_then(|EnumSchemaValidationInfo { aliases, .. }| aliases.get(variant))
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    };
+
+    let cases = enum_schema
+        .iter()
+        .map(|variant| Case {
+            labels: [variant]
+                .into_iter()
+                .chain(aliases(variant))
+                .map(Expr::str)
+                .collect(),
+            body: vec![Stmt::Return(Expr::ident(name).member(variant))],
+        })
+        .collect::<Vec<_>>();
+
+    let variants = enum_schema
+        .iter()
+        .zip(&cases)
+        .enumerate()
+        .map(|(i, (variant, case))| VariantModel {
+            name: variant,
+            aliases: aliases(variant),
+            declaration: printer.variant(variant, i + 1 == enum_schema.len()),
+            case: printer.case(case, 2),
+        })
+        .collect();
+
+    let unknown_variant =
+        Stmt::Throw(Expr::ident("runtime").member("DeserializationError").new_([
+            Expr::ident("path"),
+            Expr::Template(vec![
+                    TemplatePart::Text(format!("unknown {name} variant ")),
+                    TemplatePart::Expr(
+                        Expr::ident("JSON")
+                            .member("stringify")
+                            .call([Expr::ident("value")]),
+                    ),
+                ]),
+        ]));
+
+    let serialization = Stmt::Return(Expr::ident(name).index(Expr::ident("value")));
+
+    let enum_ = Decl::Enum {
+        name: name.clone(),
+        variants: enum_schema.to_vec(),
+    }
+    .export();
+    let deserializer = Decl::Function {
+        name: deserializer_name(name),
+        params: vec![
+            Param::typed("value", Type::named("unknown")),
+            Param {
+                default: Some(Expr::str(name)),
+                ..Param::new("path")
+            },
+        ],
+        ret: Type::named(name),
+        body: vec![
+            Stmt::Switch(Expr::ident("value"), cases),
+            Stmt::Blank,
+            unknown_variant.clone(),
+        ],
+    }
+    .export();
+    let serializer = Decl::Function {
+        name: serializer_name(name),
+        params: vec![Param::typed("value", Type::named(name))],
+        ret: Type::named("string"),
+        body: vec![serialization.clone()],
+    }
+    .export();
+
+    let heads = EnumHeads {
+        enum_: printer.head(&enum_),
+        deserializer: printer.head(&deserializer),
+        serializer: printer.head(&serializer),
+    };
+    let declarations = EnumDeclarations {
+        enum_: printer.decl(&enum_),
+        default: printer.decl(
+            &Decl::Const {
+                name: default_name.clone(),
+                ty: Some(Type::named(name)),
+                value: Expr::ident(name).member(*default),
+            }
+            .export(),
+        ),
+        deserializer: printer.decl(&deserializer),
+        serializer: printer.decl(&serializer),
+    };
+
+    imports.runtime = true;
+
+    Ok(EnumModel {
+        schema: (*schema_info).into(),
+        name,
+        default_name,
+        default,
+        deserializer: deserializer_name(name),
+        serializer: serializer_name(name),
+        variants,
+        unknown_variant: printer.stmt(&unknown_variant, 1),
+        serialization: printer.stmt(&serialization, 1),
+        heads,
+        declarations,
+    })
+}
+
+/// What the `enum` template is rendered with
+#[derive(Serialize)]
+pub struct EnumModel<'a> {
+    schema: SchemaModel<'a>,
+    name: &'a String,
+    /// Name of the constant holding the default variant
+    default_name: String,
+    default: &'a String,
+    deserializer: String,
+    serializer: String,
+    variants: Vec<VariantModel<'a>>,
+    /// Statement of the deserializer throwing on values that aren't a variant
+    unknown_variant: String,
+    /// Statement of the serializer returning the wire name
+    serialization: String,
+    heads: EnumHeads,
+    declarations: EnumDeclarations,
+}
+
+/// The printed declarations up to the brace opening their bodies
+#[derive(Serialize)]
+struct EnumHeads {
+    #[serde(rename = "enum")]
+    enum_: String,
+    deserializer: String,
+    serializer: String,
+}
+
+/// The printed declarations making up the built-in output
+#[derive(Serialize)]
+struct EnumDeclarations {
+    #[serde(rename = "enum")]
+    enum_: String,
+    default: String,
+    deserializer: String,
+    serializer: String,
+}
+
+#[derive(Serialize)]
+struct VariantModel<'a> {
+    name: &'a String,
+    aliases: &'a [String],
+    /// Member of the enum declaration, followed by its comma
+    declaration: String,
+    /// Case of the deserializer's `switch` returning the variant
+    case: String,
+}
+
+pub type EnumGeneratorResult<T> = Result<T, EnumGeneratorError>;
+
+#[derive(Debug, Error, Diagnostic)]
+pub enum EnumGeneratorError {}
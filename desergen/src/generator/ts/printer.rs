@@ -0,0 +1,892 @@
+use serde::Deserialize;
+
+use super::ast::{
+    is_identifier, Case, Constructor, Decl, Expr, Field, Param, Prop, Stmt, TemplatePart, Type,
+};
+
+/// Formatting of the generated TS
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct TsStyle {
+    /// Indent with tabs rather than `tab_width` spaces
+    pub use_tabs: bool,
+    /// Width of an indentation level, tabs count as that many columns
+    pub tab_width: usize,
+    pub quotes: Quotes,
+    pub semicolons: bool,
+    /// Comma after the last item of lists spread over several lines
+    pub trailing_commas: bool,
+    /// Width lists and arguments are spread over several lines beyond
+    pub line_width: usize,
+}
+
+impl Default for TsStyle {
+    fn default() -> Self {
+        Self {
+            use_tabs: true,
+            tab_width: 4,
+            quotes: Quotes::Double,
+            semicolons: true,
+            trailing_commas: true,
+            line_width: 100,
+        }
+    }
+}
+
+impl TsStyle {
+    /// One level of indentation
+    pub fn indent(&self) -> String {
+        match self.use_tabs {
+            true => "\t".into(),
+            false => " ".repeat(self.tab_width),
+        }
+    }
+
+    /// What statements end with
+    pub fn semicolon(&self) -> &'static str {
+        match self.semicolons {
+            true => ";",
+            false => "",
+        }
+    }
+
+    /// What follows the last item of lists spread over several lines
+    pub fn trailing_comma(&self) -> &'static str {
+        match self.trailing_commas {
+            true => ",",
+            false => "",
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Quotes {
+    #[default]
+    Double,
+    Single,
+}
+
+/// Layout of text that can be laid out flat or broken over several lines, after Wadler's "prettier printer"
+enum Doc {
+    Text(String),
+    /// Space when the enclosing group is flat, line break otherwise
+    Line,
+    /// Nothing when the enclosing group is flat, line break otherwise
+    SoftLine,
+    /// Line break that also breaks all enclosing groups
+    HardLine,
+    /// Text only written when the enclosing group is broken
+    IfBreak(&'static str),
+    Indent(Box<Doc>),
+    /// Laid out flat when it fits on the line, broken otherwise
+    Group(Box<Doc>),
+    Concat(Vec<Doc>),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Flat,
+    Break,
+}
+
+/// Expressions that never break, fine to precede a hugged argument
+fn is_simple(expr: &Expr) -> bool {
+    match expr {
+        Expr::Ident(_) | Expr::Str(_) | Expr::Lit(_) => true,
+        Expr::Member(object, _) => is_simple(object),
+        _ => false,
+    }
+}
+
+/// Non-empty object or array literals, and arrow functions returning one
+fn is_huggable(expr: &Expr) -> bool {
+    match expr {
+        Expr::Object(props) => !props.is_empty(),
+        Expr::Array(items) => !items.is_empty(),
+        Expr::Arrow(_, body) => is_huggable(body),
+        _ => false,
+    }
+}
+
+fn text(str: impl Into<String>) -> Doc {
+    Doc::Text(str.into())
+}
+
+fn concat(docs: impl IntoIterator<Item = Doc>) -> Doc {
+    Doc::Concat(docs.into_iter().collect())
+}
+
+fn indent(doc: Doc) -> Doc {
+    Doc::Indent(Box::new(doc))
+}
+
+fn group(doc: Doc) -> Doc {
+    Doc::Group(Box::new(doc))
+}
+
+fn join(separator: impl Fn() -> Doc, docs: impl IntoIterator<Item = Doc>) -> Doc {
+    let mut joined = Vec::new();
+
+    for doc in docs {
+        if !joined.is_empty() {
+            joined.push(separator());
+        }
+
+        joined.push(doc);
+    }
+
+    Doc::Concat(joined)
+}
+
+pub struct Printer {
+    style: TsStyle,
+}
+
+impl Printer {
+    pub fn new(style: TsStyle) -> Self {
+        Self { style }
+    }
+
+    pub fn decl(&self, decl: &Decl) -> String {
+        self.render(&self.decl_doc(decl), 0)
+    }
+
+    pub fn expr(&self, expr: &Expr) -> String {
+        self.render(&self.expr_doc(expr), 0)
+    }
+
+    pub fn ty(&self, ty: &Type) -> String {
+        self.render(&self.type_doc(ty), 0)
+    }
+
+    /// Property declaration to be placed `level` indentation levels deep, its continuation
+    /// lines indented accordingly and the first one not at all, as are the fragments below
+    pub fn field(&self, field: &Field, level: usize) -> String {
+        self.render(&self.field_doc(field), level)
+    }
+
+    pub fn stmt(&self, stmt: &Stmt, level: usize) -> String {
+        self.render(&self.stmt_doc(stmt), level)
+    }
+
+    /// Property of an object literal spread over several lines, followed by its comma
+    pub fn prop(&self, prop: &Prop, is_last: bool, level: usize) -> String {
+        self.render(&self.prop_doc(prop), level) + self.separator(is_last)
+    }
+
+    /// Labels of a `switch` case followed by their body
+    pub fn case(&self, case: &Case, level: usize) -> String {
+        self.render(&self.case_doc(case), level)
+    }
+
+    /// Member of an enum declaration followed by its comma, quoted unless it's an identifier
+    pub fn variant(&self, variant: &str, is_last: bool) -> String {
+        self.property_key(variant) + self.separator(is_last)
+    }
+
+    /// Declaration up to the brace opening its body, for templates laying out the body themselves
+    pub fn head(&self, decl: &Decl) -> String {
+        self.render(&self.head_doc(decl), 0)
+    }
+
+    /// `constructor(...) {` of a class
+    pub fn constructor_head(&self, params: &[Param], level: usize) -> String {
+        self.render(&concat([self.constructor_doc(params), text(" {")]), level)
+    }
+
+    /// Comma following an item of a list spread over several lines
+    pub fn separator(&self, is_last: bool) -> &'static str {
+        match is_last {
+            true => self.style.trailing_comma(),
+            false => ",",
+        }
+    }
+
+    /// String literal in the configured quotes, with all the necessary escaping
+    pub fn string(&self, str: &str) -> String {
+        let quote = match self.style.quotes {
+            Quotes::Double => '"',
+            Quotes::Single => '\'',
+        };
+
+        let mut res = String::with_capacity(str.len() + 2);
+        res.push(quote);
+
+        for char in str.chars() {
+            match char {
+                '\\' => res.push_str("\\\\"),
+                '\n' => res.push_str("\\n"),
+                '\r' => res.push_str("\\r"),
+                '\t' => res.push_str("\\t"),
+                char if char == quote => {
+                    res.push('\\');
+                    res.push(char);
+                }
+                char if char.is_control() || char == '\u{2028}' || char == '\u{2029}' => {
+                    res.push_str(&format!("\\u{:04x}", char as u32))
+                }
+                char => res.push(char),
+            }
+        }
+
+        res.push(quote);
+        res
+    }
+
+    /// Property name as written in a declaration or an object literal
+    pub fn property_key(&self, name: &str) -> String {
+        match is_identifier(name) {
+            true => name.into(),
+            false => self.string(name),
+        }
+    }
+
+    /// Property access as written after an object, `.name` or `["name"]`
+    pub fn property_access(&self, name: &str) -> String {
+        match is_identifier(name) {
+            true => format!(".{name}"),
+            false => format!("[{}]", self.string(name)),
+        }
+    }
+
+    fn semi(&self) -> Doc {
+        text(self.style.semicolon())
+    }
+
+    fn trailing_comma(&self) -> Doc {
+        Doc::IfBreak(self.style.trailing_comma())
+    }
+
+    fn decl_doc(&self, decl: &Decl) -> Doc {
+        match decl {
+            Decl::Export(decl) => concat([text("export "), self.decl_doc(decl)]),
+            Decl::Import { names, from } => concat([
+                text("import "),
+                self.list("{", names.iter().map(|name| text(name.as_str())), "}", true),
+                text(format!(" from {}", self.string(from))),
+                self.semi(),
+            ]),
+            Decl::ImportAll { alias, from } => concat([
+                text(format!("import * as {alias} from {}", self.string(from))),
+                self.semi(),
+            ]),
+            Decl::Interface { name, fields } => concat([
+                text(format!("interface {name} ")),
+                self.block(fields.iter().map(|field| self.field_doc(field))),
+            ]),
+            Decl::Class {
+                name,
+                fields,
+                constructor,
+            } => {
+                let mut members = fields
+                    .iter()
+                    .map(|field| self.field_doc(field))
+                    .collect::<Vec<_>>();
+
+                if let Some(Constructor { params, body }) = constructor {
+                    if !members.is_empty() {
+                        members.push(text(""));
+                    }
+
+                    members.push(concat([
+                        self.constructor_doc(params),
+                        text(" "),
+                        self.statements(body),
+                    ]));
+                }
+
+                concat([text(format!("class {name} ")), self.block(members)])
+            }
+            Decl::Enum { name, variants } => concat([
+                text(format!("enum {name} ")),
+                self.block(
+                    variants
+                        .iter()
+                        .enumerate()
+                        .map(|(i, variant)| text(self.variant(variant, i + 1 == variants.len()))),
+                ),
+            ]),
+            Decl::Const { name, ty, value } => self.const_(name, ty.as_ref(), value),
+            Decl::TypeAlias { name, ty } => concat([
+                text(format!("type {name} = ")),
+                self.type_doc(ty),
+                self.semi(),
+            ]),
+            Decl::Function {
+                name,
+                params,
+                ret,
+                body,
+            } => concat([
+                self.signature_doc(name, params, ret),
+                text(" "),
+                self.statements(body),
+            ]),
+        }
+    }
+
+    fn head_doc(&self, decl: &Decl) -> Doc {
+        match decl {
+            Decl::Export(decl) => concat([text("export "), self.head_doc(decl)]),
+            Decl::Interface { name, .. } => text(format!("interface {name} {{")),
+            Decl::Class { name, .. } => text(format!("class {name} {{")),
+            Decl::Enum { name, .. } => text(format!("enum {name} {{")),
+            Decl::Function {
+                name, params, ret, ..
+            } => concat([self.signature_doc(name, params, ret), text(" {")]),
+            // The rest has no body
+            decl => self.decl_doc(decl),
+        }
+    }
+
+    fn signature_doc(&self, name: &str, params: &[Param], ret: &Type) -> Doc {
+        concat([
+            text(format!("function {name}")),
+            self.params(params),
+            text(": "),
+            self.type_doc(ret),
+        ])
+    }
+
+    fn constructor_doc(&self, params: &[Param]) -> Doc {
+        concat([text("constructor"), self.params(params)])
+    }
+
+    fn field_doc(&self, Field { name, ty, optional }: &Field) -> Doc {
+        concat([
+            text(self.property_key(name)),
+            text(match optional {
+                true => "?: ",
+                false => ": ",
+            }),
+            self.type_doc(ty),
+            self.semi(),
+        ])
+    }
+
+    fn params(&self, params: &[Param]) -> Doc {
+        self.list(
+            "(",
+            params.iter().map(|Param { name, ty, default }| {
+                concat([
+                    text(name.as_str()),
+                    match ty {
+                        Some(ty) => concat([text(": "), self.type_doc(ty)]),
+                        None => text(""),
+                    },
+                    match default {
+                        Some(default) => concat([text(" = "), self.expr_doc(default)]),
+                        None => text(""),
+                    },
+                ])
+            }),
+            ")",
+            false,
+        )
+    }
+
+    fn const_(&self, name: &str, ty: Option<&Type>, value: &Expr) -> Doc {
+        concat([
+            text(format!("const {name}")),
+            match ty {
+                Some(ty) => concat([text(": "), self.type_doc(ty)]),
+                None => text(""),
+            },
+            text(" = "),
+            self.expr_doc(value),
+            self.semi(),
+        ])
+    }
+
+    /// Braces around one line per item, `{}` without items
+    fn block(&self, lines: impl IntoIterator<Item = Doc>) -> Doc {
+        let lines = lines.into_iter().collect::<Vec<_>>();
+
+        match lines.is_empty() {
+            true => text("{}"),
+            false => concat([
+                text("{"),
+                indent(concat([Doc::HardLine, join(|| Doc::HardLine, lines)])),
+                Doc::HardLine,
+                text("}"),
+            ]),
+        }
+    }
+
+    fn statements(&self, stmts: &[Stmt]) -> Doc {
+        self.block(stmts.iter().map(|stmt| self.stmt_doc(stmt)))
+    }
+
+    fn stmt_doc(&self, stmt: &Stmt) -> Doc {
+        match stmt {
+            Stmt::Const { name, ty, value } => self.const_(name, ty.as_ref(), value),
+            Stmt::Expr(expr) => concat([self.expr_doc(expr), self.semi()]),
+            Stmt::Assign(target, value) => concat([
+                self.expr_doc(target),
+                text(" = "),
+                self.expr_doc(value),
+                self.semi(),
+            ]),
+            Stmt::Return(value) => concat([text("return "), self.expr_doc(value), self.semi()]),
+            Stmt::Throw(value) => concat([text("throw "), self.expr_doc(value), self.semi()]),
+            Stmt::If(condition, body) => concat([
+                text("if ("),
+                self.expr_doc(condition),
+                text(") "),
+                self.statements(body),
+            ]),
+            Stmt::Switch(value, cases) => concat([
+                text("switch ("),
+                self.expr_doc(value),
+                text(") "),
+                self.block(cases.iter().map(|case| self.case_doc(case))),
+            ]),
+            Stmt::Blank => text(""),
+        }
+    }
+
+    fn case_doc(&self, Case { labels, body }: &Case) -> Doc {
+        concat([
+            join(
+                || Doc::HardLine,
+                labels
+                    .iter()
+                    .map(|label| concat([text("case "), self.expr_doc(label), text(":")])),
+            ),
+            indent(concat(
+                body.iter()
+                    .flat_map(|stmt| [Doc::HardLine, self.stmt_doc(stmt)]),
+            )),
+        ])
+    }
+
+    fn type_doc(&self, ty: &Type) -> Doc {
+        match ty {
+            Type::Named(name) => text(name.as_str()),
+            Type::Generic(name, args) => concat([
+                text(format!("{name}<")),
+                join(|| text(", "), args.iter().map(|arg| self.type_doc(arg))),
+                text(">"),
+            ]),
+            Type::Union(types) => join(|| text(" | "), types.iter().map(|ty| self.type_doc(ty))),
+            Type::Intersection(types) => {
+                join(|| text(" & "), types.iter().map(|ty| self.type_doc(ty)))
+            }
+            Type::Object(fields) => self.block(fields.iter().map(|field| self.field_doc(field))),
+        }
+    }
+
+    fn expr_doc(&self, expr: &Expr) -> Doc {
+        match expr {
+            Expr::Ident(name) | Expr::Lit(name) => text(name.as_str()),
+            Expr::Str(str) => text(self.string(str)),
+            Expr::Template(parts) => concat(
+                [text("`")]
+                    .into_iter()
+                    .chain(parts.iter().map(|part| {
+                        match part {
+                            TemplatePart::Text(str) => text(
+                                str.replace('\\', "\\\\")
+                                    .replace('`', "\\`")
+                                    .replace("${", "\\${"),
+                            ),
+                            TemplatePart::Expr(expr) => {
+                                concat([text("${"), self.expr_doc(expr), text("}")])
+                            }
+                        }
+                    }))
+                    .chain([text("`")]),
+            ),
+            Expr::Member(object, property) => {
+                concat([self.expr_doc(object), text(self.property_access(property))])
+            }
+            Expr::Index(object, index) => concat([
+                self.expr_doc(object),
+                text("["),
+                self.expr_doc(index),
+                text("]"),
+            ]),
+            Expr::Call(callee, args) => concat([self.expr_doc(callee), self.args(args)]),
+            Expr::New(callee, args) => {
+                concat([text("new "), self.expr_doc(callee), self.args(args)])
+            }
+            Expr::Arrow(params, body) => concat([
+                text(format!("({}) => ", params.join(", "))),
+                match body.as_ref() {
+                    body @ Expr::Object(_) => concat([text("("), self.expr_doc(body), text(")")]),
+                    body => self.expr_doc(body),
+                },
+            ]),
+            Expr::Binary(left, op, right) => concat([
+                self.expr_doc(left),
+                text(format!(" {op} ")),
+                self.expr_doc(right),
+            ]),
+            Expr::Array(items) => self.list(
+                "[",
+                items.iter().map(|item| self.expr_doc(item)),
+                "]",
+                false,
+            ),
+            Expr::Object(props) => {
+                self.list("{", props.iter().map(|prop| self.prop_doc(prop)), "}", true)
+            }
+        }
+    }
+
+    fn prop_doc(&self, prop: &Prop) -> Doc {
+        match prop {
+            Prop::KeyValue(key, value) => concat([
+                text(format!("{}: ", self.property_key(key))),
+                self.expr_doc(value),
+            ]),
+            Prop::Spread(value) => concat([text("..."), self.expr_doc(value)]),
+        }
+    }
+
+    /// Arguments between parentheses, hugging a last object or array, or an arrow function
+    /// returning one, that follows simple ones
+    fn args(&self, args: &[Expr]) -> Doc {
+        let hugged = match args.split_last() {
+            Some((last, leading)) if is_huggable(last) => Some(leading),
+            _ => None,
+        }
+        .filter(|leading| leading.iter().all(is_simple));
+
+        match hugged {
+            Some(leading) => concat(
+                [text("(")]
+                    .into_iter()
+                    .chain(
+                        leading
+                            .iter()
+                            .flat_map(|arg| [self.expr_doc(arg), text(", ")]),
+                    )
+                    .chain([self.expr_doc(&args[args.len() - 1]), text(")")]),
+            ),
+            None => self.list("(", args.iter().map(|arg| self.expr_doc(arg)), ")", false),
+        }
+    }
+
+    /// Comma separated items between delimiters, one per line when they don't fit.
+    /// `spaced` lists have spaces inside the delimiters when laid out flat
+    fn list(
+        &self,
+        open: &str,
+        items: impl IntoIterator<Item = Doc>,
+        close: &str,
+        spaced: bool,
+    ) -> Doc {
+        let items = items.into_iter().collect::<Vec<_>>();
+
+        if items.is_empty() {
+            return text(format!("{open}{close}"));
+        }
+
+        let line = || match spaced {
+            true => Doc::Line,
+            false => Doc::SoftLine,
+        };
+
+        group(concat([
+            text(open),
+            indent(concat([
+                line(),
+                join(|| concat([text(","), Doc::Line]), items),
+            ])),
+            self.trailing_comma(),
+            line(),
+            text(close),
+        ]))
+    }
+
+    /// The document laid out as if it started `level` indentation levels deep
+    fn render(&self, doc: &Doc, level: usize) -> String {
+        let mut res = String::new();
+        let mut column = level * self.style.tab_width;
+        let mut stack = vec![(level, Mode::Break, doc)];
+
+        while let Some((level, mode, doc)) = stack.pop() {
+            match doc {
+                Doc::Text(str) => {
+                    res.push_str(str);
+                    column += str.chars().count();
+                }
+                Doc::Line if mode == Mode::Flat => {
+                    res.push(' ');
+                    column += 1;
+                }
+                Doc::SoftLine if mode == Mode::Flat => {}
+                Doc::Line | Doc::SoftLine | Doc::HardLine => {
+                    res.truncate(res.trim_end_matches([' ', '\t']).len());
+                    res.push('\n');
+                    res.push_str(&self.style.indent().repeat(level));
+                    column = level * self.style.tab_width;
+                }
+                Doc::IfBreak(str) => {
+                    if mode == Mode::Break {
+                        res.push_str(str);
+                        column += str.chars().count();
+                    }
+                }
+                Doc::Indent(doc) => stack.push((level + 1, mode, doc)),
+                Doc::Group(doc) => {
+                    let remaining = self.style.line_width as isize - column as isize;
+                    let mode = match mode == Mode::Flat || self.fits(doc, &stack, remaining) {
+                        true => Mode::Flat,
+                        false => Mode::Break,
+                    };
+
+                    stack.push((level, mode, doc));
+                }
+                Doc::Concat(docs) => stack.extend(docs.iter().rev().map(|doc| (level, mode, doc))),
+            }
+        }
+
+        res
+    }
+
+    /// Whether the group laid out flat, along with what follows it up to the next line break, fits in `remaining` columns
+    fn fits(&self, doc: &Doc, rest: &[(usize, Mode, &Doc)], mut remaining: isize) -> bool {
+        let mut stack = vec![(Mode::Flat, doc)];
+        let mut rest = rest.iter().rev();
+
+        while remaining >= 0 {
+            let (mode, doc) = match stack.pop() {
+                Some(next) => next,
+                None => match rest.next() {
+                    Some((_, mode, doc)) => (*mode, *doc),
+                    None => return true,
+                },
+            };
+
+            match doc {
+                Doc::Text(str) => remaining -= str.chars().count() as isize,
+                Doc::Line | Doc::SoftLine if mode == Mode::Break => return true,
+                Doc::Line => remaining -= 1,
+                Doc::SoftLine => {}
+                Doc::HardLine => return mode == Mode::Break,
+                Doc::IfBreak(str) => {
+                    if mode == Mode::Break {
+                        remaining -= str.chars().count() as isize;
+                    }
+                }
+                Doc::Indent(doc) | Doc::Group(doc) => stack.push((mode, doc)),
+                Doc::Concat(docs) => stack.extend(docs.iter().rev().map(|doc| (mode, doc))),
+            }
+        }
+
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn printer(style: TsStyle) -> Printer {
+        Printer::new(style)
+    }
+
+    fn point() -> Decl {
+        Decl::Interface {
+            name: "Point".into(),
+            fields: vec![
+                Field {
+                    name: "x".into(),
+                    ty: Type::named("number"),
+                    optional: false,
+                },
+                Field {
+                    name: "the label".into(),
+                    ty: Type::named("string"),
+                    optional: true,
+                },
+            ],
+        }
+        .export()
+    }
+
+    #[test]
+    fn default_style() {
+        assert_eq!(
+            printer(TsStyle::default()).decl(&point()),
+            "export interface Point {\n\tx: number;\n\t\"the label\"?: string;\n}"
+        );
+    }
+
+    #[test]
+    fn custom_style() {
+        let style = TsStyle {
+            use_tabs: false,
+            tab_width: 2,
+            quotes: Quotes::Single,
+            semicolons: false,
+            ..TsStyle::default()
+        };
+
+        assert_eq!(
+            printer(style).decl(&point()),
+            "export interface Point {\n  x: number\n  'the label'?: string\n}"
+        );
+    }
+
+    #[test]
+    fn string_escaping() {
+        let printer = printer(TsStyle::default());
+
+        assert_eq!(printer.string("a\"b'c\\\n"), r#""a\"b'c\\\n""#);
+        assert_eq!(
+            Printer::new(TsStyle {
+                quotes: Quotes::Single,
+                ..TsStyle::default()
+            })
+            .string("a\"b'c"),
+            r#"'a"b\'c'"#
+        );
+        assert_eq!(printer.string("\u{1}\u{2028}"), r#""\u0001\u2028""#);
+    }
+
+    #[test]
+    fn property_names() {
+        let printer = printer(TsStyle::default());
+
+        assert_eq!(printer.property_key("$valid_1"), "$valid_1");
+        assert_eq!(printer.property_key("1st"), "\"1st\"");
+        assert_eq!(printer.property_access("name"), ".name");
+        assert_eq!(printer.property_access("a-b"), "[\"a-b\"]");
+    }
+
+    fn call(args: usize) -> Decl {
+        Decl::Const {
+            name: "value".into(),
+            ty: None,
+            value: Expr::runtime(
+                "union",
+                (0..args).map(|i| Expr::ident(format!("argument{i}"))),
+            ),
+        }
+    }
+
+    #[test]
+    fn breaks_long_lines() {
+        let style = TsStyle {
+            line_width: 60,
+            ..TsStyle::default()
+        };
+
+        assert_eq!(
+            printer(style).decl(&call(2)),
+            "const value = runtime.union(argument0, argument1);"
+        );
+        assert_eq!(
+            printer(style).decl(&call(3)),
+            "const value = runtime.union(\n\targument0,\n\targument1,\n\targument2,\n);"
+        );
+        assert_eq!(
+            printer(TsStyle {
+                trailing_commas: false,
+                ..style
+            })
+            .decl(&call(3)),
+            "const value = runtime.union(\n\targument0,\n\targument1,\n\targument2\n);"
+        );
+    }
+
+    #[test]
+    fn hugs_last_argument() {
+        let style = TsStyle {
+            line_width: 40,
+            ..TsStyle::default()
+        };
+        let value = Expr::runtime(
+            "object",
+            [
+                Expr::str("key"),
+                Expr::Object(vec![
+                    Prop::KeyValue("first".into(), Expr::Lit("1".into())),
+                    Prop::KeyValue("second".into(), Expr::Lit("2".into())),
+                ]),
+            ],
+        );
+
+        assert_eq!(
+            printer(style).expr(&value),
+            "runtime.object(\"key\", {\n\tfirst: 1,\n\tsecond: 2,\n})"
+        );
+    }
+
+    #[test]
+    fn fragments_at_level() {
+        let printer = printer(TsStyle::default());
+        let field = Field {
+            name: "x".into(),
+            ty: Type::Union(vec![Type::named("number"), Type::named("null")]),
+            optional: false,
+        };
+
+        assert_eq!(printer.field(&field, 1), "x: number | null;");
+        assert_eq!(
+            printer.stmt(&Stmt::Return(Expr::ident("x")), 2),
+            "return x;"
+        );
+        assert_eq!(
+            printer.prop(&Prop::Spread(Expr::ident("rest")), true, 1),
+            "...rest,"
+        );
+        assert_eq!(printer.separator(false), ",");
+    }
+
+    #[test]
+    fn enum_members() {
+        let decl = Decl::Enum {
+            name: "Status".into(),
+            variants: vec!["done".into(), "in-progress".into()],
+        };
+
+        assert_eq!(
+            printer(TsStyle::default()).decl(&decl),
+            "enum Status {\n\tdone,\n\t\"in-progress\",\n}"
+        );
+        assert_eq!(
+            printer(TsStyle {
+                trailing_commas: false,
+                ..TsStyle::default()
+            })
+            .decl(&decl),
+            "enum Status {\n\tdone,\n\t\"in-progress\"\n}"
+        );
+    }
+
+    #[test]
+    fn heads() {
+        let printer = printer(TsStyle {
+            line_width: 30,
+            ..TsStyle::default()
+        });
+        let function = Decl::Function {
+            name: "serialize".into(),
+            params: vec![Param::typed("value", Type::named("Status"))],
+            ret: Type::named("string"),
+            body: vec![],
+        };
+
+        assert_eq!(
+            printer.head(&Decl::Enum {
+                name: "Status".into(),
+                variants: vec![],
+            }),
+            "enum Status {"
+        );
+        assert_eq!(
+            printer.head(&function),
+            "function serialize(\n\tvalue: Status,\n): string {"
+        );
+        assert_eq!(
+            printer.constructor_head(&[Param::typed("init", Type::named("Init"))], 1),
+            "constructor(init: Init) {"
+        );
+    }
+}
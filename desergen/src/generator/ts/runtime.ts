@@ -0,0 +1,151 @@
+export class DeserializationError extends Error {
+	readonly path: string;
+
+	constructor(path: string, message: string) {
+		super(`${path}: ${message}`);
+		this.path = path;
+	}
+}
+
+export type Deserializer<T> = (value: unknown, path: string) => T;
+
+function describe(value: unknown): string {
+	if (value === null) {
+		return "null";
+	}
+
+	if (Array.isArray(value)) {
+		return "array";
+	}
+
+	return typeof value;
+}
+
+function mismatch(path: string, expected: string, value: unknown): DeserializationError {
+	return new DeserializationError(path, `expected ${expected}, found ${describe(value)}`);
+}
+
+export function expectNum(value: unknown, path: string): number {
+	if (typeof value !== "number") {
+		throw mismatch(path, "number", value);
+	}
+
+	return value;
+}
+
+export function expectStr(value: unknown, path: string): string {
+	if (typeof value !== "string") {
+		throw mismatch(path, "string", value);
+	}
+
+	return value;
+}
+
+export function expectBool(value: unknown, path: string): boolean {
+	if (typeof value !== "boolean") {
+		throw mismatch(path, "boolean", value);
+	}
+
+	return value;
+}
+
+export function expectObj(value: unknown, path: string): Record<string, unknown> {
+	if (typeof value !== "object" || value === null || Array.isArray(value)) {
+		throw mismatch(path, "object", value);
+	}
+
+	return value as Record<string, unknown>;
+}
+
+export function expectArr<T>(value: unknown, path: string, item: Deserializer<T>): T[] {
+	if (!Array.isArray(value)) {
+		throw mismatch(path, "array", value);
+	}
+
+	return value.map((value, index) => item(value, `${path}[${index}]`));
+}
+
+export function expectMap<K, V>(
+	value: unknown,
+	path: string,
+	key: Deserializer<K>,
+	val: Deserializer<V>,
+): Map<K, V> {
+	const obj = expectObj(value, path);
+	const map = new Map<K, V>();
+
+	for (const [objKey, objVal] of Object.entries(obj)) {
+		const entryPath = `${path}[${JSON.stringify(objKey)}]`;
+
+		map.set(key(objKey, entryPath), val(objVal, entryPath));
+	}
+
+	return map;
+}
+
+/** Keys of numeric maps as they appear on the wire, decimal numbers with an optional exponent */
+const NUM_KEY = /^[+-]?([0-9]+(\.[0-9]*)?|\.[0-9]+)([eE][+-]?[0-9]+)?$/;
+
+export function expectNumKey(value: unknown, path: string): number {
+	if (typeof value !== "string" || !NUM_KEY.test(value)) {
+		throw mismatch(path, "numeric key", value);
+	}
+
+	return Number(value);
+}
+
+export function expectOpt<T>(value: unknown, path: string, inner: Deserializer<T>): T | undefined {
+	return value === undefined || value === null ? undefined : inner(value, path);
+}
+
+export function expectMaybe<T>(value: unknown, path: string, inner: Deserializer<T>): T | undefined {
+	return value === undefined ? undefined : inner(value, path);
+}
+
+export function pick(obj: Record<string, unknown>, keys: string[]): unknown {
+	for (const key of keys) {
+		if (obj[key] !== undefined) {
+			return obj[key];
+		}
+	}
+
+	return undefined;
+}
+
+function unknownKeys(obj: Record<string, unknown>, known: string[]): string[] {
+	return Object.keys(obj).filter((key) => !known.includes(key));
+}
+
+export function expectNoUnknownKeys(obj: Record<string, unknown>, path: string, known: string[]): void {
+	const unknown = unknownKeys(obj, known);
+
+	if (unknown.length > 0) {
+		throw new DeserializationError(path, `unknown keys ${unknown.map((key) => JSON.stringify(key)).join(", ")}`);
+	}
+}
+
+export function collectUnknownKeys(obj: Record<string, unknown>, known: string[]): Record<string, unknown> {
+	return Object.fromEntries(unknownKeys(obj, known).map((key) => [key, obj[key]]));
+}
+
+export function identity<T>(value: T): T {
+	return value;
+}
+
+export function serializeMap<K, V>(
+	map: Map<K, V>,
+	key: (key: K) => unknown,
+	val: (value: V) => unknown,
+): Record<string, unknown> {
+	const obj: Record<string, unknown> = {};
+
+	for (const [mapKey, mapVal] of map) {
+		obj[String(key(mapKey))] = val(mapVal);
+	}
+
+	return obj;
+}
+
+export function serializeOpt<T>(value: T | undefined, inner: (value: T) => unknown): unknown {
+	return value === undefined ? null : inner(value);
+}
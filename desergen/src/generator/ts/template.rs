@@ -0,0 +1,187 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use miette::Diagnostic;
+use minijinja::{Environment, UndefinedBehavior};
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::schema::{info::SchemaInfo, rename_rule::RenameRule, unknown_keys::UnknownKeys};
+
+use super::printer::{Printer, TsStyle};
+
+/// Extension of template files, the rest of the file name is the template's name
+pub const TEMPLATE_EXTENSION: &str = "jinja";
+
+/// Templates the output is rendered from, each one can be overridden by a file of the same name
+const DEFAULTS: [(&str, &str); 4] = [
+    ("header", include_str!("templates/header.jinja")),
+    ("imports", include_str!("templates/imports.jinja")),
+    ("class", include_str!("templates/class.jinja")),
+    ("enum", include_str!("templates/enum.jinja")),
+];
+
+pub struct Templates {
+    env: Environment<'static>,
+}
+
+impl Templates {
+    /// The built-in templates, overridden by the `.jinja` files of `dir`.
+    /// Other files of `dir` can be included or imported by their name
+    pub fn new(dir: Option<&Path>, style: TsStyle) -> TemplateResult<Self> {
+        let mut env = Environment::new();
+        env.set_trim_blocks(true);
+        env.set_lstrip_blocks(true);
+        env.set_keep_trailing_newline(true);
+        env.set_undefined_behavior(UndefinedBehavior::Strict);
+        env.add_global("indent", style.indent());
+        env.add_global("semi", style.semicolon());
+        env.add_global("comma", style.trailing_comma());
+        let printer = Printer::new(style);
+        env.add_filter("quoted", move |str: String| printer.string(&str));
+
+        for (name, source) in DEFAULTS {
+            env.add_template(name, source)
+                .map_err(|err| TemplateError::Syntax(name.into(), err))?;
+        }
+
+        if let Some(dir) = dir {
+            let entries = fs::read_dir(dir).map_err(|err| TemplateError::IO(err, dir.into()))?;
+
+            for entry in entries {
+                let path = entry
+                    .map_err(|err| TemplateError::IO(err, dir.into()))?
+                    .path();
+
+                let name = match (path.extension(), path.file_stem()) {
+                    (Some(ext), Some(name)) if ext == TEMPLATE_EXTENSION => {
+                        name.to_string_lossy().into_owned()
+                    }
+                    _ => continue,
+                };
+                let source = fs::read_to_string(&path)
+                    .map_err(|err| TemplateError::IO(err, path.clone()))?;
+
+                tracing::debug!("Using template {path:?} for '{name}'");
+
+                env.add_template_owned(name.clone(), source)
+                    .map_err(|err| TemplateError::Syntax(name, err))?;
+            }
+        }
+
+        Ok(Self { env })
+    }
+
+    pub fn render(&self, name: &str, model: impl Serialize) -> TemplateResult<String> {
+        self.env
+            .get_template(name)
+            .and_then(|template| template.render(model))
+            .map_err(|err| TemplateError::Render(name.into(), err))
+    }
+}
+
+/// What every template gets to know about the schema it's rendered for
+#[derive(Serialize)]
+pub struct SchemaModel<'a> {
+    pub name: &'a String,
+    pub file_name: &'a String,
+    /// Module path inside of its root
+    pub mod_path: String,
+    pub qualified_mod_path: String,
+    pub root: Option<&'a String>,
+    pub rename_all: Option<&'a RenameRule>,
+    pub unknown_keys: UnknownKeys,
+}
+
+impl<'a> From<&'a SchemaInfo> for SchemaModel<'a> {
+    fn from(schema_info: &'a SchemaInfo) -> Self {
+        Self {
+            name: &schema_info.name,
+            file_name: &schema_info.file_name,
+            mod_path: schema_info.mod_path.to_string(),
+            qualified_mod_path: schema_info.qualified_mod_path().to_string(),
+            root: schema_info.root.as_ref(),
+            rename_all: schema_info.rename_all.as_ref(),
+            unknown_keys: schema_info.unknown_keys,
+        }
+    }
+}
+
+pub type TemplateResult<T> = Result<T, TemplateError>;
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Error, Diagnostic)]
+pub enum TemplateError {
+    #[error("[Template] [IO ({1:?})] {0}")]
+    IO(io::Error, PathBuf),
+    #[error("[Template] Couldn't parse '{0}': {1}")]
+    Syntax(String, minijinja::Error),
+    #[error("[Template] Couldn't render '{0}': {1}")]
+    Render(String, minijinja::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::{generator::ts::printer::Quotes, test_utils::temp_dir};
+
+    #[test]
+    fn overrides_and_includes() {
+        let dir = temp_dir();
+        fs::write(
+            dir.path().join("macros.jinja"),
+            "{% macro tag(name) %}@{{ name }}{% endmacro %}",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("enum.jinja"),
+            "{% import \"macros\" as macros %}{{ macros.tag(schema.name) }} {{ schema.name | quoted }}{{ semi }}",
+        )
+        .unwrap();
+        fs::write(dir.path().join("notes.txt"), "{{ not a template").unwrap();
+        let style = TsStyle {
+            quotes: Quotes::Single,
+            semicolons: false,
+            ..TsStyle::default()
+        };
+        let templates = Templates::new(Some(dir.path()), style).unwrap();
+
+        assert_eq!(
+            templates
+                .render("enum", json!({ "schema": { "name": "It's" } }))
+                .unwrap(),
+            "@It's 'It\\'s'"
+        );
+        // The others stay built in
+        assert!(templates
+            .render("imports", json!({ "declarations": ["import x"] }))
+            .unwrap()
+            .starts_with("import x\n"));
+    }
+
+    #[test]
+    fn template_errors() {
+        let dir = temp_dir();
+        fs::write(dir.path().join("class.jinja"), "{{ schema.nam }}").unwrap();
+        let templates = Templates::new(Some(dir.path()), TsStyle::default()).unwrap();
+
+        assert!(matches!(
+            templates.render("class", json!({ "schema": { "name": "A" } })),
+            Err(TemplateError::Render(name, _)) if name == "class"
+        ));
+
+        fs::write(dir.path().join("enum.jinja"), "{% if %}").unwrap();
+        assert!(matches!(
+            Templates::new(Some(dir.path()), TsStyle::default()),
+            Err(TemplateError::Syntax(name, _)) if name == "enum"
+        ));
+        assert!(matches!(
+            Templates::new(Some(&dir.path().join("missing")), TsStyle::default()),
+            Err(TemplateError::IO(..))
+        ));
+    }
+}
@@ -0,0 +1,571 @@
+use std::path::PathBuf;
+
+use convert_case::{Case, Casing};
+use miette::Diagnostic;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::schema::{
+    info::SchemaInfo,
+    module_path::ModulePath,
+    registry::{Registry, RegistryError},
+    unknown_keys::UnknownKeys,
+    validation_info::{ClassSchemaValidationInfo, EnumSchemaValidationInfo},
+    ClassSchema, EnumSchema, MapKeyType, SchemaMemberType,
+};
+
+use super::{
+    backend::{Backend, Imports, SchemaContext},
+    default_variant, import_path, module,
+    ts::{
+        ast::{Decl, Expr, Field, Prop, Type},
+        import_decls,
+        printer::{Printer, TsStyle},
+        runtime_import, schema_module,
+    },
+    ts_file, GeneratorResult, RUNTIME_MODULE,
+};
+
+const RUNTIME_SRC: &str = include_str!("zod/runtime.ts");
+
+/// One module per schema holding its Zod schema (`<Name>Schema`) and the type inferred from it,
+/// printed in `style`
+pub struct ZodBackend {
+    printer: Printer,
+}
+
+impl ZodBackend {
+    pub fn new(style: TsStyle) -> Self {
+        Self {
+            printer: Printer::new(style),
+        }
+    }
+
+    /// The declarations separated by blank lines
+    fn print(&self, decls: &[Decl]) -> String {
+        decls
+            .iter()
+            .map(|decl| self.printer.decl(decl))
+            .collect::<Vec<_>>()
+            .join("\n\n")
+            + "\n"
+    }
+}
+
+impl Backend for ZodBackend {
+    fn name(&self) -> &'static str {
+        "zod"
+    }
+
+    fn file(&self, schema_info: &SchemaInfo) -> PathBuf {
+        ts_file(&module(schema_info))
+    }
+
+    fn support_files(&self, _schema_files: &[PathBuf]) -> Vec<(PathBuf, String)> {
+        vec![(ts_file(RUNTIME_MODULE.as_ref()), RUNTIME_SRC.into())]
+    }
+
+    fn class(
+        &self,
+        ctx: &SchemaContext,
+        class_schema: &ClassSchema,
+        imports: &mut Imports,
+    ) -> GeneratorResult<String> {
+        let SchemaInfo {
+            name, validation, ..
+        } = ctx.schema_info;
+
+        Ok(self.print(&ZodGenerator::new(ctx, imports)?.class(
+            name,
+            class_schema,
+            validation.as_ref().map(|validation| validation.class()),
+        )?))
+    }
+
+    fn enum_(
+        &self,
+        ctx: &SchemaContext,
+        enum_schema: &EnumSchema,
+        imports: &mut Imports,
+    ) -> GeneratorResult<String> {
+        let SchemaInfo {
+            name, validation, ..
+        } = ctx.schema_info;
+
+        Ok(self.print(&ZodGenerator::new(ctx, imports)?.enum_(
+            name,
+            enum_schema,
+            validation.as_ref().map(|validation| validation.enum_()),
+            default_variant(ctx.schema_info, enum_schema)?,
+        )))
+    }
+
+    fn imports(&self, ctx: &SchemaContext, imports: &Imports) -> GeneratorResult<String> {
+        let runtime = runtime_import(ctx, imports)?;
+        let zod = Decl::Import {
+            names: vec!["z".into()],
+            from: "zod".into(),
+        };
+
+        Ok([zod]
+            .into_iter()
+            .chain(import_decls(runtime.as_ref(), imports))
+            .map(|decl| self.printer.decl(&decl) + "\n")
+            .collect::<String>()
+            + "\n")
+    }
+}
+
+pub fn schema_name(name: impl AsRef<str>) -> String {
+    format!("{}Schema", name.as_ref())
+}
+
+/// `z.<name>(args)`
+fn zod(name: &str, args: impl IntoIterator<Item = Expr>) -> Expr {
+    Expr::ident("z").member(name).call(args)
+}
+
+/// Literal of a JSON value
+fn json_literal(value: &serde_json::Value) -> Expr {
+    match value {
+        serde_json::Value::String(str) => Expr::str(str),
+        serde_json::Value::Array(items) => Expr::Array(items.iter().map(json_literal).collect()),
+        serde_json::Value::Object(entries) => Expr::Object(
+            entries
+                .iter()
+                .map(|(key, value)| Prop::KeyValue(key.clone(), json_literal(value)))
+                .collect(),
+        ),
+        value => Expr::Lit(value.to_string()),
+    }
+}
+
+struct ZodGenerator<'a, 'c> {
+    ctx: &'c SchemaContext<'a>,
+    schema_info: &'a SchemaInfo,
+    module: PathBuf,
+    registry: &'a Registry,
+    imports: &'c mut Imports,
+}
+
+impl<'a, 'c> ZodGenerator<'a, 'c> {
+    fn new(ctx: &'c SchemaContext<'a>, imports: &'c mut Imports) -> GeneratorResult<Self> {
+        Ok(Self {
+            ctx,
+            schema_info: ctx.schema_info,
+            module: schema_module(ctx)?,
+            registry: ctx.registry,
+            imports,
+        })
+    }
+
+    fn class(
+        &mut self,
+        name: &str,
+        class_schema: &ClassSchema,
+        validation: Option<ClassSchemaValidationInfo>,
+    ) -> ZodGeneratorResult<Vec<Decl>> {
+        // `z.infer` can't see through `z.lazy`, so the type of cyclic schemas is spelled out
+        let is_cyclic = self.ctx.is_cyclic();
+
+        let mut shape = Vec::with_capacity(class_schema.len());
+        let mut type_fields = Vec::new();
+        let mut renamed = Vec::with_capacity(class_schema.len());
+        let mut is_renamed = false;
+        let mut aliases = Vec::new();
+
+        for (field_name, field_ty) in class_schema.iter() {
+            let (field_ty, is_opt) = match field_ty {
+                SchemaMemberType::Opt(opt_ty) => (opt_ty.clone().into(), true),
+                field_ty => (field_ty.clone(), false),
+            };
+            let default = validation
+                .as_ref()
+                .and_then(|ClassSchemaValidationInfo { defaults, .. }| defaults.as_ref())
+                .and_then(|defaults| defaults.get(field_name));
+            let allow_undefined = validation.as_ref().is_some_and(
+                |ClassSchemaValidationInfo {
+                     allow_undefined, ..
+                 }| allow_undefined.contains(field_name),
+            );
+
+            let mut schema = self.member(&field_ty)?;
+
+            if is_opt {
+                schema = schema.member("nullish").call([]);
+            } else if allow_undefined && default.is_none() {
+                schema = schema.member("optional").call([]);
+            }
+
+            if let Some(default) = default {
+                schema = schema
+                    .member("default")
+                    .call([json_literal(&default.to_json())]);
+            }
+
+            shape.push(Prop::KeyValue(field_name.clone(), schema));
+
+            let property_name = self.schema_info.property_name(field_name);
+
+            if is_cyclic {
+                let ty = match is_opt {
+                    true => Type::Union(vec![self.ty(&field_ty)?, Type::named("null")]),
+                    false => self.ty(&field_ty)?,
+                };
+
+                type_fields.push(Field {
+                    name: property_name.clone(),
+                    ty,
+                    optional: (is_opt || allow_undefined) && default.is_none(),
+                });
+            }
+
+            renamed.push(Prop::KeyValue(
+                property_name.clone(),
+                Expr::ident("value").member(field_name),
+            ));
+            is_renamed |= &property_name != field_name;
+
+            if let Some(field_aliases) = validation
+                .as_ref()
+                .and_then(|ClassSchemaValidationInfo { aliases, .. }| aliases.get(field_name))
+            {
+                aliases.push(Prop::KeyValue(
+                    field_name.clone(),
+                    Expr::Array(field_aliases.iter().map(Expr::str).collect()),
+                ));
+            }
+        }
+
+        let unknown_keys = self.schema_info.unknown_keys;
+
+        let object = zod("object", [Expr::Object(shape)]);
+        let object = match unknown_keys {
+            UnknownKeys::Strict => object.member("strict").call([]),
+            UnknownKeys::Strip => object,
+            UnknownKeys::Passthrough => object.member("passthrough").call([]),
+        };
+
+        let object = match aliases.is_empty() {
+            true => object,
+            false => {
+                self.imports.runtime = true;
+
+                Expr::runtime("withAliases", [Expr::Object(aliases), object])
+            }
+        };
+
+        // Only schemas that are renamed need their output reshaped
+        let object = match is_renamed {
+            true => {
+                if unknown_keys == UnknownKeys::Passthrough {
+                    self.imports.runtime = true;
+
+                    renamed.insert(
+                        0,
+                        Prop::Spread(Expr::runtime(
+                            "omit",
+                            [
+                                Expr::ident("value"),
+                                Expr::Array(class_schema.keys().map(Expr::str).collect()),
+                            ],
+                        )),
+                    );
+                }
+
+                object
+                    .member("transform")
+                    .call([Expr::arrow(["value"], Expr::Object(renamed))])
+            }
+            false => object,
+        };
+
+        let schema_name = schema_name(name);
+
+        Ok(match is_cyclic {
+            true => {
+                let ty = match unknown_keys {
+                    UnknownKeys::Passthrough => {
+                        Type::Intersection(vec![Type::Object(type_fields), Type::record()])
+                    }
+                    _ => Type::Object(type_fields),
+                };
+
+                vec![
+                    Decl::TypeAlias {
+                        name: name.into(),
+                        ty,
+                    }
+                    .export(),
+                    Decl::Const {
+                        name: schema_name,
+                        ty: Some(Type::generic(
+                            "z.ZodType",
+                            [
+                                Type::named(name),
+                                Type::named("z.ZodTypeDef"),
+                                Type::named("unknown"),
+                            ],
+                        )),
+                        value: object,
+                    }
+                    .export(),
+                ]
+            }
+            false => vec![
+                Decl::Const {
+                    name: schema_name.clone(),
+                    ty: None,
+                    value: object,
+                }
+                .export(),
+                inferred_type(name, &schema_name),
+            ],
+        })
+    }
+
+    fn enum_(
+        &mut self,
+        name: &str,
+        variants: &EnumSchema,
+        validation: Option<EnumSchemaValidationInfo>,
+        default: &str,
+    ) -> Vec<Decl> {
+        let schema_name = schema_name(name);
+
+        let schema = zod(
+            "enum",
+            [Expr::Array(variants.iter().map(Expr::str).collect())],
+        );
+
+        let aliases = variants
+            .iter()
+            .flat_map(|variant| {
+                validation
+                    .as_ref()
+                    .and_then(|EnumSchemaValidationInfo { aliases, .. }| aliases.get(variant))
+                    .into_iter()
+                    .flatten()
+                    .map(move |alias| Prop::KeyValue(alias.clone(), Expr::str(variant)))
+            })
+            .collect::<Vec<_>>();
+
+        let schema = match aliases.is_empty() {
+            true => schema,
+            false => {
+                self.imports.runtime = true;
+
+                Expr::runtime("withEnumAliases", [Expr::Object(aliases), schema])
+            }
+        };
+
+        vec![
+            Decl::Const {
+                name: schema_name.clone(),
+                ty: None,
+                value: schema,
+            }
+            .export(),
+            inferred_type(name, &schema_name),
+            Decl::Const {
+                name: format!("{}_DEFAULT", name.to_case(Case::UpperSnake)),
+                ty: Some(Type::named(name)),
+                value: Expr::str(default),
+            }
+            .export(),
+        ]
+    }
+
+    /// Zod schema of the member type
+    fn member(&mut self, schema_member_type: &SchemaMemberType) -> ZodGeneratorResult<Expr> {
+        Ok(match schema_member_type {
+            SchemaMemberType::Num => zod("number", []),
+            SchemaMemberType::Str => zod("string", []),
+            SchemaMemberType::Bool => zod("boolean", []),
+            SchemaMemberType::Arr(arr_ty) => zod("array", [self.member(arr_ty)?]),
+            SchemaMemberType::Map(map_key_ty, map_val_ty) => {
+                let key = match map_key_ty {
+                    MapKeyType::Num => {
+                        self.imports.runtime = true;
+                        Expr::ident("runtime").member("numKey")
+                    }
+                    MapKeyType::Str => zod("string", []),
+                    MapKeyType::DefEnum(id) => self.member(&SchemaMemberType::DefEnum(*id))?,
+                };
+
+                zod("record", [key, self.member(map_val_ty)?])
+            }
+            SchemaMemberType::Opt(opt_ty) => self
+                .member(&opt_ty.clone().into())?
+                .member("nullish")
+                .call([]),
+            SchemaMemberType::DefClass(id) | SchemaMemberType::DefEnum(id) => {
+                let schema_name = Expr::ident(schema_name(&self.reference(id, false)?.name));
+
+                match self.ctx.in_cycle(id) {
+                    true => zod("lazy", [Expr::arrow::<&str>([], schema_name)]),
+                    false => schema_name,
+                }
+            }
+        })
+    }
+
+    /// Output type of the member's Zod schema, for schemas whose type can't be inferred
+    fn ty(&mut self, schema_member_type: &SchemaMemberType) -> ZodGeneratorResult<Type> {
+        Ok(match schema_member_type {
+            SchemaMemberType::Num => Type::named("number"),
+            SchemaMemberType::Str => Type::named("string"),
+            SchemaMemberType::Bool => Type::named("boolean"),
+            SchemaMemberType::Arr(arr_ty) => Type::generic("Array", [self.ty(arr_ty)?]),
+            SchemaMemberType::Map(MapKeyType::DefEnum(id), map_val_ty) => Type::generic(
+                "Partial",
+                [Type::generic(
+                    "Record",
+                    [
+                        Type::named(&self.reference(id, true)?.name),
+                        self.ty(map_val_ty)?,
+                    ],
+                )],
+            ),
+            SchemaMemberType::Map(_, map_val_ty) => {
+                Type::generic("Record", [Type::named("string"), self.ty(map_val_ty)?])
+            }
+            SchemaMemberType::Opt(opt_ty) => Type::Union(vec![
+                self.ty(&opt_ty.clone().into())?,
+                Type::named("null"),
+                Type::named("undefined"),
+            ]),
+            SchemaMemberType::DefClass(id) | SchemaMemberType::DefEnum(id) => {
+                Type::named(&self.reference(id, true)?.name)
+            }
+        })
+    }
+
+    /// Looks up a referenced schema and registers the import of its Zod schema (or type)
+    fn reference(&mut self, id: &Uuid, as_type: bool) -> ZodGeneratorResult<&'a SchemaInfo> {
+        let schema_info = self.registry.get(id)?;
+
+        let module = self
+            .ctx
+            .outputs
+            .module(schema_info)
+            .ok_or_else(|| ZodGeneratorError::NoOutputRoot(schema_info.qualified_mod_path()))?;
+
+        if module != self.module {
+            self.imports.add(
+                import_path(&self.module, &module),
+                match as_type {
+                    true => format!("type {}", schema_info.name),
+                    false => schema_name(&schema_info.name),
+                },
+            );
+        }
+
+        Ok(schema_info)
+    }
+}
+
+/// `export type <name> = z.infer<typeof <schema_name>>;`
+fn inferred_type(name: &str, schema_name: &str) -> Decl {
+    Decl::TypeAlias {
+        name: name.into(),
+        ty: Type::generic("z.infer", [Type::named(format!("typeof {schema_name}"))]),
+    }
+    .export()
+}
+
+pub type ZodGeneratorResult<T> = Result<T, ZodGeneratorError>;
+
+#[derive(Debug, Error, Diagnostic)]
+pub enum ZodGeneratorError {
+    #[error("[Zod] {0}")]
+    Registry(#[from] RegistryError),
+    #[error("[Zod] No output directory for the root of '{0}'")]
+    NoOutputRoot(ModulePath),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::generated;
+
+    /// `a` and `b` reference each other, `c` is only referenced
+    const SCHEMAS: [(&str, &str); 3] = [
+        (
+            "a",
+            r#"(
+                schema: Class({
+                    "n": Num,
+                    "o": Opt(Str),
+                    "m": Map(Str, Bool),
+                    "arr": Arr(DefEnum("c")),
+                    "b": DefClass("b"),
+                }),
+                unknown_keys: Some("strict"),
+            )"#,
+        ),
+        ("b", r#"(schema: Class({ "a": Opt(DefClass("a")) }))"#),
+        ("c", r#"(schema: Enum(["x", "y-z"]))"#),
+    ];
+
+    fn zod(mod_path: &str) -> String {
+        generated(&ZodBackend::new(TsStyle::default()), &SCHEMAS, mod_path).unwrap()
+    }
+
+    #[test]
+    fn class_schemas() {
+        assert!(zod("a").contains(
+            "export const ASchema: z.ZodType<A, z.ZodTypeDef, unknown> = z.object({
+\tn: z.number(),
+\to: z.string().nullish(),
+\tm: z.record(z.string(), z.boolean()),
+\tarr: z.array(CSchema),
+\tb: z.lazy(() => BSchema),
+}).strict();"
+        ));
+        assert!(zod("a").contains("export type A = {\n\tn: number;\n\to?: string | null;\n"));
+        assert!(zod("b").contains("\ta: z.lazy(() => ASchema).nullish(),\n"));
+    }
+
+    #[test]
+    fn enum_schemas() {
+        let c = zod("c");
+
+        assert!(c.contains("export const CSchema = z.enum([\"x\", \"y-z\"]);"));
+        assert!(c.contains("export type C = z.infer<typeof CSchema>;"));
+        assert!(c.contains("export const C_DEFAULT: C = \"x\";"));
+    }
+
+    #[test]
+    fn validation() {
+        let schemas = [
+            (
+                "b",
+                r#"(
+                    schema: Class({ "count": Num, "tag": Str, "k": DefEnum("c") }),
+                    validation: Some((
+                        allow_undefined: ["tag"],
+                        aliases: { "count": ["n"] },
+                        defaults: Some(Class({ "count": 2, "k": "y-z" })),
+                    )),
+                )"#,
+            ),
+            SCHEMAS[2],
+        ];
+        let b = generated(&ZodBackend::new(TsStyle::default()), &schemas, "b").unwrap();
+
+        assert!(b.contains(
+            "= runtime.withAliases(
+\t{ count: [\"n\"] },
+\tz.object({
+\t\tcount: z.number().default(2),
+\t\ttag: z.string().optional(),
+\t\tk: CSchema.default(\"y-z\"),
+\t}),
+);"
+        ));
+        // Outside of cycles the type is inferred from the schema
+        assert!(b.contains("export const BSchema = runtime.withAliases("));
+        assert!(b.contains("export type B = z.infer<typeof BSchema>;"));
+    }
+}
@@ -0,0 +1,44 @@
+import { z } from "zod";
+
+/** Keys of numeric maps as they appear on the wire */
+export const numKey = z.string().regex(/^[+-]?([0-9]+(\.[0-9]*)?|\.[0-9]+)([eE][+-]?[0-9]+)?$/);
+
+function isRecord(value: unknown): value is Record<string, unknown> {
+	return typeof value === "object" && value !== null && !Array.isArray(value);
+}
+
+/** Moves the first defined alias of every key to the key itself, dropping the rest */
+export function withAliases<T extends z.ZodTypeAny>(aliases: Record<string, string[]>, schema: T) {
+	return z.preprocess((value) => {
+		if (!isRecord(value)) {
+			return value;
+		}
+
+		const obj = { ...value };
+
+		for (const [key, keyAliases] of Object.entries(aliases)) {
+			for (const alias of keyAliases) {
+				if (obj[key] === undefined && obj[alias] !== undefined) {
+					obj[key] = obj[alias];
+				}
+
+				delete obj[alias];
+			}
+		}
+
+		return obj;
+	}, schema);
+}
+
+/** Replaces variant aliases with the variants they stand for */
+export function withEnumAliases<T extends z.ZodTypeAny>(aliases: Record<string, string>, schema: T) {
+	return z.preprocess(
+		(value) => (typeof value === "string" && Object.hasOwn(aliases, value) ? aliases[value] : value),
+		schema,
+	);
+}
+
+/** Everything but the given keys, for carrying unknown keys through a rename */
+export function omit(value: Record<string, unknown>, keys: string[]): Record<string, unknown> {
+	return Object.fromEntries(Object.entries(value).filter(([key]) => !keys.includes(key)));
+}
@@ -0,0 +1,427 @@
+use std::path::PathBuf;
+
+use convert_case::{Case, Casing};
+use indexmap::IndexMap;
+use miette::Diagnostic;
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::schema::{
+    info::raw::RawSchemaInfo,
+    is_num_key,
+    module_path::ModulePath,
+    raw::{RawMapKeyType, RawSchema, RawSchemaMemberType},
+};
+
+/// Everything seen at one position across all samples
+#[derive(Debug, Default)]
+struct Shape {
+    /// Values seen, `null`s included
+    seen: usize,
+    nulls: usize,
+    num: bool,
+    str: bool,
+    bool: bool,
+    /// Merged items of every array seen
+    arr: Option<Box<Shape>>,
+    obj: Option<ObjShape>,
+}
+
+#[derive(Debug, Default)]
+struct ObjShape {
+    samples: usize,
+    fields: IndexMap<String, Shape>,
+}
+
+impl Shape {
+    fn merge(&mut self, value: &Value) {
+        self.seen += 1;
+
+        match value {
+            Value::Null => self.nulls += 1,
+            Value::Number(_) => self.num = true,
+            Value::String(_) => self.str = true,
+            Value::Bool(_) => self.bool = true,
+            Value::Array(items) => {
+                let arr = self.arr.get_or_insert_with(Default::default);
+
+                for item in items {
+                    arr.merge(item);
+                }
+            }
+            Value::Object(obj) => self.obj.get_or_insert_with(Default::default).merge(obj),
+        }
+    }
+
+    fn kinds(&self) -> Vec<&'static str> {
+        [
+            (self.num, "number"),
+            (self.str, "string"),
+            (self.bool, "boolean"),
+            (self.arr.is_some(), "array"),
+            (self.obj.is_some(), "object"),
+        ]
+        .into_iter()
+        .filter_map(|(seen, kind)| seen.then_some(kind))
+        .collect()
+    }
+}
+
+impl ObjShape {
+    fn merge(&mut self, obj: &serde_json::Map<String, Value>) {
+        self.samples += 1;
+
+        for (key, value) in obj {
+            self.fields.entry(key.clone()).or_default().merge(value);
+        }
+    }
+
+    /// Objects keyed by data rather than by field names
+    fn map_key(&self) -> Option<RawMapKeyType> {
+        let keys = || self.fields.keys();
+
+        if self.fields.is_empty() {
+            return None;
+        }
+
+        if keys().all(|key| is_num_key(key)) {
+            return Some(RawMapKeyType::Num);
+        }
+
+        keys()
+            .any(|key| {
+                key.starts_with(|char: char| char.is_ascii_digit())
+                    || !key
+                        .chars()
+                        .all(|char| char.is_alphanumeric() || matches!(char, '_' | '$' | '-'))
+            })
+            .then_some(RawMapKeyType::Str)
+    }
+
+    /// All values of a map merged into one shape
+    fn values(&self) -> Shape {
+        let mut values = Shape::default();
+
+        for shape in self.fields.values() {
+            merge_shapes(&mut values, shape);
+        }
+
+        values
+    }
+}
+
+fn merge_shapes(into: &mut Shape, shape: &Shape) {
+    into.seen += shape.seen;
+    into.nulls += shape.nulls;
+    into.num |= shape.num;
+    into.str |= shape.str;
+    into.bool |= shape.bool;
+
+    if let Some(arr) = &shape.arr {
+        merge_shapes(into.arr.get_or_insert_with(Default::default), arr);
+    }
+
+    if let Some(obj) = &shape.obj {
+        let into_obj = into.obj.get_or_insert_with(Default::default);
+        into_obj.samples += obj.samples;
+
+        for (key, field) in &obj.fields {
+            merge_shapes(into_obj.fields.entry(key.clone()).or_default(), field);
+        }
+    }
+}
+
+/// Guesses schemas from sample documents, the root one at `mod_path` and one
+/// next to it for every nested object
+pub fn infer(
+    mod_path: ModulePath,
+    samples: &[PathBuf],
+) -> InferResult<IndexMap<ModulePath, RawSchemaInfo>> {
+    let mut root = ObjShape::default();
+
+    for sample in samples {
+        let sample_str =
+            std::fs::read_to_string(sample).map_err(|err| InferError::IO(err, sample.clone()))?;
+        let value = serde_json::from_str::<Value>(&sample_str)
+            .map_err(|err| InferError::JSON(err, sample.clone()))?;
+
+        // A top level array is a list of samples
+        let values = match value {
+            Value::Array(values) => values,
+            value => vec![value],
+        };
+
+        for value in values {
+            match value {
+                Value::Object(obj) => root.merge(&obj),
+                _ => return Err(InferError::NotAnObject(sample.clone())),
+            }
+        }
+    }
+
+    let mut inferrer = Inferrer {
+        schemas: IndexMap::new(),
+    };
+    inferrer.class(mod_path, &root);
+
+    Ok(inferrer.schemas)
+}
+
+struct Inferrer {
+    schemas: IndexMap<ModulePath, RawSchemaInfo>,
+}
+
+impl Inferrer {
+    fn class(&mut self, mod_path: ModulePath, obj: &ObjShape) {
+        // Reserve the name before nested objects pick theirs
+        let index = self
+            .schemas
+            .insert_full(mod_path.clone(), class_schema_info(Default::default()))
+            .0;
+
+        let schema = obj
+            .fields
+            .iter()
+            .map(|(field_name, shape)| {
+                let ty = self.member(&mod_path, field_name, shape);
+
+                let ty = match shape.seen < obj.samples || shape.nulls > 0 {
+                    true => ty.into_opt(),
+                    false => ty,
+                };
+
+                (field_name.clone(), ty)
+            })
+            .collect::<IndexMap<_, _>>();
+
+        self.schemas[index] = class_schema_info(schema);
+    }
+
+    fn member(&mut self, owner: &ModulePath, name: &str, shape: &Shape) -> RawSchemaMemberType {
+        let kinds = shape.kinds();
+
+        match kinds.as_slice() {
+            [] => {
+                tracing::warn!("{owner}::{name} was never seen with a value, guessing Str");
+                RawSchemaMemberType::Str
+            }
+            [_] => self.single_kind(owner, name, shape),
+            kinds => {
+                tracing::warn!("{owner}::{name} was seen as {kinds:?}, guessing Str");
+                RawSchemaMemberType::Str
+            }
+        }
+    }
+
+    fn single_kind(
+        &mut self,
+        owner: &ModulePath,
+        name: &str,
+        shape: &Shape,
+    ) -> RawSchemaMemberType {
+        if shape.num {
+            return RawSchemaMemberType::Num;
+        }
+
+        if shape.str {
+            return RawSchemaMemberType::Str;
+        }
+
+        if shape.bool {
+            return RawSchemaMemberType::Bool;
+        }
+
+        if let Some(arr) = &shape.arr {
+            let item_name = format!("{name}Item");
+            let item_ty = self.member(owner, &item_name, arr);
+
+            return RawSchemaMemberType::Arr(Box::new(match arr.nulls > 0 {
+                true => item_ty.into_opt(),
+                false => item_ty,
+            }));
+        }
+
+        let obj = shape.obj.as_ref().expect("one kind was seen");
+
+        match obj.map_key() {
+            Some(map_key_ty) => {
+                let values = obj.values();
+                let val_ty = self.member(owner, &format!("{name}Value"), &values);
+
+                RawSchemaMemberType::Map(
+                    map_key_ty,
+                    Box::new(match values.nulls > 0 {
+                        true => val_ty.into_opt(),
+                        false => val_ty,
+                    }),
+                )
+            }
+            None => {
+                let mod_path = self.nested_mod_path(owner, name);
+                self.class(mod_path.clone(), obj);
+
+                RawSchemaMemberType::DefClass(mod_path)
+            }
+        }
+    }
+
+    fn nested_mod_path(&self, owner: &ModulePath, name: &str) -> ModulePath {
+        let base = format!("{}{}", owner.last(), name.to_case(Case::Pascal));
+
+        (1..)
+            .map(|index| match index {
+                1 => owner.with_last(base.clone()),
+                index => owner.with_last(format!("{base}{index}")),
+            })
+            .find(|mod_path| !self.schemas.contains_key(mod_path))
+            .unwrap()
+    }
+}
+
+/// Sample keys are wire names, so the config's `rename_all` applies to them
+fn class_schema_info(schema: IndexMap<String, RawSchemaMemberType>) -> RawSchemaInfo {
+    RawSchemaInfo {
+        name: None,
+        file_name: None,
+        mod_path: None,
+        schema: RawSchema::Class(schema),
+        validation: None,
+        rename_all: None,
+        unknown_keys: None,
+    }
+}
+
+pub type InferResult<T> = Result<T, InferError>;
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Error, Diagnostic)]
+pub enum InferError {
+    #[error("[Infer] [IO ({1:?})] {0}")]
+    IO(std::io::Error, PathBuf),
+    #[error("[Infer] [JSON ({1:?})] {0}")]
+    JSON(serde_json::Error, PathBuf),
+    #[error("[Infer] {0:?} has to hold an object or an array of objects")]
+    NotAnObject(PathBuf),
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::{
+        schema::{
+            info::SchemaInfoDefaults,
+            registry::{RawSchemaEntry, Registry},
+        },
+        test_utils::temp_dir,
+    };
+
+    fn sample(dir: &TempDir, name: &str, contents: &str) -> PathBuf {
+        let path = dir.path().join(format!("{name}.json"));
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    fn infer_ron(samples: &[PathBuf]) -> Vec<(String, String)> {
+        infer(ModulePath::single("sample"), samples)
+            .unwrap()
+            .iter()
+            .map(|(mod_path, raw_schema_info)| {
+                (
+                    mod_path.to_string(),
+                    ron::to_string(&raw_schema_info.schema).unwrap(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn infers() {
+        let dir = temp_dir();
+        let samples = sample(
+            &dir,
+            "infers",
+            r#"[
+                {
+                    "id": 1,
+                    "name": "a",
+                    "tags": ["x", null],
+                    "owner": { "login": "l" },
+                    "scores": { "1": 2, "2.5": 3 },
+                    "labels": { "en-US": "x", "1x": "y" },
+                    "extra": null
+                },
+                { "id": 2, "active": true, "owner": { "login": "m", "site": { "url": "u" } } }
+            ]"#,
+        );
+
+        assert_eq!(
+            infer_ron(&[samples]),
+            [
+                (
+                    "sample".into(),
+                    concat!(
+                        r#"Class({"id":Num,"name":Opt(Str),"tags":Opt(Arr(Opt(Str))),"#,
+                        r#""owner":DefClass("sampleOwner"),"scores":Opt(Map(Num,Num)),"#,
+                        r#""labels":Opt(Map(Str,Str)),"extra":Opt(Str),"active":Opt(Bool)})"#
+                    )
+                    .to_string()
+                ),
+                (
+                    "sampleOwner".into(),
+                    r#"Class({"login":Str,"site":Opt(DefClass("sampleOwnerSite"))})"#.into()
+                ),
+                ("sampleOwnerSite".into(), r#"Class({"url":Str})"#.into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn samples_across_files() {
+        let dir = temp_dir();
+        let first = sample(&dir, "first", r#"{ "id": 1, "name": "a" }"#);
+        let second = sample(&dir, "second", r#"{ "id": 2 }"#);
+        let both = sample(&dir, "both", r#"[{ "id": 1, "name": "a" }, { "id": 2 }]"#);
+
+        assert_eq!(infer_ron(&[first, second]), infer_ron(&[both]));
+    }
+
+    #[test]
+    fn inferred_schemas_are_valid() {
+        let dir = temp_dir();
+        let samples = sample(
+            &dir,
+            "valid",
+            r#"{ "id": 1, "nested": { "values": [{ "x": true }] } }"#,
+        );
+        let entries = infer(ModulePath::single("sample"), &[samples])
+            .unwrap()
+            .into_iter()
+            .map(|(mod_path, schema_info)| RawSchemaEntry {
+                root: None,
+                mod_path,
+                schema_info,
+                path: PathBuf::new(),
+                last_updated: 0,
+            })
+            .collect();
+
+        Registry::default()
+            .process_raw_schemas(entries, &SchemaInfoDefaults::default())
+            .unwrap();
+    }
+
+    #[test]
+    fn rejects_other_documents() {
+        let dir = temp_dir();
+        let samples = sample(&dir, "scalar", "[1]");
+
+        assert!(matches!(
+            infer(ModulePath::single("sample"), &[samples]),
+            Err(InferError::NotAnObject(_))
+        ));
+    }
+}
@@ -0,0 +1,14 @@
+pub mod export;
+pub mod import;
+
+/// Keywords holding the named subschemas a `$ref` can point into
+pub const DEFS_KEYWORDS: [&str; 2] = ["$defs", "definitions"];
+
+/// Escapes a single JSON Pointer reference token
+pub fn escape_pointer_token(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+pub fn unescape_pointer_token(token: &str) -> String {
+    token.replace("~1", "/").replace("~0", "~")
+}
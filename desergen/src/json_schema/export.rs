@@ -0,0 +1,268 @@
+use std::path::{Path, PathBuf};
+
+use miette::Diagnostic;
+use serde_json::{json, Map, Value};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::{
+    generator::{import_path, GeneratedFile, OutputRoots},
+    schema::{
+        info::SchemaInfo,
+        module_path::ModulePath,
+        registry::{Registry, RegistryError},
+        unknown_keys::UnknownKeys,
+        validation_info::{ClassSchemaValidationInfo, EnumSchemaValidationInfo},
+        ClassSchema, EnumSchema, MapKeyType, Schema, SchemaMemberType, NUM_KEY_PATTERN,
+    },
+};
+
+pub const DRAFT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// One JSON Schema document per schema, referencing each other by relative path
+pub fn export(
+    registry: &Registry,
+    outputs: &OutputRoots,
+) -> JsonSchemaExportResult<Vec<GeneratedFile>> {
+    let mut schema_infos = registry.schemas().collect::<Vec<_>>();
+    schema_infos.sort_by_key(|schema_info| schema_info.qualified_mod_path().to_string());
+
+    schema_infos
+        .into_iter()
+        .map(|schema_info| {
+            let module = module(outputs, schema_info)?;
+
+            tracing::info!(
+                "Exporting {} ({})...",
+                schema_info.name,
+                schema_info.qualified_mod_path()
+            );
+
+            let exporter = JsonSchemaExporter {
+                registry,
+                outputs,
+                module: &module,
+            };
+            let document = exporter.document(schema_info)?;
+
+            Ok(GeneratedFile {
+                path: json_file(&module),
+                contents: serde_json::to_string_pretty(&document)
+                    .expect("JSON values always serialize")
+                    + "\n",
+                source: None,
+            })
+        })
+        .collect()
+}
+
+struct JsonSchemaExporter<'a> {
+    registry: &'a Registry,
+    outputs: &'a OutputRoots,
+    module: &'a Path,
+}
+
+impl<'a> JsonSchemaExporter<'a> {
+    fn document(&self, schema_info: &SchemaInfo) -> JsonSchemaExportResult<Value> {
+        let SchemaInfo {
+            name,
+            schema,
+            validation,
+            unknown_keys,
+            ..
+        } = schema_info;
+
+        let mut document = Map::new();
+        document.insert("$schema".into(), DRAFT.into());
+        document.insert("title".into(), name.as_str().into());
+
+        document.extend(match schema {
+            Schema::Class(class_schema) => self.class(
+                class_schema,
+                validation.as_ref().map(|validation| validation.class()),
+                *unknown_keys,
+            )?,
+            Schema::Enum(enum_schema) => Self::enum_(
+                enum_schema,
+                validation.as_ref().map(|validation| validation.enum_()),
+            ),
+        });
+
+        Ok(Value::Object(document))
+    }
+
+    fn class(
+        &self,
+        class_schema: &ClassSchema,
+        validation: Option<ClassSchemaValidationInfo>,
+        unknown_keys: UnknownKeys,
+    ) -> JsonSchemaExportResult<Map<String, Value>> {
+        let mut properties = Map::new();
+        let mut required = Vec::new();
+        let mut required_aliased = Vec::new();
+
+        for (field_name, field_ty) in class_schema {
+            let aliases = validation
+                .as_ref()
+                .and_then(|validation| validation.aliases.get(field_name))
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            let default = validation
+                .as_ref()
+                .and_then(|validation| validation.defaults)
+                .and_then(|defaults| defaults.get(field_name));
+            let allow_undefined = validation
+                .as_ref()
+                .is_some_and(|validation| validation.allow_undefined.contains(field_name));
+
+            let mut property = self.member(field_ty)?;
+
+            if let Some(default) = default {
+                property.insert("default".into(), default.to_json());
+            }
+
+            let names = [field_name].into_iter().chain(aliases).collect::<Vec<_>>();
+
+            for name in &names {
+                properties.insert((*name).clone(), Value::Object(property.clone()));
+            }
+
+            let is_required = !matches!(field_ty, SchemaMemberType::Opt(_))
+                && !allow_undefined
+                && default.is_none();
+
+            match (is_required, names.as_slice()) {
+                (false, _) => {}
+                (true, [name]) => required.push(json!(name)),
+                (true, names) => required_aliased.push(json!({
+                    "anyOf": names
+                        .iter()
+                        .map(|name| json!({ "required": [name] }))
+                        .collect::<Vec<_>>()
+                })),
+            }
+        }
+
+        let mut class = Map::new();
+        class.insert("type".into(), "object".into());
+        class.insert("properties".into(), Value::Object(properties));
+
+        if !required.is_empty() {
+            class.insert("required".into(), Value::Array(required));
+        }
+
+        if !required_aliased.is_empty() {
+            class.insert("allOf".into(), Value::Array(required_aliased));
+        }
+
+        if unknown_keys == UnknownKeys::Strict {
+            class.insert("additionalProperties".into(), false.into());
+        }
+
+        Ok(class)
+    }
+
+    fn enum_(
+        enum_schema: &EnumSchema,
+        validation: Option<EnumSchemaValidationInfo>,
+    ) -> Map<String, Value> {
+        let variants = enum_schema
+            .iter()
+            .flat_map(|variant| {
+                let aliases = validation
+                    .as_ref()
+                    .and_then(|validation| validation.aliases.get(variant));
+
+                [variant].into_iter().chain(aliases.into_iter().flatten())
+            })
+            .map(|variant| variant.as_str().into())
+            .collect();
+
+        let mut enum_ = Map::new();
+        enum_.insert("type".into(), "string".into());
+        enum_.insert("enum".into(), Value::Array(variants));
+
+        if let Some(default) = validation.and_then(|validation| validation.default) {
+            enum_.insert("default".into(), default.as_str().into());
+        }
+
+        enum_
+    }
+
+    fn member(
+        &self,
+        schema_member_type: &SchemaMemberType,
+    ) -> JsonSchemaExportResult<Map<String, Value>> {
+        let member = match schema_member_type {
+            SchemaMemberType::Num => json!({ "type": "number" }),
+            SchemaMemberType::Str => json!({ "type": "string" }),
+            SchemaMemberType::Bool => json!({ "type": "boolean" }),
+            SchemaMemberType::Arr(arr_ty) => json!({
+                "type": "array",
+                "items": self.member(arr_ty)?,
+            }),
+            SchemaMemberType::Map(map_key_ty, map_val_ty) => {
+                let mut map = Map::new();
+                map.insert("type".into(), "object".into());
+
+                match map_key_ty {
+                    MapKeyType::Num => {
+                        map.insert(
+                            "propertyNames".into(),
+                            json!({ "pattern": NUM_KEY_PATTERN }),
+                        );
+                    }
+                    MapKeyType::Str => {}
+                    MapKeyType::DefEnum(id) => {
+                        map.insert("propertyNames".into(), self.reference(id)?);
+                    }
+                }
+
+                map.insert(
+                    "additionalProperties".into(),
+                    Value::Object(self.member(map_val_ty)?),
+                );
+
+                Value::Object(map)
+            }
+            SchemaMemberType::Opt(opt_ty) => json!({
+                "anyOf": [self.member(&opt_ty.clone().into())?, { "type": "null" }],
+            }),
+            SchemaMemberType::DefClass(id) | SchemaMemberType::DefEnum(id) => self.reference(id)?,
+        };
+
+        Ok(match member {
+            Value::Object(member) => member,
+            _ => unreachable!("members are always objects"),
+        })
+    }
+
+    fn reference(&self, id: &Uuid) -> JsonSchemaExportResult<Value> {
+        let target = module(self.outputs, self.registry.get(id)?)?;
+
+        Ok(json!({ "$ref": import_path(self.module, &json_file(&target)) }))
+    }
+}
+
+fn module(outputs: &OutputRoots, schema_info: &SchemaInfo) -> JsonSchemaExportResult<PathBuf> {
+    outputs
+        .module(schema_info)
+        .ok_or_else(|| JsonSchemaExportError::NoOutputRoot(schema_info.qualified_mod_path()))
+}
+
+fn json_file(module: &Path) -> PathBuf {
+    let mut path = module.as_os_str().to_owned();
+    path.push(".json");
+
+    path.into()
+}
+
+pub type JsonSchemaExportResult<T> = Result<T, JsonSchemaExportError>;
+
+#[derive(Debug, Error, Diagnostic)]
+pub enum JsonSchemaExportError {
+    #[error("[JsonSchemaExport] {0}")]
+    Registry(#[from] RegistryError),
+    #[error("[JsonSchemaExport] No output directory for the root of '{0}'")]
+    NoOutputRoot(ModulePath),
+}
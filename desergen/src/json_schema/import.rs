@@ -0,0 +1,856 @@
+use std::{collections::HashSet, fmt::Display, path::PathBuf};
+
+use convert_case::{Case, Casing};
+use indexmap::IndexMap;
+use miette::Diagnostic;
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::{
+    generator::ts::ast::is_identifier,
+    schema::{
+        info::{
+            raw::{RawSchemaInfo, RawSchemaInfoError},
+            SchemaInfoDefaults,
+        },
+        module_path::ModulePath,
+        raw::{RawMapKeyType, RawSchema, RawSchemaMemberType},
+        registry::{RawSchemaEntry, Registry, RegistryInitError},
+        unknown_keys::UnknownKeys,
+        validation_info::raw::{
+            RawDefaultValue, RawSchemaValidationDefaults, RawSchemaValidationInfo,
+        },
+    },
+};
+
+use super::{escape_pointer_token, unescape_pointer_token, DEFS_KEYWORDS};
+
+/// Keywords that change what a schema accepts in ways desergen can't express
+const UNSUPPORTED_KEYWORDS: [&str; 13] = [
+    "allOf",
+    "not",
+    "if",
+    "const",
+    "patternProperties",
+    "prefixItems",
+    "contains",
+    "dependentSchemas",
+    "dependentRequired",
+    "unevaluatedProperties",
+    "unevaluatedItems",
+    "$dynamicRef",
+    "$recursiveRef",
+];
+
+/// A JSON Schema construct that has no desergen equivalent
+#[derive(Debug)]
+pub struct Unsupported {
+    pub location: String,
+    pub reason: String,
+}
+
+impl Display for Unsupported {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.location, self.reason)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct JsonSchemaImport {
+    pub schemas: IndexMap<ModulePath, RawSchemaInfo>,
+    pub unsupported: Vec<Unsupported>,
+}
+
+impl JsonSchemaImport {
+    /// Runs the converted schemas through the same checks as the configured ones
+    pub fn validate(
+        &self,
+        root: Option<&String>,
+        defaults: &SchemaInfoDefaults,
+    ) -> JsonSchemaImportResult<()> {
+        let entries = self
+            .schemas
+            .iter()
+            .map(|(mod_path, raw_schema_info)| RawSchemaEntry {
+                root: root.cloned(),
+                mod_path: mod_path.clone(),
+                schema_info: raw_schema_info.clone(),
+                path: PathBuf::new(),
+                last_updated: 0,
+            })
+            .collect();
+
+        Registry::default().process_raw_schemas(entries, defaults)?;
+
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy)]
+enum TargetKind {
+    Class,
+    Enum,
+}
+
+struct Document {
+    path: PathBuf,
+    mod_path: ModulePath,
+    value: Value,
+}
+
+/// Converts JSON Schema documents, each becoming a schema named after its file,
+/// with its `$defs` nested underneath
+pub fn import(
+    files: &[PathBuf],
+    prefix: Option<&ModulePath>,
+) -> JsonSchemaImportResult<JsonSchemaImport> {
+    let mut documents = IndexMap::<PathBuf, Document>::new();
+
+    for file in files {
+        let path = file
+            .canonicalize()
+            .map_err(|err| JsonSchemaImportError::IO(err, file.clone()))?;
+        let stem = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .ok_or(JsonSchemaImportError::NoFileStem(file.clone()))?
+            .to_case(Case::Camel);
+        let mod_path = match prefix {
+            Some(prefix) => prefix.join(stem),
+            None => ModulePath::single(stem),
+        };
+
+        if documents
+            .values()
+            .any(|document| document.mod_path == mod_path)
+        {
+            return Err(JsonSchemaImportError::DuplicateModulePath(mod_path));
+        }
+
+        let value_str = std::fs::read_to_string(&path)
+            .map_err(|err| JsonSchemaImportError::IO(err, file.clone()))?;
+        let value = serde_json::from_str(&value_str)
+            .map_err(|err| JsonSchemaImportError::JSON(err, file.clone()))?;
+
+        documents.insert(
+            path.clone(),
+            Document {
+                path,
+                mod_path,
+                value,
+            },
+        );
+    }
+
+    let mut importer = JsonSchemaImporter {
+        documents: &documents,
+        reserved: HashSet::new(),
+        inlining: HashSet::new(),
+        import: JsonSchemaImport::default(),
+    };
+
+    // Targets of `$ref`s keep their module paths no matter what gets hoisted
+    for document in documents.values() {
+        importer.reserved.insert(document.mod_path.clone());
+
+        for (_, def_name, _) in defs(&document.value) {
+            importer.reserved.insert(def_mod_path(document, def_name));
+        }
+    }
+
+    for document in documents.values() {
+        importer.document(document);
+    }
+
+    Ok(importer.import)
+}
+
+struct JsonSchemaImporter<'d> {
+    documents: &'d IndexMap<PathBuf, Document>,
+    reserved: HashSet<ModulePath>,
+    /// `$ref`s currently being inlined, to catch cycles
+    inlining: HashSet<String>,
+    import: JsonSchemaImport,
+}
+
+impl<'d> JsonSchemaImporter<'d> {
+    fn document(&mut self, document: &'d Document) {
+        let defs = defs(&document.value).collect::<Vec<_>>();
+
+        match kind(&document.value) {
+            Some(kind) => self.target(
+                document,
+                String::new(),
+                &document.value,
+                document.mod_path.clone(),
+                kind,
+            ),
+            // A document only holding `$defs` is just a container
+            None if !defs.is_empty() => {}
+            None => self.unsupported(
+                document,
+                "",
+                "document root is neither an object with properties nor a string enum",
+            ),
+        }
+
+        for (keyword, def_name, def) in defs {
+            // Everything else gets inlined wherever it's referenced
+            if let Some(kind) = kind(def) {
+                self.target(
+                    document,
+                    def_pointer(keyword, def_name),
+                    def,
+                    def_mod_path(document, def_name),
+                    kind,
+                );
+            }
+        }
+    }
+
+    fn target(
+        &mut self,
+        document: &'d Document,
+        pointer: String,
+        value: &'d Value,
+        mod_path: ModulePath,
+        kind: TargetKind,
+    ) {
+        match kind {
+            TargetKind::Class => self.class(document, pointer, value, mod_path),
+            TargetKind::Enum => self.enum_(document, pointer, value, mod_path),
+        }
+    }
+
+    fn class(
+        &mut self,
+        document: &'d Document,
+        pointer: String,
+        value: &'d Value,
+        mod_path: ModulePath,
+    ) {
+        let properties = value
+            .get("properties")
+            .and_then(Value::as_object)
+            .into_iter()
+            .flatten();
+        let required: HashSet<&str> = value
+            .get("required")
+            .and_then(Value::as_array)
+            .map(|required| required.iter().filter_map(Value::as_str).collect())
+            .unwrap_or_default();
+
+        let mut schema = IndexMap::new();
+        let mut defaults = IndexMap::new();
+
+        for (property, property_value) in properties {
+            let property_pointer =
+                format!("{pointer}/properties/{}", escape_pointer_token(property));
+            let hint = hoisted_mod_path(&mod_path, property);
+
+            let Some(ty) = self.member(document, &property_pointer, property_value, &hint) else {
+                continue;
+            };
+
+            let ty = match required.contains(property.as_str()) {
+                true => ty,
+                false => ty.into_opt(),
+            };
+
+            if let Some(default) = property_value.get("default") {
+                match serde_json::from_value::<RawDefaultValue>(default.clone()) {
+                    Ok(default) => {
+                        defaults.insert(property.clone(), default);
+                    }
+                    Err(_) => self.unsupported(
+                        document,
+                        &property_pointer,
+                        format!("default {default} can't be represented"),
+                    ),
+                }
+            }
+
+            schema.insert(property.clone(), ty);
+        }
+
+        let unknown_keys = match value.get("additionalProperties") {
+            Some(Value::Bool(false)) => Some(UnknownKeys::Strict),
+            Some(Value::Object(_)) => {
+                self.unsupported(
+                    document,
+                    &pointer,
+                    "typed additionalProperties next to properties (kept as passthrough)",
+                );
+
+                Some(UnknownKeys::Passthrough)
+            }
+            _ => None,
+        };
+
+        let validation = (!defaults.is_empty()).then(|| RawSchemaValidationInfo {
+            defaults: Some(RawSchemaValidationDefaults::Class(defaults)),
+            ..Default::default()
+        });
+
+        self.insert(
+            mod_path,
+            RawSchemaInfo {
+                name: None,
+                file_name: None,
+                mod_path: None,
+                schema: RawSchema::Class(schema),
+                validation,
+                // Property names are wire names, so the config's `rename_all` applies to them
+                rename_all: None,
+                unknown_keys,
+            },
+        );
+    }
+
+    fn enum_(
+        &mut self,
+        document: &'d Document,
+        pointer: String,
+        value: &'d Value,
+        mod_path: ModulePath,
+    ) {
+        let variants = enum_variants(value).unwrap_or_default();
+        let validation =
+            value
+                .get("default")
+                .and_then(Value::as_str)
+                .map(|default| RawSchemaValidationInfo {
+                    defaults: Some(RawSchemaValidationDefaults::Enum(default.into())),
+                    ..Default::default()
+                });
+
+        if value.get("default").is_some() && validation.is_none() {
+            self.unsupported(document, &pointer, "non-string enum default");
+        }
+
+        self.insert(
+            mod_path,
+            RawSchemaInfo {
+                name: None,
+                file_name: None,
+                mod_path: None,
+                schema: RawSchema::Enum(variants),
+                validation,
+                rename_all: None,
+                unknown_keys: None,
+            },
+        );
+    }
+
+    fn insert(&mut self, mod_path: ModulePath, raw_schema_info: RawSchemaInfo) {
+        tracing::debug!("Converted {mod_path}");
+
+        self.reserved.insert(mod_path.clone());
+        self.import.schemas.insert(mod_path, raw_schema_info);
+    }
+
+    /// Hoists an inline class or enum into its own schema
+    fn hoist(
+        &mut self,
+        document: &'d Document,
+        pointer: &str,
+        value: &'d Value,
+        hint: &ModulePath,
+        kind: TargetKind,
+    ) -> RawSchemaMemberType {
+        let mod_path = (1..)
+            .map(|index| match index {
+                1 => hint.clone(),
+                index => hint.with_last(format!("{}{index}", hint.last())),
+            })
+            .find(|mod_path| !self.reserved.contains(mod_path))
+            .unwrap();
+
+        self.reserved.insert(mod_path.clone());
+        self.target(document, pointer.into(), value, mod_path.clone(), kind);
+
+        match kind {
+            TargetKind::Class => RawSchemaMemberType::DefClass(mod_path),
+            TargetKind::Enum => RawSchemaMemberType::DefEnum(mod_path),
+        }
+    }
+
+    fn member(
+        &mut self,
+        document: &'d Document,
+        pointer: &str,
+        value: &'d Value,
+        hint: &ModulePath,
+    ) -> Option<RawSchemaMemberType> {
+        let Some(obj) = value.as_object() else {
+            self.unsupported(document, pointer, "boolean schema");
+            return None;
+        };
+
+        if let Some(keyword) = UNSUPPORTED_KEYWORDS
+            .into_iter()
+            .find(|keyword| obj.contains_key(*keyword))
+        {
+            self.unsupported(document, pointer, format!("'{keyword}' keyword"));
+            return None;
+        }
+
+        if let Some(reference) = obj.get("$ref").and_then(Value::as_str) {
+            return self.reference(document, pointer, reference, hint);
+        }
+
+        if obj.contains_key("enum") {
+            return match enum_variants(value) {
+                Some(_) => Some(self.hoist(document, pointer, value, hint, TargetKind::Enum)),
+                None => {
+                    self.unsupported(
+                        document,
+                        pointer,
+                        "enum values have to be strings usable as identifiers",
+                    );
+                    None
+                }
+            };
+        }
+
+        for keyword in ["anyOf", "oneOf"] {
+            if let Some(alternatives) = obj.get(keyword) {
+                return self.nullable(document, pointer, keyword, alternatives, hint);
+            }
+        }
+
+        match obj.get("type") {
+            Some(Value::String(ty)) => self.typed(document, pointer, value, ty, hint),
+            Some(Value::Array(types)) => {
+                let types = types.iter().filter_map(Value::as_str).collect::<Vec<_>>();
+
+                match types.as_slice() {
+                    [ty] => self.typed(document, pointer, value, ty, hint),
+                    ["null", ty] | [ty, "null"] => self
+                        .typed(document, pointer, value, ty, hint)
+                        .map(RawSchemaMemberType::into_opt),
+                    _ => {
+                        self.unsupported(document, pointer, format!("union of types {types:?}"));
+                        None
+                    }
+                }
+            }
+            _ if obj.contains_key("properties") || obj.contains_key("additionalProperties") => {
+                self.typed(document, pointer, value, "object", hint)
+            }
+            _ => {
+                self.unsupported(document, pointer, "schema without a type");
+                None
+            }
+        }
+    }
+
+    /// `anyOf`/`oneOf` are only understood as a way to make a schema nullable
+    fn nullable(
+        &mut self,
+        document: &'d Document,
+        pointer: &str,
+        keyword: &str,
+        alternatives: &'d Value,
+        hint: &ModulePath,
+    ) -> Option<RawSchemaMemberType> {
+        let is_null = |value: &Value| value.get("type").and_then(Value::as_str) == Some("null");
+        let alternatives = alternatives
+            .as_array()
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        let non_null = alternatives
+            .iter()
+            .enumerate()
+            .filter(|(_, alternative)| !is_null(alternative))
+            .collect::<Vec<_>>();
+
+        match (alternatives.len(), non_null.as_slice()) {
+            (2, [(index, value)]) => self
+                .member(
+                    document,
+                    &format!("{pointer}/{keyword}/{index}"),
+                    value,
+                    hint,
+                )
+                .map(RawSchemaMemberType::into_opt),
+            _ => {
+                self.unsupported(
+                    document,
+                    pointer,
+                    format!("'{keyword}' other than a nullable schema"),
+                );
+                None
+            }
+        }
+    }
+
+    fn typed(
+        &mut self,
+        document: &'d Document,
+        pointer: &str,
+        value: &'d Value,
+        ty: &str,
+        hint: &ModulePath,
+    ) -> Option<RawSchemaMemberType> {
+        match ty {
+            "number" | "integer" => Some(RawSchemaMemberType::Num),
+            "string" => Some(RawSchemaMemberType::Str),
+            "boolean" => Some(RawSchemaMemberType::Bool),
+            "array" => match value.get("items") {
+                Some(items) => self
+                    .member(
+                        document,
+                        &format!("{pointer}/items"),
+                        items,
+                        &hint.with_last(format!("{}Item", hint.last())),
+                    )
+                    .map(|item_ty| RawSchemaMemberType::Arr(Box::new(item_ty))),
+                None => {
+                    self.unsupported(document, pointer, "array without items");
+                    None
+                }
+            },
+            "object" => match (value.get("properties"), value.get("additionalProperties")) {
+                (Some(_), _) => Some(self.hoist(document, pointer, value, hint, TargetKind::Class)),
+                (None, Some(val @ Value::Object(_))) => {
+                    let key_ty = self.map_key(document, pointer, value, hint)?;
+                    let val_ty = self.member(
+                        document,
+                        &format!("{pointer}/additionalProperties"),
+                        val,
+                        &hint.with_last(format!("{}Value", hint.last())),
+                    )?;
+
+                    Some(RawSchemaMemberType::Map(key_ty, Box::new(val_ty)))
+                }
+                _ => {
+                    self.unsupported(
+                        document,
+                        pointer,
+                        "object without properties or a typed additionalProperties",
+                    );
+                    None
+                }
+            },
+            ty => {
+                self.unsupported(document, pointer, format!("type '{ty}'"));
+                None
+            }
+        }
+    }
+
+    fn map_key(
+        &mut self,
+        document: &'d Document,
+        pointer: &str,
+        value: &'d Value,
+        hint: &ModulePath,
+    ) -> Option<RawMapKeyType> {
+        let Some(property_names) = value.get("propertyNames") else {
+            return Some(RawMapKeyType::Str);
+        };
+
+        let pointer = format!("{pointer}/propertyNames");
+
+        match self.member(
+            document,
+            &pointer,
+            property_names,
+            &hint.with_last(format!("{}Key", hint.last())),
+        )? {
+            RawSchemaMemberType::Str => Some(RawMapKeyType::Str),
+            RawSchemaMemberType::DefEnum(mod_path) => Some(RawMapKeyType::DefEnum(mod_path)),
+            _ => {
+                self.unsupported(
+                    document,
+                    &pointer,
+                    "propertyNames other than a string or a string enum",
+                );
+                None
+            }
+        }
+    }
+
+    fn reference(
+        &mut self,
+        document: &'d Document,
+        pointer: &str,
+        reference: &str,
+        hint: &ModulePath,
+    ) -> Option<RawSchemaMemberType> {
+        let (file, fragment) = reference.split_once('#').unwrap_or((reference, ""));
+
+        let target_document = match file.is_empty() {
+            true => Some(document),
+            false => document
+                .path
+                .parent()
+                .map(|dir| dir.join(file))
+                .and_then(|path| path.canonicalize().ok())
+                .and_then(|path| self.documents.get(&path)),
+        };
+        let Some(target_document) = target_document else {
+            self.unsupported(
+                document,
+                pointer,
+                format!("$ref '{reference}' points to a document that isn't being imported"),
+            );
+            return None;
+        };
+
+        let def = match fragment {
+            "" | "/" => None,
+            fragment => {
+                let def = DEFS_KEYWORDS.into_iter().find_map(|keyword| {
+                    let def_name = fragment.strip_prefix(&format!("/{keyword}/"))?;
+                    let def_name = unescape_pointer_token(def_name);
+
+                    target_document
+                        .value
+                        .get(keyword)
+                        .and_then(|defs| defs.get(&def_name))
+                        .map(|def| (keyword, def_name, def))
+                });
+
+                match def {
+                    Some(def) => Some(def),
+                    None => {
+                        self.unsupported(
+                            document,
+                            pointer,
+                            format!(
+                                "$ref '{reference}' doesn't point to the root or a $defs entry"
+                            ),
+                        );
+                        return None;
+                    }
+                }
+            }
+        };
+
+        let (target_pointer, target, target_mod_path) = match &def {
+            Some((keyword, def_name, def)) => (
+                def_pointer(keyword, def_name),
+                *def,
+                def_mod_path(target_document, def_name),
+            ),
+            None => (
+                String::new(),
+                &target_document.value,
+                target_document.mod_path.clone(),
+            ),
+        };
+
+        match kind(target) {
+            Some(TargetKind::Class) => Some(RawSchemaMemberType::DefClass(target_mod_path)),
+            Some(TargetKind::Enum) => Some(RawSchemaMemberType::DefEnum(target_mod_path)),
+            None => {
+                let key = format!("{}#{target_pointer}", target_document.path.display());
+
+                if !self.inlining.insert(key.clone()) {
+                    self.unsupported(document, pointer, format!("recursive $ref '{reference}'"));
+                    return None;
+                }
+
+                let ty = self.member(target_document, &target_pointer, target, hint);
+                self.inlining.remove(&key);
+
+                ty
+            }
+        }
+    }
+
+    fn unsupported(&mut self, document: &Document, pointer: &str, reason: impl Into<String>) {
+        self.import.unsupported.push(Unsupported {
+            location: format!("{}#{pointer}", document.path.display()),
+            reason: reason.into(),
+        });
+    }
+}
+
+/// Only objects with properties and string enums become schemas of their own
+fn kind(value: &Value) -> Option<TargetKind> {
+    match value.get("properties").is_some() {
+        true => Some(TargetKind::Class),
+        false => enum_variants(value).map(|_| TargetKind::Enum),
+    }
+}
+
+fn enum_variants(value: &Value) -> Option<Vec<String>> {
+    value
+        .get("enum")?
+        .as_array()?
+        .iter()
+        .map(|variant| variant.as_str().filter(|variant| is_identifier(variant)))
+        .map(|variant| variant.map(Into::into))
+        .collect::<Option<Vec<_>>>()
+        .filter(|variants| !variants.is_empty())
+}
+
+fn defs(value: &Value) -> impl Iterator<Item = (&'static str, &String, &Value)> {
+    DEFS_KEYWORDS.into_iter().flat_map(move |keyword| {
+        value
+            .get(keyword)
+            .and_then(Value::as_object)
+            .into_iter()
+            .flatten()
+            .map(move |(def_name, def)| (keyword, def_name, def))
+    })
+}
+
+fn def_pointer(keyword: &str, def_name: &str) -> String {
+    format!("/{keyword}/{}", escape_pointer_token(def_name))
+}
+
+fn def_mod_path(document: &Document, def_name: &str) -> ModulePath {
+    document.mod_path.join(def_name.to_case(Case::Camel))
+}
+
+fn hoisted_mod_path(owner: &ModulePath, property: &str) -> ModulePath {
+    owner.with_last(format!(
+        "{}{}",
+        owner.last(),
+        property.to_case(Case::Pascal)
+    ))
+}
+
+pub type JsonSchemaImportResult<T> = Result<T, JsonSchemaImportError>;
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Error, Diagnostic)]
+pub enum JsonSchemaImportError {
+    #[error("[JsonSchemaImport] [IO ({1:?})] {0}")]
+    IO(std::io::Error, PathBuf),
+    #[error("[JsonSchemaImport] [JSON ({1:?})] {0}")]
+    JSON(serde_json::Error, PathBuf),
+    #[error("[JsonSchemaImport] {0:?} has no usable file name")]
+    NoFileStem(PathBuf),
+    #[error("[JsonSchemaImport] Multiple documents map to '{0}'")]
+    DuplicateModulePath(ModulePath),
+    #[error("[JsonSchemaImport] {0} construct(s) couldn't be converted")]
+    Unsupported(usize),
+    #[error("[JsonSchemaImport] {0}")]
+    RawSchemaInfo(#[from] RawSchemaInfoError),
+    #[error("[JsonSchemaImport] {0}")]
+    Registry(#[from] RegistryInitError),
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, fs};
+
+    use super::*;
+    use crate::{generator::OutputRoots, json_schema::export::export, test_utils::temp_dir};
+
+    const USER: &str = r##"{
+        "type": "object",
+        "properties": {
+            "id": { "type": "number" },
+            "name": { "type": "string", "default": "anon" },
+            "role": { "enum": ["admin", "guest"] },
+            "address": {
+                "type": "object",
+                "properties": { "city": { "type": "string" } },
+                "required": ["city"]
+            },
+            "scores": { "type": "object", "additionalProperties": { "type": "number" } },
+            "tags": { "type": "array", "items": { "type": "string" } }
+        },
+        "required": ["id", "role"],
+        "additionalProperties": false
+    }"##;
+
+    /// Every schema in RON by module path, in a stable order
+    fn schemas(import: &JsonSchemaImport) -> BTreeMap<String, String> {
+        import
+            .schemas
+            .iter()
+            .map(|(mod_path, raw_schema_info)| {
+                (
+                    mod_path.to_string(),
+                    ron::to_string(raw_schema_info).unwrap(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn imports() {
+        let dir = temp_dir();
+        fs::write(dir.path().join("user.json"), USER).unwrap();
+
+        let import = import(&[dir.path().join("user.json")], None).unwrap();
+
+        assert!(import.unsupported.is_empty());
+        assert_eq!(
+            schemas(&import),
+            BTreeMap::from([
+                (
+                    "user".into(),
+                    concat!(
+                        r#"(schema:Class({"id":Num,"name":Opt(Str),"role":DefEnum("userRole"),"#,
+                        r#""address":Opt(DefClass("userAddress")),"scores":Opt(Map(Str,Num)),"#,
+                        r#""tags":Opt(Arr(Str))}),validation:Some((defaults:Some(Class({"name":"anon"})))),"#,
+                        r#"unknown_keys:Some("strict"))"#
+                    )
+                    .into()
+                ),
+                (
+                    "userAddress".into(),
+                    r#"(schema:Class({"city":Str}))"#.into()
+                ),
+                (
+                    "userRole".into(),
+                    r#"(schema:Enum(["admin","guest"]))"#.into()
+                ),
+            ])
+        );
+        import
+            .validate(None, &SchemaInfoDefaults::default())
+            .unwrap();
+    }
+
+    #[test]
+    fn round_trips_through_export() {
+        let dir = temp_dir();
+        fs::write(dir.path().join("user.json"), USER).unwrap();
+        let imported = import(&[dir.path().join("user.json")], None).unwrap();
+
+        let entries = imported
+            .schemas
+            .iter()
+            .map(|(mod_path, raw_schema_info)| RawSchemaEntry {
+                root: None,
+                mod_path: mod_path.clone(),
+                schema_info: raw_schema_info.clone(),
+                path: PathBuf::new(),
+                last_updated: 0,
+            })
+            .collect();
+        let mut registry = Registry::default();
+        registry
+            .process_raw_schemas(entries, &SchemaInfoDefaults::default())
+            .unwrap();
+
+        let outputs = OutputRoots::from_iter([(None, dir.path().join("out"))]);
+        let exported = export(&registry, &outputs).unwrap();
+        fs::create_dir_all(dir.path().join("out")).unwrap();
+
+        for file in &exported {
+            fs::write(&file.path, &file.contents).unwrap();
+        }
+
+        let reimported = import(
+            &exported
+                .iter()
+                .map(|file| file.path.clone())
+                .collect::<Vec<_>>(),
+            None,
+        )
+        .unwrap();
+
+        assert!(reimported.unsupported.is_empty());
+        assert_eq!(schemas(&reimported), schemas(&imported));
+    }
+}
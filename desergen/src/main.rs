@@ -1,16 +1,52 @@
+mod cache;
 mod config;
 mod generator;
+mod infer;
+mod json_schema;
+mod scaffold;
 mod schema;
+#[cfg(test)]
+mod test_utils;
+mod watch;
 
-use std::path::PathBuf;
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
 
-use clap::Parser;
-use config::Config;
+use clap::{error::ErrorKind, CommandFactory, Parser, Subcommand};
+use config::{Config, ConfigFormat, ResolvedRoot};
 use miette::IntoDiagnostic;
-use schema::registry::Registry;
+use schema::{
+    discovery::select_schemas,
+    info::{raw::RawSchemaInfo, SchemaInfoDefaults},
+    module_path::ModulePath,
+    registry::{RawSchemaEntry, Registry},
+    root::SchemaRoot,
+};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use uuid::Uuid;
 
-use crate::generator::generate;
+use crate::{
+    cache::{Cache, Inputs},
+    generator::{
+        backend,
+        changes::{changes, Change},
+        ensure_distinct, generate,
+        header::{add_headers, ensure_unmodified},
+        manifest::{Manifest, ManifestEntry},
+        normalize,
+        plugin::Plugin,
+        write_files, GeneratedFile, GeneratorError, OutputRoots,
+    },
+    infer::infer,
+    json_schema::{
+        export::export,
+        import::{import, JsonSchemaImportError},
+    },
+    scaffold::{init, new_schema, SchemaKind},
+    watch::watch,
+};
 
 /// Generate type-safe deserializable TS classes from JSON/JS Object - defining schemas
 #[derive(Parser)]
@@ -20,53 +56,567 @@ struct Args {
     verbose: bool,
     #[arg(short, long)]
     config: Option<PathBuf>,
+    /// Overwrite generated files even when they were edited by hand (generate, watch)
+    #[arg(long)]
+    force: bool,
+    /// Compare the output with the files on disk instead of writing it, failing when they differ (generate)
+    #[arg(long)]
+    check: bool,
+    /// Print the files that would be created, changed or deleted with their diffs instead of writing them (generate)
+    #[arg(long, conflicts_with = "check")]
+    dry_run: bool,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Generate TS code from the configured schemas (default)
+    Generate,
+    /// Write a config file and the schemas directory for a new project
+    Init {
+        /// Format of the config file, defaults to the extension of `--config` or TOML
+        #[arg(short, long)]
+        format: Option<ConfigFormat>,
+    },
+    /// Create a schema file to fill in, adding it to `schemas` unless `include` globs are used
+    New {
+        kind: SchemaKind,
+        mod_path: ModulePath,
+        /// Named schema root to create it in instead of the unnamed one
+        #[arg(short, long)]
+        root: Option<String>,
+    },
+    /// Validate the configured schemas and targets without writing any files
+    Check,
+    /// Regenerate whenever the config or a schema changes, only the changed schemas and their referrers
+    Watch,
+    /// Write a JSON Schema document for every configured schema
+    ExportJsonSchema {
+        /// Directory to write into, defaults to `<desergen_root>/json-schema`
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Convert JSON Schema (draft 2020-12) documents into schema files
+    ImportJsonSchema {
+        files: Vec<PathBuf>,
+        /// Module path the imported schemas are placed under
+        #[arg(short, long)]
+        prefix: Option<ModulePath>,
+        /// Drop constructs that can't be converted instead of failing
+        #[arg(long)]
+        skip_unsupported: bool,
+        /// Named schema root to write into instead of the unnamed one
+        #[arg(short, long)]
+        root: Option<String>,
+    },
+    /// Guess a schema from sample JSON documents, splitting nested objects into their own schemas
+    Infer {
+        /// Module path of the schema for the top level objects
+        mod_path: ModulePath,
+        /// JSON files holding an object or an array of objects each
+        #[arg(required = true)]
+        samples: Vec<PathBuf>,
+        /// Named schema root to write into instead of the unnamed one
+        #[arg(short, long)]
+        root: Option<String>,
+    },
 }
 
 fn main() -> miette::Result<()> {
-    let Args { verbose, config } = Args::parse();
+    let Args {
+        verbose,
+        config,
+        force,
+        check,
+        dry_run,
+        command,
+    } = Args::parse();
 
     init_logging(verbose);
 
-    let config_path = config.unwrap_or(
-        std::env::current_dir()
-            .into_diagnostic()?
-            .join("desergen.toml"),
-    );
+    let command = command.unwrap_or(Command::Generate);
+    reject_generate_flags(&command, force, check, dry_run);
+
+    if let Command::Init { format } = command {
+        return init_project(config, format);
+    }
+
+    let config_path = match config {
+        Some(config_path) => config_path,
+        None => Config::default_path(&std::env::current_dir().into_diagnostic()?),
+    };
     let config_root_dir: PathBuf = config_path.parent().map(Into::into).ok_or(miette::miette!(
         "Couldn't get the parent directory of {config_path:?}"
     ))?;
 
-    let Config {
-        src_root,
-        desergen_root,
-        src_output_root,
-        schemas,
-    } = Config::from_path(config_path)?;
+    let config = Config::from_path(&config_path)?;
+    let roots = config.roots(&config_root_dir)?;
+    let desergen_root = config_root_dir.join(&config.desergen_root);
+    let schema_defaults = &config.schema_defaults;
+
+    match command {
+        Command::Init { .. } => unreachable!("initialized before loading the config"),
+        Command::Generate => {
+            let schema_roots = schema_roots(&roots)?;
+            let outputs = output_roots(&roots);
+
+            if check || dry_run {
+                let registry = registry_from(schema_roots, schema_defaults)?;
+                let mut files =
+                    generate_files(&config, &config_root_dir, &registry, &outputs, None)?;
+                add_headers(&mut files);
+
+                let manifest = Manifest::load(&desergen_root);
+                let manifest_files = manifest.files(&desergen_root);
+
+                return match check {
+                    true => check_output(&files, &outputs, &manifest_files),
+                    false => dry_run_output(&files, &manifest_files, force),
+                };
+            }
+
+            // Forcing regenerates everything, as edited outputs would otherwise be kept
+            let cache = match force {
+                true => Cache::default(),
+                false => Cache::load(&desergen_root),
+            };
+            let inputs = Inputs::read(
+                &config_path,
+                config.templates_dir(&config_root_dir).as_deref(),
+                &schema_roots,
+                &cache,
+            )?;
+
+            // Plugins can change without the config changing
+            let uses_plugins = config
+                .targets
+                .iter()
+                .any(|target| config.plugins.contains_key(target.name()));
+
+            if !uses_plugins && cache.is_fresh(&inputs) {
+                tracing::info!("Generated files are up to date");
+                return Ok(());
+            }
+
+            let registry = registry_from_raw(inputs.raw_schemas(), schema_defaults)?;
+            let only = cache.stale(&inputs, &registry);
+
+            let mut files = generate_files(
+                &config,
+                &config_root_dir,
+                &registry,
+                &outputs,
+                only.as_ref(),
+            )?;
+            add_headers(&mut files);
+
+            write_output(&files, &registry, only.as_ref(), &desergen_root, force)?;
+            cache
+                .update(&inputs, &registry, &files)
+                .save(&desergen_root)?;
+        }
+        Command::Check => {
+            let registry = init_registry(&roots, schema_defaults)?;
+            let templates_dir = config.templates_dir(&config_root_dir);
+
+            for target in &config.targets {
+                if !config.plugins.contains_key(target.name()) {
+                    backend(
+                        target.name(),
+                        &config.rust,
+                        config.ts,
+                        templates_dir.as_deref(),
+                    )?;
+                }
+            }
+
+            tracing::info!("{} schemas are valid", registry.schemas().count());
+        }
+        Command::New {
+            kind,
+            mod_path,
+            root,
+        } => {
+            let root = find_root(&roots, root.as_ref())?;
+            let path = new_schema(kind, &root.schemas_dir, mod_path.clone())?;
+            tracing::info!("Created {path:?}");
+
+            match root.include.is_empty() {
+                true => {
+                    Config::add_schema(&config_path, root.name.as_ref(), &mod_path)?;
+                    tracing::info!("Added \"{mod_path}\" to the schemas of {config_path:?}");
+                }
+                false => tracing::info!("Make sure the `include` globs pick up \"{mod_path}\""),
+            }
+        }
+        Command::Watch => watch(&config_path, &config_root_dir, force)?,
+        Command::ExportJsonSchema { output } => {
+            let registry = init_registry(&roots, schema_defaults)?;
+            let output = output.unwrap_or(desergen_root.join("json-schema"));
+            let outputs = roots
+                .iter()
+                .map(|root| {
+                    let dir = match &root.name {
+                        Some(name) => output.join(name),
+                        None => output.clone(),
+                    };
+
+                    (root.name.clone(), dir)
+                })
+                .collect();
+
+            let files = export(&registry, &outputs)?;
+            write_files(&files)?;
+        }
+        Command::ImportJsonSchema {
+            files,
+            prefix,
+            skip_unsupported,
+            root,
+        } => import_json_schema(
+            find_root(&roots, root.as_ref())?,
+            &files,
+            prefix.as_ref(),
+            skip_unsupported,
+            schema_defaults,
+        )?,
+        Command::Infer {
+            mod_path,
+            samples,
+            root,
+        } => {
+            let root = find_root(&roots, root.as_ref())?;
+            let schemas = infer(mod_path, &samples)?;
+
+            for path in RawSchemaInfo::create_all(&root.schemas_dir, &schemas)? {
+                tracing::info!("Created {path:?}");
+            }
+
+            log_schemas_to_add(schemas.keys());
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes the config file, at `config_path` when given, and the schemas directory of a new project
+fn init_project(config_path: Option<PathBuf>, format: Option<ConfigFormat>) -> miette::Result<()> {
+    let format = match (&config_path, format) {
+        (_, Some(format)) => format,
+        (Some(config_path), None) => ConfigFormat::of(config_path)?,
+        (None, None) => ConfigFormat::TOML,
+    };
+    let config_path = match config_path {
+        Some(config_path) => config_path,
+        None => std::env::current_dir()
+            .into_diagnostic()?
+            .join(format!("desergen.{}", format.extension())),
+    };
+
+    for path in init(&config_path, format)? {
+        tracing::info!("Created {path:?}");
+    }
+
+    Ok(())
+}
+
+/// Output directory of every root
+fn output_roots(roots: &[ResolvedRoot]) -> OutputRoots {
+    roots
+        .iter()
+        .map(|root| (root.name.clone(), root.output_dir.clone()))
+        .collect()
+}
+
+/// Files of every configured target, for the schemas of `only` when set
+fn generate_files(
+    config: &Config,
+    config_root_dir: &Path,
+    registry: &Registry,
+    outputs: &OutputRoots,
+    only: Option<&HashSet<Uuid>>,
+) -> miette::Result<Vec<GeneratedFile>> {
+    let templates_dir = config.templates_dir(config_root_dir);
+    let mut files = vec![];
+
+    for target in &config.targets {
+        let outputs = match target.output_subdir() {
+            Some(subdir) => outputs.join(subdir),
+            None => outputs.clone(),
+        };
+
+        files.extend(match config.plugins.get(target.name()) {
+            Some(plugin) => Plugin {
+                name: target.name(),
+                command: plugin.command(config_root_dir),
+                args: &plugin.args,
+            }
+            .run(registry, &outputs)
+            .map_err(GeneratorError::from)?,
+            None => generate(
+                backend(
+                    target.name(),
+                    &config.rust,
+                    config.ts,
+                    templates_dir.as_deref(),
+                )?
+                .as_ref(),
+                registry,
+                &outputs,
+                only,
+            )?,
+        });
+    }
+
+    ensure_distinct(&files)?;
+
+    Ok(files)
+}
+
+/// Writes the files unless generated ones were edited by hand, then removes the previously
+/// generated files that no schema generates anymore and records the new output in the manifest
+fn write_output(
+    files: &[GeneratedFile],
+    registry: &Registry,
+    only: Option<&HashSet<Uuid>>,
+    desergen_root: &Path,
+    force: bool,
+) -> miette::Result<()> {
+    let manifest = Manifest::load(desergen_root);
+
+    if !force {
+        ensure_unmodified(files, &manifest.files(desergen_root))?;
+    }
+
+    write_files(files)?;
 
-    let desergen_root = config_root_dir.join(desergen_root);
-    let schemas_root_dir = desergen_root.join("schemas");
+    let skipped = registry
+        .entries()
+        .filter(|(id, _)| only.is_some_and(|only| !only.contains(id)))
+        .map(|(_, schema_info)| normalize(&schema_info.path))
+        .collect();
+    let (mut manifest, orphans) = manifest.update(desergen_root, files, &skipped);
 
-    if !schemas_root_dir.exists() {
-        return Err(miette::miette!(
-            "Schemas root path {schemas_root_dir:?} doesn't exist"
-        ));
+    manifest.remove_orphans(desergen_root, orphans, force)?;
+    manifest.save(desergen_root)?;
+
+    Ok(())
+}
+
+/// Reports every file that differs from the generated output, with its diff
+fn check_output(
+    files: &[GeneratedFile],
+    outputs: &OutputRoots,
+    manifest_files: &[(PathBuf, &ManifestEntry)],
+) -> miette::Result<()> {
+    let changes = changes(files, Some(outputs), manifest_files)?;
+
+    for (path, change) in &changes {
+        match change {
+            Change::Created(_) => tracing::error!("{path:?} is missing"),
+            Change::Changed { .. } => tracing::error!("{path:?} is out of date"),
+            Change::Orphaned { .. } => tracing::error!("{path:?} no longer has a schema"),
+        }
+
+        print!("{}", change.diff(path));
+    }
+
+    match changes.is_empty() {
+        true => {
+            tracing::info!("Generated files are up to date");
+            Ok(())
+        }
+        false => Err(GeneratorError::Stale(changes.len()).into()),
+    }
+}
+
+/// Prints what writing the files would create, change and delete along with the diffs,
+/// failing like writing them would on generated files edited by hand
+fn dry_run_output(
+    files: &[GeneratedFile],
+    manifest_files: &[(PathBuf, &ManifestEntry)],
+    force: bool,
+) -> miette::Result<()> {
+    if !force {
+        ensure_unmodified(files, manifest_files)?;
+    }
+
+    // Only the files of the manifest are deleted, unlike the ones merely looking generated
+    let changes = changes(files, None, manifest_files)?;
+
+    for (path, change) in &changes {
+        match change {
+            Change::Created(_) => tracing::info!("Would create {path:?}"),
+            Change::Changed { .. } => tracing::info!("Would change {path:?}"),
+            Change::Orphaned { edited: true, .. } if !force => {
+                tracing::warn!("Would keep {path:?} (edited, pass `--force` to delete it)");
+                continue;
+            }
+            Change::Orphaned { .. } => tracing::info!("Would delete {path:?}"),
+        }
+
+        print!("{}", change.diff(path));
     }
 
-    let registry = {
-        tracing::info!("Initializing registry");
-        let mut registry = Registry::default();
-        registry.process_schema_files(schemas_root_dir, schemas)?;
+    if changes.is_empty() {
+        tracing::info!("Generated files are up to date");
+    }
 
-        tracing::info!("Registry Initialized");
+    Ok(())
+}
 
-        registry
+/// Exits with a usage error when flags of `generate` are passed to another subcommand,
+/// `--force` also applying to `watch`
+fn reject_generate_flags(command: &Command, force: bool, check: bool, dry_run: bool) {
+    let (takes_force, takes_rest) = match command {
+        Command::Generate => (true, true),
+        Command::Watch => (true, false),
+        _ => (false, false),
     };
 
-    generate(&registry)?;
+    let rejected = [
+        ("--force", force && !takes_force),
+        ("--check", check && !takes_rest),
+        ("--dry-run", dry_run && !takes_rest),
+    ]
+    .into_iter()
+    .find_map(|(flag, rejected)| rejected.then_some(flag));
+
+    if let Some(flag) = rejected {
+        let applies_to = match flag {
+            "--force" => "`generate` and `watch`",
+            _ => "`generate`",
+        };
+
+        Args::command()
+            .error(
+                ErrorKind::ArgumentConflict,
+                format!("`{flag}` only applies to {applies_to}"),
+            )
+            .exit();
+    }
+}
+
+/// Processes the schemas of every root that selects any
+fn init_registry(
+    roots: &[ResolvedRoot],
+    schema_defaults: &SchemaInfoDefaults,
+) -> miette::Result<Registry> {
+    registry_from(schema_roots(roots)?, schema_defaults)
+}
+
+/// The schemas selected in every root, the unnamed root only being skipped
+/// when named roots are used instead of it
+fn schema_roots(roots: &[ResolvedRoot]) -> miette::Result<Vec<SchemaRoot>> {
+    let uses_named_roots = roots.iter().any(|root| root.name.is_some());
+
+    roots
+        .iter()
+        .filter(|root| root.name.is_some() || root.selects_schemas() || !uses_named_roots)
+        .map(|root| {
+            let ResolvedRoot {
+                name,
+                schemas_dir,
+                schemas,
+                include,
+                exclude,
+                ..
+            } = root;
+
+            if !schemas_dir.exists() {
+                return Err(miette::miette!(
+                    "Schemas root path {schemas_dir:?} doesn't exist"
+                ));
+            }
+
+            Ok(SchemaRoot {
+                name: name.clone(),
+                dir: schemas_dir.clone(),
+                schemas: select_schemas(schemas_dir, schemas.clone(), include, exclude)?,
+            })
+        })
+        .collect()
+}
+
+fn registry_from(
+    schema_roots: Vec<SchemaRoot>,
+    schema_defaults: &SchemaInfoDefaults,
+) -> miette::Result<Registry> {
+    tracing::info!("Initializing registry");
+    let mut registry = Registry::default();
+    registry.process_schema_files(schema_roots, schema_defaults)?;
+
+    tracing::info!("Registry Initialized");
+
+    Ok(registry)
+}
+
+/// Processes schemas that were already read
+fn registry_from_raw(
+    entries: Vec<RawSchemaEntry>,
+    schema_defaults: &SchemaInfoDefaults,
+) -> miette::Result<Registry> {
+    tracing::info!("Initializing registry");
+    let mut registry = Registry::default();
+    registry.process_raw_schemas(entries, schema_defaults)?;
+
+    tracing::info!("Registry Initialized");
+
+    Ok(registry)
+}
+
+fn find_root<'a>(
+    roots: &'a [ResolvedRoot],
+    name: Option<&String>,
+) -> miette::Result<&'a ResolvedRoot> {
+    roots
+        .iter()
+        .find(|root| root.name.as_ref() == name)
+        .ok_or_else(|| miette::miette!("There is no schema root named {name:?}"))
+}
+
+fn import_json_schema(
+    root: &ResolvedRoot,
+    files: &[PathBuf],
+    prefix: Option<&ModulePath>,
+    skip_unsupported: bool,
+    schema_defaults: &SchemaInfoDefaults,
+) -> miette::Result<()> {
+    let import = import(files, prefix)?;
+
+    for unsupported in &import.unsupported {
+        match skip_unsupported {
+            true => tracing::warn!("Skipped {unsupported}"),
+            false => tracing::error!("{unsupported}"),
+        }
+    }
+
+    if !import.unsupported.is_empty() && !skip_unsupported {
+        return Err(JsonSchemaImportError::Unsupported(import.unsupported.len()).into());
+    }
+
+    import.validate(root.name.as_ref(), schema_defaults)?;
+
+    for path in RawSchemaInfo::create_all(&root.schemas_dir, &import.schemas)? {
+        tracing::info!("Created {path:?}");
+    }
+
+    log_schemas_to_add(import.schemas.keys());
 
     Ok(())
 }
 
+fn log_schemas_to_add<'a>(mod_paths: impl Iterator<Item = &'a ModulePath>) {
+    tracing::info!(
+        "Add the new schemas to `schemas` unless `include` picks them up: {}",
+        mod_paths
+            .map(|mod_path| format!("\"{mod_path}\""))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+}
+
 fn init_logging(verbose: bool) {
     std::env::set_var(
         "RUST_LOG",
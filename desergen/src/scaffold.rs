@@ -0,0 +1,147 @@
+use std::path::{Path, PathBuf};
+
+use clap::ValueEnum;
+use indexmap::IndexMap;
+use miette::Diagnostic;
+use thiserror::Error;
+
+use crate::{
+    config::{ConfigError, ConfigFormat, InitConfig},
+    schema::{
+        info::raw::{RawSchemaInfo, RawSchemaInfoError},
+        module_path::ModulePath,
+        raw::RawSchema,
+    },
+};
+
+/// Kind of schema `desergen new` starts
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum SchemaKind {
+    Class,
+    Enum,
+}
+
+/// Writes a config file with the default layout along with its schemas directory,
+/// returning the created paths
+pub fn init(config_path: &Path, format: ConfigFormat) -> ScaffoldResult<Vec<PathBuf>> {
+    if config_path.exists() {
+        return Err(ScaffoldError::AlreadyExists(config_path.into()));
+    }
+
+    let config = InitConfig::default();
+    let schemas_dir = config_path
+        .parent()
+        .unwrap_or(Path::new(""))
+        .join(&config.desergen_root)
+        .join("schemas");
+
+    std::fs::create_dir_all(&schemas_dir)
+        .map_err(|err| ScaffoldError::IO(err, schemas_dir.clone()))?;
+    std::fs::write(config_path, config.contents(format)?)
+        .map_err(|err| ScaffoldError::IO(err, config_path.into()))?;
+
+    Ok(vec![config_path.into(), schemas_dir])
+}
+
+/// Writes a class schema file without members or an enum schema file with a single
+/// placeholder variant, as enums need at least one, returning its path
+pub fn new_schema(
+    kind: SchemaKind,
+    schemas_dir: &Path,
+    mod_path: ModulePath,
+) -> ScaffoldResult<PathBuf> {
+    let schema = match kind {
+        SchemaKind::Class => RawSchema::Class(IndexMap::new()),
+        SchemaKind::Enum => RawSchema::Enum(vec!["Variant".into()]),
+    };
+
+    Ok(RawSchemaInfo {
+        name: None,
+        file_name: None,
+        mod_path: None,
+        schema,
+        validation: None,
+        rename_all: None,
+        unknown_keys: None,
+    }
+    .create(schemas_dir, mod_path)?)
+}
+
+pub type ScaffoldResult<T> = Result<T, ScaffoldError>;
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Error, Diagnostic)]
+pub enum ScaffoldError {
+    #[error("[Scaffold] {0:?} already exists")]
+    AlreadyExists(PathBuf),
+    #[error("[Scaffold] [IO ({1:?})] {0}")]
+    IO(std::io::Error, PathBuf),
+    #[error("[Scaffold] {0}")]
+    Config(#[from] ConfigError),
+    #[error("[Scaffold] {0}")]
+    RawSchemaInfo(#[from] RawSchemaInfoError),
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::{
+        config::Config,
+        schema::{info::SchemaInfoDefaults, registry::Registry, root::SchemaRoot},
+        test_utils::temp_dir,
+    };
+
+    #[test]
+    fn inits_every_format() {
+        for format in [ConfigFormat::TOML, ConfigFormat::JSON, ConfigFormat::YAML] {
+            let dir = temp_dir();
+            let config_path = dir.path().join(format!("desergen.{}", format.extension()));
+            let created = init(&config_path, format).unwrap();
+
+            assert_eq!(
+                created,
+                [config_path.clone(), dir.path().join("desergen/schemas")]
+            );
+            assert!(dir.path().join("desergen/schemas").is_dir());
+            assert!(Config::from_path(&config_path).is_ok());
+            assert!(matches!(
+                init(&config_path, format),
+                Err(ScaffoldError::AlreadyExists(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn new_schemas_are_valid() {
+        let dir = temp_dir();
+        let mod_paths =
+            ["api::order", "api::state"].map(|mod_path| mod_path.parse::<ModulePath>().unwrap());
+        let [order, state] = mod_paths.clone();
+
+        let order_path = new_schema(SchemaKind::Class, dir.path(), order.clone()).unwrap();
+        new_schema(SchemaKind::Enum, dir.path(), state).unwrap();
+
+        assert_eq!(order_path, dir.path().join("api/order.ron"));
+        assert!(fs::read_to_string(&order_path)
+            .unwrap()
+            .starts_with("#![enable(implicit_some)]\n"));
+        assert!(Registry::default()
+            .process_schema_files(
+                vec![SchemaRoot {
+                    name: None,
+                    dir: dir.path().into(),
+                    schemas: mod_paths.to_vec(),
+                }],
+                &SchemaInfoDefaults::default(),
+            )
+            .is_ok());
+        assert!(matches!(
+            new_schema(SchemaKind::Enum, dir.path(), order),
+            Err(ScaffoldError::RawSchemaInfo(
+                RawSchemaInfoError::AlreadyExists(_)
+            ))
+        ));
+    }
+}
@@ -1,12 +1,19 @@
+pub mod discovery;
+pub mod graph;
 pub mod info;
 pub mod module_path;
 pub mod raw;
 pub mod registry;
+pub mod rename_rule;
+pub mod root;
+pub mod unknown_keys;
 pub mod validation_info;
 
-use std::collections::HashMap;
+use std::sync::LazyLock;
 
+use indexmap::IndexMap;
 use miette::Diagnostic;
+use regex::Regex;
 use thiserror::Error;
 use uuid::Uuid;
 
@@ -16,9 +23,24 @@ pub enum Schema {
     Enum(EnumSchema),
 }
 
-pub type ClassSchema = HashMap<String, SchemaMemberType>;
+impl Schema {
+    /// Ids of all schemas referenced by the members, in order of appearance
+    pub fn references(&self) -> Vec<Uuid> {
+        let mut references = Vec::new();
 
-#[derive(Debug)]
+        if let Schema::Class(class_schema) = self {
+            for member_type in class_schema.values() {
+                member_type.collect_references(&mut references);
+            }
+        }
+
+        references
+    }
+}
+
+pub type ClassSchema = IndexMap<String, SchemaMemberType>;
+
+#[derive(Debug, Clone)]
 pub enum SchemaMemberType {
     Num,
     Str,
@@ -30,13 +52,52 @@ pub enum SchemaMemberType {
     DefEnum(Uuid),
 }
 
-#[derive(Debug)]
+impl SchemaMemberType {
+    fn collect_references(&self, references: &mut Vec<Uuid>) {
+        match self {
+            SchemaMemberType::Num | SchemaMemberType::Str | SchemaMemberType::Bool => {}
+            SchemaMemberType::Arr(arr_ty) => arr_ty.collect_references(references),
+            SchemaMemberType::Map(map_key_ty, map_val_ty) => {
+                SchemaMemberType::from(map_key_ty.clone()).collect_references(references);
+                map_val_ty.collect_references(references);
+            }
+            SchemaMemberType::Opt(opt_ty) => {
+                SchemaMemberType::from(opt_ty.clone()).collect_references(references)
+            }
+            SchemaMemberType::DefClass(id) | SchemaMemberType::DefEnum(id) => references.push(*id),
+        }
+    }
+}
+
+/// Keys of numeric maps as they appear on the wire, decimal numbers with an optional exponent.
+/// The runtimes of the generated code accept the same keys
+pub const NUM_KEY_PATTERN: &str = r"^[+-]?([0-9]+(\.[0-9]*)?|\.[0-9]+)([eE][+-]?[0-9]+)?$";
+
+static NUM_KEY: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(NUM_KEY_PATTERN).expect("the numeric key pattern is valid"));
+
+/// Whether a wire key is one of a numeric map
+pub fn is_num_key(key: &str) -> bool {
+    NUM_KEY.is_match(key)
+}
+
+#[derive(Debug, Clone)]
 pub enum MapKeyType {
     Num,
     Str,
     DefEnum(Uuid),
 }
 
+impl From<MapKeyType> for SchemaMemberType {
+    fn from(value: MapKeyType) -> Self {
+        match value {
+            MapKeyType::Num => Self::Num,
+            MapKeyType::Str => Self::Str,
+            MapKeyType::DefEnum(id) => Self::DefEnum(id),
+        }
+    }
+}
+
 impl TryFrom<SchemaMemberType> for MapKeyType {
     type Error = SchemaError;
 
@@ -50,7 +111,7 @@ impl TryFrom<SchemaMemberType> for MapKeyType {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum OptType {
     Num,
     Str,
@@ -61,6 +122,20 @@ pub enum OptType {
     DefEnum(Uuid),
 }
 
+impl From<OptType> for SchemaMemberType {
+    fn from(value: OptType) -> Self {
+        match value {
+            OptType::Num => Self::Num,
+            OptType::Str => Self::Str,
+            OptType::Bool => Self::Bool,
+            OptType::Arr(arr_ty) => Self::Arr(arr_ty),
+            OptType::Map(map_key_ty, map_val_ty) => Self::Map(map_key_ty, map_val_ty),
+            OptType::DefClass(id) => Self::DefClass(id),
+            OptType::DefEnum(id) => Self::DefEnum(id),
+        }
+    }
+}
+
 impl TryFrom<SchemaMemberType> for OptType {
     type Error = SchemaError;
 
@@ -87,3 +162,34 @@ pub enum SchemaError {
     #[error("[Schema] {0:?} is either Num, Str, Bool, Arr, Map, DefClass or DefEnum")]
     SMTOpt(SchemaMemberType),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn num_keys() {
+        for key in ["0", "-1", "+2.5", "3.", ".5", "1e10", "1.5E-3"] {
+            assert!(is_num_key(key), "{key}");
+        }
+
+        for key in ["", "a", "1a", "0x10", "1e", ".", "1 ", "NaN", "Infinity"] {
+            assert!(!is_num_key(key), "{key}");
+        }
+    }
+
+    #[test]
+    fn runtimes_share_the_num_key_pattern() {
+        let js = format!("/{NUM_KEY_PATTERN}/");
+
+        assert!(include_str!("generator/ts/runtime.ts").contains(&js));
+        assert!(include_str!("generator/zod/runtime.ts").contains(&js));
+        // `$` also matches before a trailing newline in Python
+        assert!(
+            include_str!("generator/python/runtime.py").contains(&format!(
+                "r\"{}\\Z\"",
+                NUM_KEY_PATTERN.trim_end_matches('$')
+            ))
+        );
+    }
+}
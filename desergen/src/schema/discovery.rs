@@ -0,0 +1,203 @@
+use std::path::{Path, PathBuf};
+
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use indexmap::IndexSet;
+use miette::Diagnostic;
+use thiserror::Error;
+use walkdir::WalkDir;
+
+use super::{info::raw::SchemaFormat, module_path::ModulePath};
+
+/// Schemas listed explicitly followed by the ones found through `include`/`exclude`
+/// globs, matched against file paths relative to the schemas root (`api/order.ron`).
+/// `*` stays within a directory while `**` spans any number of them
+pub fn select_schemas(
+    schemas_root: impl AsRef<Path>,
+    schemas: Vec<ModulePath>,
+    include: &[String],
+    exclude: &[String],
+) -> DiscoveryResult<Vec<ModulePath>> {
+    if schemas.is_empty() && include.is_empty() {
+        return Err(DiscoveryError::NothingSelected);
+    }
+
+    let mut selected = schemas.into_iter().collect::<IndexSet<_>>();
+
+    if !include.is_empty() {
+        selected.extend(discover(schemas_root, include, exclude)?);
+    }
+
+    Ok(selected.into_iter().collect())
+}
+
+fn discover(
+    schemas_root: impl AsRef<Path>,
+    include: &[String],
+    exclude: &[String],
+) -> DiscoveryResult<Vec<ModulePath>> {
+    let schemas_root = schemas_root.as_ref();
+    let include = glob_set(include)?;
+    let exclude = glob_set(exclude)?;
+
+    let mut discovered = Vec::new();
+
+    for entry in WalkDir::new(schemas_root).sort_by_file_name() {
+        let entry = entry?;
+
+        if !entry.file_type().is_file() {
+            continue;
+        }
+
+        let relative = entry
+            .path()
+            .strip_prefix(schemas_root)
+            .expect("walked paths are inside the root");
+
+        if !include.is_match(relative) || exclude.is_match(relative) {
+            continue;
+        }
+
+        let is_schema_file = relative
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| ext.parse::<SchemaFormat>().is_ok());
+
+        match is_schema_file {
+            true => discovered.push(module_path(relative)?),
+            false => tracing::debug!("Skipping {relative:?}, not a schema file"),
+        }
+    }
+
+    tracing::info!("Discovered {} schema(s)", discovered.len());
+
+    Ok(discovered)
+}
+
+fn glob_set(patterns: &[String]) -> DiscoveryResult<GlobSet> {
+    patterns
+        .iter()
+        .try_fold(
+            GlobSetBuilder::new(),
+            |mut builder, pattern| -> DiscoveryResult<_> {
+                builder.add(
+                    GlobBuilder::new(pattern)
+                        .literal_separator(true)
+                        .build()
+                        .map_err(|err| DiscoveryError::Glob(pattern.clone(), err))?,
+                );
+
+                Ok(builder)
+            },
+        )?
+        .build()
+        .map_err(|err| DiscoveryError::Glob(patterns.join(", "), err))
+}
+
+/// `api/order.ron` -> `api::order`
+fn module_path(relative: &Path) -> DiscoveryResult<ModulePath> {
+    relative
+        .with_extension("")
+        .iter()
+        .map(|component| component.to_str())
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(|| DiscoveryError::NonUnicodePath(relative.into()))?
+        .join("::")
+        .parse()
+        .map_err(|err| DiscoveryError::ModulePath(relative.into(), err))
+}
+
+pub type DiscoveryResult<T> = Result<T, DiscoveryError>;
+
+#[derive(Debug, Error, Diagnostic)]
+pub enum DiscoveryError {
+    #[error("[Discovery] Neither `schemas` nor `include` are set, no schemas to process")]
+    NothingSelected,
+    #[error("[Discovery] Invalid glob '{0}': {1}")]
+    Glob(String, globset::Error),
+    #[error("[Discovery] {0}")]
+    Walk(#[from] walkdir::Error),
+    #[error("[Discovery] {0:?} is not valid unicode")]
+    NonUnicodePath(PathBuf),
+    #[error("[Discovery] {0:?} can't be used as a module path: {1}")]
+    ModulePath(PathBuf, String),
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::test_utils::temp_dir;
+
+    fn selected(
+        schemas: &[&str],
+        include: &[&str],
+        exclude: &[&str],
+    ) -> DiscoveryResult<Vec<String>> {
+        let root = temp_dir();
+
+        for file in [
+            "a.ron",
+            "api/order.yaml",
+            "api/internal/audit.json",
+            "api/notes.md",
+        ] {
+            let path = root.path().join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "").unwrap();
+        }
+
+        let strings = |patterns: &[&str]| {
+            patterns
+                .iter()
+                .map(|pattern| pattern.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        Ok(select_schemas(
+            root.path(),
+            schemas
+                .iter()
+                .map(|schema| schema.parse().unwrap())
+                .collect(),
+            &strings(include),
+            &strings(exclude),
+        )?
+        .iter()
+        .map(ModulePath::to_string)
+        .collect())
+    }
+
+    #[test]
+    fn discovers_schema_files() {
+        assert_eq!(
+            selected(&[], &["**"], &[]).unwrap(),
+            ["a", "api::internal::audit", "api::order"]
+        );
+        assert_eq!(
+            selected(&[], &["api/**"], &["**/internal/**"]).unwrap(),
+            ["api::order"]
+        );
+    }
+
+    #[test]
+    fn keeps_explicit_schemas_first() {
+        assert_eq!(selected(&["b", "a"], &[], &[]).unwrap(), ["b", "a"]);
+        assert_eq!(
+            selected(&["api::order"], &["*.ron", "api/*"], &[]).unwrap(),
+            ["api::order", "a"]
+        );
+    }
+
+    #[test]
+    fn rejects_bad_selections() {
+        assert!(matches!(
+            selected(&[], &[], &["**"]),
+            Err(DiscoveryError::NothingSelected)
+        ));
+        assert!(matches!(
+            selected(&[], &["[a"], &[]),
+            Err(DiscoveryError::Glob(pattern, _)) if pattern == "[a"
+        ));
+    }
+}
@@ -0,0 +1,138 @@
+use std::collections::{HashMap, HashSet};
+
+use uuid::Uuid;
+
+use super::registry::Registry;
+
+/// Index of the strongly connected component of every schema in the reference graph.
+/// A reference stays within its component exactly when it is part of a cycle
+pub fn components(registry: &Registry) -> HashMap<Uuid, usize> {
+    let mut ids = registry.entries().map(|(id, _)| *id).collect::<Vec<_>>();
+    ids.sort();
+
+    let edges = registry
+        .entries()
+        .map(|(id, schema_info)| (*id, schema_info.schema.references()))
+        .collect::<HashMap<_, _>>();
+
+    let mut tarjan = Tarjan {
+        edges: &edges,
+        index: 0,
+        indices: HashMap::new(),
+        low_links: HashMap::new(),
+        stack: Vec::new(),
+        components: HashMap::new(),
+        component_count: 0,
+    };
+
+    for id in ids {
+        if !tarjan.indices.contains_key(&id) {
+            tarjan.visit(id);
+        }
+    }
+
+    tarjan.components
+}
+
+/// The schemas of `ids` along with every schema referencing one of them, directly or through others
+pub fn with_referrers(registry: &Registry, mut ids: HashSet<Uuid>) -> HashSet<Uuid> {
+    loop {
+        let referrers = registry
+            .entries()
+            .filter(|(id, schema_info)| {
+                !ids.contains(id)
+                    && schema_info
+                        .schema
+                        .references()
+                        .iter()
+                        .any(|reference| ids.contains(reference))
+            })
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+
+        if referrers.is_empty() {
+            return ids;
+        }
+
+        ids.extend(referrers);
+    }
+}
+
+struct Tarjan<'a> {
+    edges: &'a HashMap<Uuid, Vec<Uuid>>,
+    index: usize,
+    indices: HashMap<Uuid, usize>,
+    low_links: HashMap<Uuid, usize>,
+    stack: Vec<Uuid>,
+    components: HashMap<Uuid, usize>,
+    component_count: usize,
+}
+
+impl<'a> Tarjan<'a> {
+    fn visit(&mut self, id: Uuid) {
+        self.indices.insert(id, self.index);
+        self.low_links.insert(id, self.index);
+        self.index += 1;
+        self.stack.push(id);
+
+        for &next in self.edges.get(&id).into_iter().flatten() {
+            match self.indices.get(&next) {
+                None => {
+                    self.visit(next);
+                    self.low_links
+                        .insert(id, self.low_links[&id].min(self.low_links[&next]));
+                }
+                Some(&next_index) if !self.components.contains_key(&next) => {
+                    self.low_links
+                        .insert(id, self.low_links[&id].min(next_index));
+                }
+                Some(_) => {}
+            }
+        }
+
+        if self.low_links[&id] == self.indices[&id] {
+            while let Some(member) = self.stack.pop() {
+                self.components.insert(member, self.component_count);
+
+                if member == id {
+                    break;
+                }
+            }
+
+            self.component_count += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::registry;
+
+    #[test]
+    fn transitive_referrers() {
+        let registry = registry(&[
+            ("a", r#"(schema: Class({ "b": DefClass("b") }))"#),
+            ("b", r#"(schema: Class({ "c": DefClass("c") }))"#),
+            ("c", r#"(schema: Class({ "value": Num }))"#),
+            ("d", r#"(schema: Class({ "value": Num }))"#),
+        ])
+        .unwrap();
+        let id = |mod_path: &str| {
+            registry
+                .entries()
+                .find(|(_, schema_info)| schema_info.mod_path.to_string() == mod_path)
+                .map(|(id, _)| *id)
+                .unwrap()
+        };
+
+        assert_eq!(
+            with_referrers(&registry, HashSet::from([id("c")])),
+            HashSet::from([id("a"), id("b"), id("c")])
+        );
+        assert_eq!(
+            with_referrers(&registry, HashSet::from([id("d")])),
+            HashSet::from([id("d")])
+        );
+    }
+}
@@ -1,13 +1,48 @@
 pub mod raw;
 
-use super::{module_path::ModulePath, validation_info::SchemaValidationInfo, Schema};
+use std::path::PathBuf;
+
+use serde::Deserialize;
+
+use super::{
+    module_path::ModulePath,
+    rename_rule::{self, RenameRule},
+    root,
+    unknown_keys::UnknownKeys,
+    validation_info::SchemaValidationInfo,
+    Schema,
+};
 
 #[derive(Debug)]
 pub struct SchemaInfo {
     pub name: String,
     pub file_name: String,
+    /// Path inside of its root
     pub mod_path: ModulePath,
+    pub root: Option<String>,
     pub schema: Schema,
     pub validation: Option<SchemaValidationInfo>,
+    pub rename_all: Option<RenameRule>,
+    pub unknown_keys: UnknownKeys,
+    /// Schema file it was read from
+    pub path: PathBuf,
+    /// Modification time of the schema file in milliseconds since the Unix epoch
     pub last_updated: u128,
 }
+
+impl SchemaInfo {
+    pub fn qualified_mod_path(&self) -> ModulePath {
+        root::qualify(self.root.as_ref(), &self.mod_path)
+    }
+
+    pub fn property_name(&self, field_name: impl AsRef<str>) -> String {
+        rename_rule::property_name(self.rename_all.as_ref(), field_name)
+    }
+}
+
+/// Project-wide values of the settings that every schema can override
+#[derive(Debug, Default, Clone, Deserialize)]
+pub struct SchemaInfoDefaults {
+    pub rename_all: Option<RenameRule>,
+    pub unknown_keys: Option<UnknownKeys>,
+}
@@ -1,20 +1,38 @@
-use std::path::{Path, PathBuf};
+use std::{
+    fmt::{self, Debug, Formatter},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
+use indexmap::IndexMap;
 use miette::Diagnostic;
-use serde::Deserialize;
+use ron::{extensions::Extensions, ser::PrettyConfig};
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-use crate::schema::{
-    module_path::ModulePath, raw::RawSchema, validation_info::SchemaValidationInfo,
+use crate::{
+    config::{ConfigFormat, FormatError},
+    schema::{
+        module_path::ModulePath, raw::RawSchema, rename_rule::RenameRule,
+        unknown_keys::UnknownKeys, validation_info::raw::RawSchemaValidationInfo,
+    },
 };
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct RawSchemaInfo {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub file_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub mod_path: Option<ModulePath>,
     pub schema: RawSchema,
-    pub validation: Option<SchemaValidationInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub validation: Option<RawSchemaValidationInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rename_all: Option<RenameRule>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unknown_keys: Option<UnknownKeys>,
 }
 
 impl RawSchemaInfo {
@@ -22,26 +40,168 @@ impl RawSchemaInfo {
         schemas_root: impl AsRef<Path>,
         mod_path: ModulePath,
     ) -> RawSchemaInfoResult<(Self, PathBuf)> {
-        let path = {
-            let mut path = schemas_root.as_ref().join(PathBuf::from(mod_path));
+        let (format, path) = Self::find(schemas_root, mod_path)?;
 
-            if !path.set_extension("ron") {
-                return Err(RawSchemaInfoError::FailedToSetRonExtension(path));
-            }
+        let schema_file_str = std::fs::read_to_string(&path)
+            .map_err(|err| RawSchemaInfoError::IO(err, path.clone()))?;
 
-            if !path.exists() {
-                return Err(RawSchemaInfoError::DoesNotExist(path));
-            }
+        format
+            .parse(&schema_file_str, &path)
+            .map(|schema_info| (schema_info, path))
+    }
 
-            path
-        };
+    /// The schema file of a module path along with its format, without reading it
+    pub fn find(
+        schemas_root: impl AsRef<Path>,
+        mod_path: ModulePath,
+    ) -> RawSchemaInfoResult<(&'static SchemaFormat, PathBuf)> {
+        let base_path = schemas_root.as_ref().join(PathBuf::from(mod_path.clone()));
 
-        let schema_file_str = std::fs::read_to_string(&path)
+        let mut candidates = SchemaFormat::ALL
+            .iter()
+            .flat_map(|format| format.extensions().iter().map(move |ext| (format, ext)))
+            .map(|(format, ext)| {
+                let mut path = base_path.clone();
+
+                match path.set_extension(ext) {
+                    true => Ok((format, path)),
+                    false => Err(RawSchemaInfoError::FailedToSetExtension(ext, path)),
+                }
+            })
+            .filter(|candidate| candidate.as_ref().map_or(true, |(_, path)| path.exists()))
+            .collect::<RawSchemaInfoResult<Vec<_>>>()?;
+
+        match candidates.len() {
+            0 => Err(RawSchemaInfoError::DoesNotExist(
+                base_path,
+                SchemaFormat::ALL,
+            )),
+            1 => Ok(candidates.remove(0)),
+            _ => Err(RawSchemaInfoError::MultipleFormats(
+                mod_path,
+                candidates.into_iter().map(|(_, path)| path).collect(),
+            )),
+        }
+    }
+
+    /// Whether the module path is taken by a schema file in any format
+    pub fn exists(schemas_root: impl AsRef<Path>, mod_path: ModulePath) -> bool {
+        !matches!(
+            Self::find(schemas_root, mod_path),
+            Err(RawSchemaInfoError::DoesNotExist(..))
+        )
+    }
+
+    /// Writes new RON files for all schemas, checking up front that none of them exist yet
+    pub fn create_all(
+        schemas_root: impl AsRef<Path>,
+        schemas: &IndexMap<ModulePath, Self>,
+    ) -> RawSchemaInfoResult<Vec<PathBuf>> {
+        let schemas_root = schemas_root.as_ref();
+
+        if let Some(mod_path) = schemas
+            .keys()
+            .find(|mod_path| Self::exists(schemas_root, (*mod_path).clone()))
+        {
+            return Err(RawSchemaInfoError::AlreadyExists(mod_path.clone()));
+        }
+
+        schemas
+            .iter()
+            .map(|(mod_path, schema_info)| schema_info.create(schemas_root, mod_path.clone()))
+            .collect()
+    }
+
+    /// Writes the schema as a new RON file, refusing to replace one existing in any format
+    pub fn create(
+        &self,
+        schemas_root: impl AsRef<Path>,
+        mod_path: ModulePath,
+    ) -> RawSchemaInfoResult<PathBuf> {
+        if Self::exists(&schemas_root, mod_path.clone()) {
+            return Err(RawSchemaInfoError::AlreadyExists(mod_path));
+        }
+
+        let mut path = schemas_root.as_ref().join(PathBuf::from(mod_path));
+
+        if !path.set_extension("ron") {
+            return Err(RawSchemaInfoError::FailedToSetExtension("ron", path));
+        }
+
+        let schema_file_str = ron::ser::to_string_pretty(
+            self,
+            PrettyConfig::default().extensions(Extensions::IMPLICIT_SOME),
+        )
+        .map_err(|err| RawSchemaInfoError::RONSer(err, path.clone()))?;
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|err| RawSchemaInfoError::IO(err, parent.into()))?;
+        }
+
+        std::fs::write(&path, schema_file_str + "\n")
             .map_err(|err| RawSchemaInfoError::IO(err, path.clone()))?;
 
-        ron::from_str(&schema_file_str)
-            .map(|schema_info| (schema_info, path.clone()))
-            .map_err(|err| RawSchemaInfoError::RON(err, path))
+        Ok(path)
+    }
+}
+
+/// Formats a schema file can be written in, RON along with every config format
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum SchemaFormat {
+    RON,
+    Config(ConfigFormat),
+}
+
+pub type AllSchemaFormats = [SchemaFormat; 4];
+
+impl SchemaFormat {
+    pub const ALL: AllSchemaFormats = [
+        SchemaFormat::RON,
+        SchemaFormat::Config(ConfigFormat::TOML),
+        SchemaFormat::Config(ConfigFormat::JSON),
+        SchemaFormat::Config(ConfigFormat::YAML),
+    ];
+
+    pub fn extensions(&self) -> &'static [&'static str] {
+        match self {
+            SchemaFormat::RON => &["ron"],
+            SchemaFormat::Config(format) => format.extensions(),
+        }
+    }
+
+    pub fn parse(&self, schema_file_str: &str, path: &Path) -> RawSchemaInfoResult<RawSchemaInfo> {
+        let path = path.to_path_buf();
+
+        match self {
+            SchemaFormat::RON => {
+                ron::from_str(schema_file_str).map_err(|err| RawSchemaInfoError::RON(err, path))
+            }
+            SchemaFormat::Config(format) => format
+                .deserialize(schema_file_str)
+                .map_err(|err| RawSchemaInfoError::Format(err, path)),
+        }
+    }
+}
+
+impl Debug for SchemaFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaFormat::RON => f.write_str("RON"),
+            SchemaFormat::Config(format) => format.fmt(f),
+        }
+    }
+}
+
+impl FromStr for SchemaFormat {
+    type Err = RawSchemaInfoError;
+
+    fn from_str(ext: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|format| format.extensions().contains(&ext.to_lowercase().as_str()))
+            .ok_or(RawSchemaInfoError::WrongFormat(ext.into(), Self::ALL))
     }
 }
 
@@ -50,12 +210,22 @@ pub type RawSchemaInfoResult<T> = Result<T, RawSchemaInfoError>;
 #[allow(clippy::upper_case_acronyms)]
 #[derive(Debug, Error, Diagnostic)]
 pub enum RawSchemaInfoError {
-    #[error("[RawSchemaInfo] Failed to set a '.ron' extension for {0:?}")]
-    FailedToSetRonExtension(PathBuf),
-    #[error("[RawSchemaInfo] {0:?} does not exist")]
-    DoesNotExist(PathBuf),
+    #[error("[RawSchemaInfo] Failed to set a '.{0}' extension for {1:?}")]
+    FailedToSetExtension(&'static str, PathBuf),
+    #[error("[RawSchemaInfo] {0:?} does not exist in any of the formats: {1:?}")]
+    DoesNotExist(PathBuf, AllSchemaFormats),
+    #[error("[RawSchemaInfo] '{0}' exists in multiple formats: {1:?}")]
+    MultipleFormats(ModulePath, Vec<PathBuf>),
+    #[error("[RawSchemaInfo] '{0}' already exists")]
+    AlreadyExists(ModulePath),
+    #[error("[RawSchemaInfo] Wrong Format {0}, Available ones: {1:?}")]
+    WrongFormat(String, AllSchemaFormats),
     #[error("[RawSchemaInfo] [IO ({1:?})] {0}")]
     IO(std::io::Error, PathBuf),
     #[error("[RawSchemaInfo] [RON ({1:?})] {0}")]
     RON(ron::de::SpannedError, PathBuf),
+    #[error("[RawSchemaInfo] [RON ({1:?})] {0}")]
+    RONSer(ron::Error, PathBuf),
+    #[error("[RawSchemaInfo] [{1:?}] {0}")]
+    Format(FormatError, PathBuf),
 }
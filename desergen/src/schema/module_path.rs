@@ -1,17 +1,52 @@
-use std::{fmt::Display, path::PathBuf};
+use std::{fmt::Display, path::PathBuf, str::FromStr};
 
 use serde::{
     de::{Error, Visitor},
-    Deserialize, Deserializer,
+    Deserialize, Deserializer, Serialize, Serializer,
 };
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct ModulePath(Vec<String>);
 
 impl ModulePath {
+    pub fn single(component: impl Into<String>) -> Self {
+        Self(vec![component.into()])
+    }
+
+    pub fn first(&self) -> &String {
+        self.0.first().unwrap()
+    }
+
     pub fn last(&self) -> &String {
         self.0.last().unwrap()
     }
+
+    pub fn parents(&self) -> &[String] {
+        &self.0[..self.0.len() - 1]
+    }
+
+    pub fn join(&self, component: impl Into<String>) -> Self {
+        let mut components = self.0.clone();
+        components.push(component.into());
+
+        Self(components)
+    }
+
+    pub fn prepend(&self, component: impl Into<String>) -> Self {
+        Self(
+            [component.into()]
+                .into_iter()
+                .chain(self.0.clone())
+                .collect(),
+        )
+    }
+
+    pub fn with_last(&self, last: impl Into<String>) -> Self {
+        let mut components = self.0.clone();
+        *components.last_mut().unwrap() = last.into();
+
+        Self(components)
+    }
 }
 
 impl Display for ModulePath {
@@ -39,14 +74,21 @@ impl<'de> Visitor<'de> for ModulePathVisitor {
     where
         E: serde::de::Error,
     {
-        let components = v.split("::").map(Into::into).collect::<Vec<String>>();
+        v.parse().map_err(Error::custom)
+    }
+}
+
+impl FromStr for ModulePath {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let components = s.split("::").map(Into::into).collect::<Vec<String>>();
 
         match components.len() {
-            0 => return Err(Error::custom("Module path is empty!")),
-            1 => match components[0].is_empty() || components[0].chars().all(char::is_whitespace) {
-                true => return Err(Error::custom("Module path is empty!")),
-                false => {}
-            },
+            0 => return Err("Module path is empty!".into()),
+            1 if components[0].is_empty() || components[0].chars().all(char::is_whitespace) => {
+                return Err("Module path is empty!".into())
+            }
             _ => {}
         }
 
@@ -62,3 +104,12 @@ impl<'de> Deserialize<'de> for ModulePath {
         deserializer.deserialize_str(ModulePathVisitor)
     }
 }
+
+impl Serialize for ModulePath {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
@@ -1,18 +1,17 @@
-use std::collections::HashMap;
-
-use serde::Deserialize;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
 
 use super::module_path::ModulePath;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub enum RawSchema {
     Class(RawClassSchema),
     Enum(RawEnumSchema),
 }
 
-pub type RawClassSchema = HashMap<String, RawSchemaMemberType>;
+pub type RawClassSchema = IndexMap<String, RawSchemaMemberType>;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub enum RawSchemaMemberType {
     Num,
     Str,
@@ -24,7 +23,22 @@ pub enum RawSchemaMemberType {
     DefEnum(ModulePath),
 }
 
-#[derive(Debug, Deserialize)]
+impl RawSchemaMemberType {
+    pub fn into_opt(self) -> Self {
+        Self::Opt(match self {
+            Self::Num => RawOptType::Num,
+            Self::Str => RawOptType::Str,
+            Self::Bool => RawOptType::Bool,
+            Self::Arr(arr_ty) => RawOptType::Arr(arr_ty),
+            Self::Map(map_key_ty, map_val_ty) => RawOptType::Map(map_key_ty, map_val_ty),
+            Self::DefClass(mod_path) => RawOptType::DefClass(mod_path),
+            Self::DefEnum(mod_path) => RawOptType::DefEnum(mod_path),
+            Self::Opt(opt_ty) => opt_ty,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub enum RawMapKeyType {
     Num,
     Str,
@@ -41,7 +55,7 @@ impl From<RawMapKeyType> for RawSchemaMemberType {
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub enum RawOptType {
     Num,
     Str,
@@ -1,11 +1,12 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     fs, io,
-    path::Path,
+    path::PathBuf,
     time::{SystemTimeError, UNIX_EPOCH},
 };
 
 use convert_case::{Case, Casing};
+use indexmap::IndexMap;
 use miette::Diagnostic;
 use thiserror::Error;
 use uuid::Uuid;
@@ -13,13 +14,18 @@ use uuid::Uuid;
 use crate::schema::info::raw::RawSchemaInfo;
 
 use super::{
-    info::{
-        raw::{RawSchemaInfoError, RawSchemaInfoResult},
-        SchemaInfo,
-    },
+    info::{raw::RawSchemaInfoError, SchemaInfo, SchemaInfoDefaults},
+    is_num_key,
     module_path::ModulePath,
     raw::{RawSchema, RawSchemaMemberType},
-    MapKeyType, OptType, Schema, SchemaError, SchemaMemberType,
+    rename_rule::{property_name, RenameRule},
+    root::{qualify, resolve, SchemaRoot},
+    unknown_keys::{UnknownKeys, PASSTHROUGH_PROPERTY},
+    validation_info::{
+        raw::{RawDefaultValue, RawSchemaValidationDefaults, RawSchemaValidationInfo},
+        DefaultValue, SchemaValidationDefaults, SchemaValidationInfo,
+    },
+    ClassSchema, EnumSchema, MapKeyType, OptType, Schema, SchemaError, SchemaMemberType,
 };
 
 #[derive(Debug, Default)]
@@ -32,37 +38,94 @@ impl Registry {
         self.mapping.values()
     }
 
+    pub fn entries(&self) -> impl Iterator<Item = (&Uuid, &SchemaInfo)> {
+        self.mapping.iter()
+    }
+
     pub fn get(&self, id: &Uuid) -> RegistryResult<&SchemaInfo> {
         self.mapping.get(id).ok_or(RegistryError::IdNotFound(*id))
     }
 
     pub fn process_schema_files(
         &mut self,
-        schemas_root_dir: impl AsRef<Path>,
-        schemas: Vec<ModulePath>,
+        roots: Vec<SchemaRoot>,
+        defaults: &SchemaInfoDefaults,
     ) -> RegistryInitResult<()> {
-        let schemas_root = schemas_root_dir.as_ref();
-
         tracing::info!("Reading schema files...");
 
-        let raw_schemas = schemas
-            .into_iter()
-            .map(|mod_path| {
-                RawSchemaInfo::open(schemas_root, mod_path.clone()).map(|raw| (mod_path, raw))
-            })
-            .collect::<RawSchemaInfoResult<Vec<_>>>()?;
+        let mut entries = Vec::new();
+
+        for SchemaRoot { name, dir, schemas } in roots {
+            for mod_path in schemas {
+                let (schema_info, path) = RawSchemaInfo::open(&dir, mod_path.clone())?;
 
+                let file_meta = fs::metadata(&path)?;
+                let last_updated = file_meta
+                    .modified()?
+                    .duration_since(UNIX_EPOCH)?
+                    .as_millis();
+
+                entries.push(RawSchemaEntry {
+                    root: name.clone(),
+                    mod_path,
+                    schema_info,
+                    path,
+                    last_updated,
+                });
+            }
+        }
+
+        self.process_raw_schemas(entries, defaults)
+    }
+
+    /// Processes schemas that were already read
+    pub fn process_raw_schemas(
+        &mut self,
+        entries: Vec<RawSchemaEntry>,
+        defaults: &SchemaInfoDefaults,
+    ) -> RegistryInitResult<()> {
         tracing::info!("Creating and id map for schemas...");
 
-        let mod_path_id_mapping = HashMap::<ModulePath, Uuid>::from_iter(
-            raw_schemas
-                .iter()
-                .map(|(mod_path, _)| (mod_path.clone(), Uuid::now_v7())),
-        );
+        let mut mod_path_id_mapping = HashMap::<ModulePath, Uuid>::new();
+
+        for RawSchemaEntry { root, mod_path, .. } in &entries {
+            let qualified = qualify(root.as_ref(), mod_path);
+
+            if mod_path_id_mapping
+                .insert(qualified.clone(), Uuid::now_v7())
+                .is_some()
+            {
+                return Err(RegistryInitError::DuplicateModulePath(qualified));
+            }
+        }
+
+        let root_names = entries
+            .iter()
+            .filter_map(|RawSchemaEntry { root, .. }| root.clone())
+            .collect::<HashSet<_>>();
 
         tracing::info!("Processing schemas...");
 
-        for (mod_path, (raw_schema_info, path)) in raw_schemas {
+        let mut raw_defaults = Vec::new();
+
+        for RawSchemaEntry {
+            root,
+            mod_path,
+            schema_info: raw_schema_info,
+            path,
+            last_updated,
+        } in entries
+        {
+            let ids = IdResolver {
+                ids: &mod_path_id_mapping,
+                root_names: &root_names,
+                root: root.as_ref(),
+            };
+            let id = mod_path_id_mapping
+                .get(&qualify(root.as_ref(), &mod_path))
+                .cloned()
+                .ok_or(RegistryInitError::IdNotFound(mod_path.clone()))?;
+
             let maybe_file_name = mod_path.last().clone();
             let maybe_name = maybe_file_name.to_case(Case::Pascal);
 
@@ -72,26 +135,54 @@ impl Registry {
                 mod_path: raw_mod_path,
                 schema,
                 validation,
+                rename_all,
+                unknown_keys,
             } = raw_schema_info;
 
             let file_name = file_name.unwrap_or(maybe_file_name);
             let name = name.unwrap_or(maybe_name);
             let mod_path = raw_mod_path.unwrap_or(mod_path);
+            let rename_all = rename_all.or(defaults.rename_all);
+            let unknown_keys = unknown_keys.or(defaults.unknown_keys).unwrap_or_default();
 
-            tracing::info!("Processing schema for {name} ({mod_path})...");
-            let schema = Self::process_schema(schema, &mod_path_id_mapping)?;
+            tracing::info!(
+                "Processing schema for {name} ({})...",
+                qualify(root.as_ref(), &mod_path)
+            );
+            let schema = Self::process_schema(schema, &ids)?;
             tracing::info!("Done processing");
 
-            let id = mod_path_id_mapping
-                .get(&mod_path)
-                .cloned()
-                .ok_or(RegistryInitError::IdNotFound(mod_path.clone()))?;
+            match &schema {
+                Schema::Class(class_schema) => Self::check_property_names(
+                    &mod_path,
+                    class_schema,
+                    rename_all.as_ref(),
+                    unknown_keys,
+                )?,
+                Schema::Enum(enum_schema) => Self::check_variants(&mod_path, enum_schema)?,
+            }
+
+            let validation = validation
+                .map(|validation| -> RegistryInitResult<_> {
+                    let RawSchemaValidationInfo {
+                        allow_undefined,
+                        aliases,
+                        defaults,
+                    } = validation;
+
+                    Self::check_validation_names(&mod_path, &schema, &allow_undefined, &aliases)?;
 
-            let file_meta = fs::metadata(path)?;
-            let last_updated = file_meta
-                .modified()?
-                .duration_since(UNIX_EPOCH)?
-                .as_millis();
+                    if let Some(defaults) = defaults {
+                        raw_defaults.push((id, defaults));
+                    }
+
+                    Ok(SchemaValidationInfo {
+                        allow_undefined,
+                        aliases,
+                        defaults: None,
+                    })
+                })
+                .transpose()?;
 
             self.mapping.insert(
                 id,
@@ -99,26 +190,335 @@ impl Registry {
                     name,
                     file_name,
                     mod_path,
+                    root,
                     schema,
                     validation,
+                    rename_all,
+                    unknown_keys,
+                    path,
                     last_updated,
                 },
             );
         }
 
+        tracing::info!("Processing defaults...");
+
+        for (id, raw_defaults) in raw_defaults {
+            let defaults = self.process_defaults(&id, raw_defaults)?;
+
+            if let Some(validation) = self
+                .mapping
+                .get_mut(&id)
+                .and_then(|schema_info| schema_info.validation.as_mut())
+            {
+                validation.defaults = Some(defaults);
+            }
+        }
+
         Ok(())
     }
 
-    fn process_schema(
-        raw_schema: RawSchema,
-        mod_path_id_mapping: &HashMap<ModulePath, Uuid>,
-    ) -> RegistryInitResult<Schema> {
+    fn check_property_names(
+        mod_path: &ModulePath,
+        class_schema: &ClassSchema,
+        rename_all: Option<&RenameRule>,
+        unknown_keys: UnknownKeys,
+    ) -> RegistryInitResult<()> {
+        let mut property_names = HashMap::<String, &String>::new();
+
+        for field_name in class_schema.keys() {
+            let property_name = property_name(rename_all, field_name);
+
+            if let Some(other_field_name) = property_names.insert(property_name.clone(), field_name)
+            {
+                return Err(RegistryInitError::PropertyNameClash(
+                    mod_path.clone(),
+                    property_name,
+                    other_field_name.clone(),
+                    field_name.clone(),
+                ));
+            }
+        }
+
+        if unknown_keys == UnknownKeys::Passthrough {
+            if let Some(field_name) = property_names.get(PASSTHROUGH_PROPERTY) {
+                return Err(RegistryInitError::PassthroughPropertyClash(
+                    mod_path.clone(),
+                    (*field_name).clone(),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Every target falls back to the first variant, so there has to be one, and variants
+    /// become members of TS enums, which can't be named like numbers
+    fn check_variants(mod_path: &ModulePath, enum_schema: &EnumSchema) -> RegistryInitResult<()> {
+        if enum_schema.is_empty() {
+            return Err(RegistryInitError::NoVariants(mod_path.clone()));
+        }
+
+        match enum_schema
+            .iter()
+            .find(|variant| variant.starts_with(|char: char| char.is_ascii_digit()))
+        {
+            Some(variant) => Err(RegistryInitError::NumericVariant(
+                mod_path.clone(),
+                variant.clone(),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    fn check_validation_names(
+        mod_path: &ModulePath,
+        schema: &Schema,
+        allow_undefined: &[String],
+        aliases: &HashMap<String, Vec<String>>,
+    ) -> RegistryInitResult<()> {
+        let names = match schema {
+            Schema::Class(class_schema) => class_schema.keys().collect::<Vec<_>>(),
+            Schema::Enum(enum_schema) => {
+                if !allow_undefined.is_empty() {
+                    return Err(RegistryInitError::AllowUndefinedOnEnum(mod_path.clone()));
+                }
+
+                enum_schema.iter().collect()
+            }
+        };
+
+        if let Some(unknown) = allow_undefined
+            .iter()
+            .chain(aliases.keys())
+            .find(|name| !names.contains(name))
+        {
+            return Err(RegistryInitError::UnknownName(
+                mod_path.clone(),
+                unknown.clone(),
+            ));
+        }
+
+        let mut used = names.into_iter().collect::<HashSet<_>>();
+
+        for alias in aliases.values().flatten() {
+            if !used.insert(alias) {
+                return Err(RegistryInitError::DuplicateAlias(
+                    mod_path.clone(),
+                    alias.clone(),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn process_defaults(
+        &self,
+        id: &Uuid,
+        raw_defaults: RawSchemaValidationDefaults,
+    ) -> RegistryInitResult<SchemaValidationDefaults> {
+        let SchemaInfo {
+            mod_path, schema, ..
+        } = self
+            .mapping
+            .get(id)
+            .ok_or(RegistryInitError::IdNotFoundForDefaults(*id))?;
+
+        match (schema, raw_defaults) {
+            (Schema::Class(class_schema), RawSchemaValidationDefaults::Class(raw_defaults)) => {
+                raw_defaults
+                    .into_iter()
+                    .map(|(field_name, raw_default)| {
+                        let field_ty =
+                            class_schema
+                                .get(&field_name)
+                                .ok_or(RegistryInitError::UnknownName(
+                                    mod_path.clone(),
+                                    field_name.clone(),
+                                ))?;
+
+                        self.process_default_value(field_ty, raw_default)
+                            .map(|default| (field_name.clone(), default))
+                            .map_err(|err| {
+                                RegistryInitError::Default(mod_path.clone(), field_name, err)
+                            })
+                    })
+                    .collect::<RegistryInitResult<IndexMap<_, _>>>()
+                    .map(SchemaValidationDefaults::Class)
+            }
+            (Schema::Enum(enum_schema), RawSchemaValidationDefaults::Enum(variant)) => {
+                match enum_schema.contains(&variant) {
+                    true => Ok(SchemaValidationDefaults::Enum(variant)),
+                    false => Err(RegistryInitError::UnknownName(mod_path.clone(), variant)),
+                }
+            }
+            (Schema::Class(_), RawSchemaValidationDefaults::Enum(_)) => Err(
+                RegistryInitError::DefaultsKind(mod_path.clone(), "Class", "Enum"),
+            ),
+            (Schema::Enum(_), RawSchemaValidationDefaults::Class(_)) => Err(
+                RegistryInitError::DefaultsKind(mod_path.clone(), "Enum", "Class"),
+            ),
+        }
+    }
+
+    fn process_default_value(
+        &self,
+        schema_member_type: &SchemaMemberType,
+        raw_default: RawDefaultValue,
+    ) -> DefaultValueResult<DefaultValue> {
+        match (schema_member_type, raw_default) {
+            (SchemaMemberType::Num, RawDefaultValue::Num(num)) => Ok(DefaultValue::Num(num)),
+            (SchemaMemberType::Str, RawDefaultValue::Str(str)) => Ok(DefaultValue::Str(str)),
+            (SchemaMemberType::Bool, RawDefaultValue::Bool(bool)) => Ok(DefaultValue::Bool(bool)),
+            (SchemaMemberType::Arr(arr_ty), RawDefaultValue::Arr(items)) => items
+                .into_iter()
+                .map(|item| self.process_default_value(arr_ty, item))
+                .collect::<DefaultValueResult<Vec<_>>>()
+                .map(DefaultValue::Arr),
+            (SchemaMemberType::Map(map_key_ty, map_val_ty), RawDefaultValue::Map(entries)) => {
+                let map_key_ty = SchemaMemberType::from(map_key_ty.clone());
+
+                entries
+                    .into_iter()
+                    .map(|(key, val)| {
+                        let key = match (&map_key_ty, key) {
+                            // Keys of JSON-like formats are always strings, held to the same
+                            // pattern as the keys of documents
+                            (SchemaMemberType::Num, RawDefaultValue::Str(key)) => {
+                                match is_num_key(&key) {
+                                    true => DefaultValue::Num(
+                                        key.parse().map_err(|_| DefaultValueError::NumKey(key))?,
+                                    ),
+                                    false => return Err(DefaultValueError::NumKey(key)),
+                                }
+                            }
+                            (SchemaMemberType::Num, RawDefaultValue::Num(key))
+                                if !key.is_finite() =>
+                            {
+                                return Err(DefaultValueError::NumKey(key.to_string()))
+                            }
+                            (map_key_ty, key) => self.process_default_value(map_key_ty, key)?,
+                        };
+
+                        Ok((key, self.process_default_value(map_val_ty, val)?))
+                    })
+                    .collect::<DefaultValueResult<Vec<_>>>()
+                    .map(DefaultValue::Map)
+            }
+            (SchemaMemberType::Opt(opt_ty), raw_default) => {
+                self.process_default_value(&opt_ty.clone().into(), raw_default)
+            }
+            (SchemaMemberType::DefEnum(id), RawDefaultValue::Str(variant)) => {
+                let SchemaInfo {
+                    mod_path, schema, ..
+                } = self
+                    .mapping
+                    .get(id)
+                    .ok_or(DefaultValueError::IdNotFound(*id))?;
+
+                match schema {
+                    Schema::Enum(enum_schema) if enum_schema.contains(&variant) => {
+                        Ok(DefaultValue::DefEnum(*id, variant))
+                    }
+                    _ => Err(DefaultValueError::UnknownVariant(mod_path.clone(), variant)),
+                }
+            }
+            (SchemaMemberType::DefClass(id), RawDefaultValue::Map(entries)) => {
+                let SchemaInfo {
+                    mod_path,
+                    schema,
+                    validation,
+                    ..
+                } = self
+                    .mapping
+                    .get(id)
+                    .ok_or(DefaultValueError::IdNotFound(*id))?;
+
+                let Schema::Class(class_schema) = schema else {
+                    return Err(DefaultValueError::Mismatch(
+                        self.describe(schema_member_type),
+                        "Map",
+                    ));
+                };
+
+                let fields = entries
+                    .into_iter()
+                    .map(|(field_name, val)| {
+                        let field_name = match field_name {
+                            RawDefaultValue::Str(field_name) => field_name,
+                            field_name => {
+                                return Err(DefaultValueError::Mismatch(
+                                    "Str field name".into(),
+                                    field_name.kind(),
+                                ))
+                            }
+                        };
+                        let field_ty = class_schema.get(&field_name).ok_or(
+                            DefaultValueError::UnknownField(mod_path.clone(), field_name.clone()),
+                        )?;
+
+                        Ok((field_name, self.process_default_value(field_ty, val)?))
+                    })
+                    .collect::<DefaultValueResult<IndexMap<_, _>>>()?;
+
+                let allow_undefined = validation
+                    .as_ref()
+                    .map(|validation| validation.allow_undefined.as_slice())
+                    .unwrap_or_default();
+
+                if let Some((missing, _)) = class_schema.iter().find(|(field_name, field_ty)| {
+                    !fields.contains_key(*field_name)
+                        && !matches!(field_ty, SchemaMemberType::Opt(_))
+                        && !allow_undefined.contains(field_name)
+                }) {
+                    return Err(DefaultValueError::MissingField(
+                        mod_path.clone(),
+                        missing.clone(),
+                    ));
+                }
+
+                Ok(DefaultValue::DefClass(*id, fields))
+            }
+            (schema_member_type, raw_default) => Err(DefaultValueError::Mismatch(
+                self.describe(schema_member_type),
+                raw_default.kind(),
+            )),
+        }
+    }
+
+    /// Human readable representation of the member type for error messages
+    fn describe(&self, schema_member_type: &SchemaMemberType) -> String {
+        let describe_ref = |kind: &str, id: &Uuid| match self.mapping.get(id) {
+            Some(SchemaInfo { mod_path, .. }) => format!("{kind}({mod_path})"),
+            None => format!("{kind}({id})"),
+        };
+
+        match schema_member_type {
+            SchemaMemberType::Num => "Num".into(),
+            SchemaMemberType::Str => "Str".into(),
+            SchemaMemberType::Bool => "Bool".into(),
+            SchemaMemberType::Arr(arr_ty) => format!("Arr({})", self.describe(arr_ty)),
+            SchemaMemberType::Map(map_key_ty, map_val_ty) => format!(
+                "Map({}, {})",
+                self.describe(&map_key_ty.clone().into()),
+                self.describe(map_val_ty)
+            ),
+            SchemaMemberType::Opt(opt_ty) => {
+                format!("Opt({})", self.describe(&opt_ty.clone().into()))
+            }
+            SchemaMemberType::DefClass(id) => describe_ref("DefClass", id),
+            SchemaMemberType::DefEnum(id) => describe_ref("DefEnum", id),
+        }
+    }
+
+    fn process_schema(raw_schema: RawSchema, ids: &IdResolver) -> RegistryInitResult<Schema> {
         match raw_schema {
             RawSchema::Class(class_schema) => {
                 let new_class_schema = class_schema
                     .into_iter()
                     .map(|(field_name, field_type)| {
-                        Self::process_schema_member_type(field_type, mod_path_id_mapping)
+                        Self::process_schema_member_type(field_type, ids)
                             .map(|schema_member_type| (field_name, schema_member_type))
                     })
                     .collect::<RegistryInitResult<Vec<_>>>()?
@@ -133,43 +533,64 @@ impl Registry {
 
     fn process_schema_member_type(
         raw_schema_member_type: RawSchemaMemberType,
-        mod_path_id_mapping: &HashMap<ModulePath, Uuid>,
+        ids: &IdResolver,
     ) -> RegistryInitResult<SchemaMemberType> {
         match raw_schema_member_type {
             RawSchemaMemberType::Num => Ok(SchemaMemberType::Num),
             RawSchemaMemberType::Str => Ok(SchemaMemberType::Str),
             RawSchemaMemberType::Bool => Ok(SchemaMemberType::Bool),
-            RawSchemaMemberType::Arr(arr_ty) => {
-                Self::process_schema_member_type(*arr_ty, mod_path_id_mapping)
-                    .map(|schema_member_type| SchemaMemberType::Arr(Box::new(schema_member_type)))
-            }
+            RawSchemaMemberType::Arr(arr_ty) => Self::process_schema_member_type(*arr_ty, ids)
+                .map(|schema_member_type| SchemaMemberType::Arr(Box::new(schema_member_type))),
             RawSchemaMemberType::Map(map_key_ty, map_val_ty) => {
                 let map_key_ty = MapKeyType::try_from(Self::process_schema_member_type(
                     map_key_ty.into(),
-                    mod_path_id_mapping,
+                    ids,
                 )?)?;
-                let map_val_ty =
-                    Self::process_schema_member_type(*map_val_ty, mod_path_id_mapping)?;
+                let map_val_ty = Self::process_schema_member_type(*map_val_ty, ids)?;
 
                 Ok(SchemaMemberType::Map(map_key_ty, Box::new(map_val_ty)))
             }
             RawSchemaMemberType::Opt(opt_ty) => Ok(SchemaMemberType::Opt(OptType::try_from(
-                Self::process_schema_member_type(opt_ty.into(), mod_path_id_mapping)?,
+                Self::process_schema_member_type(opt_ty.into(), ids)?,
             )?)),
-            RawSchemaMemberType::DefClass(mod_path) => mod_path_id_mapping
-                .get(&mod_path)
-                .ok_or(RegistryInitError::IdNotFound(mod_path))
-                .cloned()
-                .map(SchemaMemberType::DefClass),
-            RawSchemaMemberType::DefEnum(mod_path) => mod_path_id_mapping
-                .get(&mod_path)
-                .cloned()
-                .ok_or(RegistryInitError::IdNotFound(mod_path))
-                .map(SchemaMemberType::DefEnum),
+            RawSchemaMemberType::DefClass(mod_path) => {
+                ids.id(&mod_path).map(SchemaMemberType::DefClass)
+            }
+            RawSchemaMemberType::DefEnum(mod_path) => {
+                ids.id(&mod_path).map(SchemaMemberType::DefEnum)
+            }
         }
     }
 }
 
+/// Schema file contents along with where they were found
+pub struct RawSchemaEntry {
+    pub root: Option<String>,
+    pub mod_path: ModulePath,
+    pub schema_info: RawSchemaInfo,
+    /// Schema file it was read from
+    pub path: PathBuf,
+    pub last_updated: u128,
+}
+
+/// Finds the ids of schemas referenced from one root
+struct IdResolver<'a> {
+    ids: &'a HashMap<ModulePath, Uuid>,
+    root_names: &'a HashSet<String>,
+    root: Option<&'a String>,
+}
+
+impl<'a> IdResolver<'a> {
+    fn id(&self, reference: &ModulePath) -> RegistryInitResult<Uuid> {
+        let qualified = resolve(self.root_names, self.root, reference);
+
+        self.ids
+            .get(&qualified)
+            .cloned()
+            .ok_or(RegistryInitError::UnknownReference(qualified))
+    }
+}
+
 pub type RegistryResult<T> = Result<T, RegistryError>;
 
 #[derive(Debug, Error, Diagnostic)]
@@ -196,6 +617,284 @@ pub enum RegistryInitError {
         "[Init] Failed to find schema id for '{0}' (Something is wrong and should not happen)"
     )]
     IdNotFound(ModulePath),
+    #[error("[Init] '{0}' is referenced but isn't one of the processed schemas")]
+    UnknownReference(ModulePath),
+    #[error("[Init] '{0}' is defined by more than one schema root")]
+    DuplicateModulePath(ModulePath),
     #[error("[Init] {0}")]
     Schema(#[from] SchemaError),
+    #[error(
+        "[Init] Failed to find schema with id '{0}' while processing defaults (Something is wrong and should not happen)"
+    )]
+    IdNotFoundForDefaults(Uuid),
+    #[error("[Init] '{0}' has no field or variant named '{1}'")]
+    UnknownName(ModulePath, String),
+    #[error("[Init] Fields '{2}' and '{3}' of '{0}' are both named '{1}' after renaming")]
+    PropertyNameClash(ModulePath, String, String, String),
+    #[error("[Init] Field '{1}' of '{0}' clashes with the property keeping unknown keys in passthrough mode")]
+    PassthroughPropertyClash(ModulePath, String),
+    #[error("[Init] Enum '{0}' has no variants")]
+    NoVariants(ModulePath),
+    #[error("[Init] Variant '{1}' of '{0}' starts with a digit, which enum members can't")]
+    NumericVariant(ModulePath, String),
+    #[error("[Init] Alias '{1}' in '{0}' clashes with another name or alias")]
+    DuplicateAlias(ModulePath, String),
+    #[error("[Init] '{0}' only supports allow_undefined for Class schemas")]
+    AllowUndefinedOnEnum(ModulePath),
+    #[error("[Init] '{0}' is a {1} schema, but has {2} defaults")]
+    DefaultsKind(ModulePath, &'static str, &'static str),
+    #[error("[Init] Invalid default for '{1}' in '{0}': {2}")]
+    Default(ModulePath, String, DefaultValueError),
+}
+
+pub type DefaultValueResult<T> = Result<T, DefaultValueError>;
+
+#[derive(Debug, Error, Diagnostic)]
+pub enum DefaultValueError {
+    #[error("Expected {0}, found {1}")]
+    Mismatch(String, &'static str),
+    #[error("Map key '{0}' is not a number")]
+    NumKey(String),
+    #[error("'{1}' is not a variant of '{0}'")]
+    UnknownVariant(ModulePath, String),
+    #[error("'{1}' is not a field of '{0}'")]
+    UnknownField(ModulePath, String),
+    #[error("Field '{1}' of '{0}' is neither Opt nor allowed to be undefined and has to be set")]
+    MissingField(ModulePath, String),
+    #[error("Failed to find schema id for '{0}' (Something is wrong and should not happen)")]
+    IdNotFound(Uuid),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{registry, registry_with, rooted_registry};
+
+    #[test]
+    fn empty_enum() {
+        assert!(matches!(
+            registry(&[("kind", "(schema: Enum([]))")]),
+            Err(RegistryInitError::NoVariants(_))
+        ));
+        assert!(registry(&[("kind", r#"(schema: Enum(["a"]))"#)]).is_ok());
+    }
+
+    #[test]
+    fn variant_names() {
+        assert!(matches!(
+            registry(&[("kind", r#"(schema: Enum(["a", "1st"]))"#)]),
+            Err(RegistryInitError::NumericVariant(_, variant)) if variant == "1st"
+        ));
+        assert!(registry(&[("kind", r#"(schema: Enum(["in-progress", "a1"]))"#)]).is_ok());
+    }
+
+    #[test]
+    fn unknown_keys() {
+        let defaults = SchemaInfoDefaults {
+            unknown_keys: Some(UnknownKeys::Strict),
+            ..SchemaInfoDefaults::default()
+        };
+        let schemas = [
+            ("a", r#"(schema: Class({ "value": Num }))"#),
+            (
+                "b",
+                r#"(schema: Class({ "value": Num }), unknown_keys: Some("passthrough"))"#,
+            ),
+        ];
+        let policies = |registry: Registry| {
+            let mut policies = registry
+                .schemas()
+                .map(|schema_info| (schema_info.mod_path.to_string(), schema_info.unknown_keys))
+                .collect::<Vec<_>>();
+            policies.sort_by(|(a, _), (b, _)| a.cmp(b));
+            policies
+        };
+
+        assert_eq!(
+            policies(registry(&schemas).unwrap()),
+            [
+                ("a".into(), UnknownKeys::Strip),
+                ("b".into(), UnknownKeys::Passthrough)
+            ]
+        );
+        assert_eq!(
+            policies(registry_with(&schemas, &defaults).unwrap()),
+            [
+                ("a".into(), UnknownKeys::Strict),
+                ("b".into(), UnknownKeys::Passthrough)
+            ]
+        );
+        assert!(matches!(
+            registry(&[(
+                "a",
+                r#"(schema: Class({ "extra": Num }), unknown_keys: Some("passthrough"))"#
+            )]),
+            Err(RegistryInitError::PassthroughPropertyClash(_, field_name)) if field_name == "extra"
+        ));
+        assert!(ron::from_str::<UnknownKeys>(r#""lenient""#).is_err());
+    }
+
+    #[test]
+    fn cross_root_references() {
+        let registry = rooted_registry(&[
+            (
+                None,
+                "order",
+                r#"(schema: Class({ "total": DefClass("shared::common::money") }))"#,
+            ),
+            (
+                None,
+                "common::money",
+                r#"(schema: Class({ "amount": Str }))"#,
+            ),
+            (
+                Some("shared"),
+                "common::money",
+                r#"(schema: Class({ "currency": DefEnum("common::currency") }))"#,
+            ),
+            (
+                Some("shared"),
+                "common::currency",
+                r#"(schema: Enum(["eur"]))"#,
+            ),
+        ])
+        .unwrap();
+        let referenced = |root: Option<&str>, mod_path: &str| {
+            let (_, schema_info) = registry
+                .entries()
+                .find(|(_, schema_info)| {
+                    schema_info.root.as_deref() == root
+                        && schema_info.mod_path.to_string() == mod_path
+                })
+                .unwrap();
+            let [reference] = schema_info.schema.references()[..] else {
+                panic!("{mod_path} should have one reference");
+            };
+            registry
+                .get(&reference)
+                .unwrap()
+                .qualified_mod_path()
+                .to_string()
+        };
+
+        assert_eq!(referenced(None, "order"), "shared::common::money");
+        assert_eq!(
+            referenced(Some("shared"), "common::money"),
+            "shared::common::currency"
+        );
+
+        assert!(matches!(
+            rooted_registry(&[
+                (None, "order", r#"(schema: Class({ "total": DefClass("shared::money") }))"#),
+                (Some("shared"), "common::money", r#"(schema: Class({ "cents": Num }))"#),
+            ]),
+            Err(RegistryInitError::UnknownReference(reference)) if reference.to_string() == "shared::money"
+        ));
+        assert!(matches!(
+            rooted_registry(&[
+                (
+                    None,
+                    "shared::money",
+                    r#"(schema: Class({ "cents": Num }))"#
+                ),
+                (
+                    Some("shared"),
+                    "money",
+                    r#"(schema: Class({ "cents": Num }))"#
+                ),
+            ]),
+            Err(RegistryInitError::DuplicateModulePath(_))
+        ));
+    }
+
+    fn default_error(schemas: &[(&str, &str)]) -> DefaultValueError {
+        match registry(schemas) {
+            Err(RegistryInitError::Default(_, _, err)) => err,
+            res => panic!("expected an invalid default, got {res:?}"),
+        }
+    }
+
+    fn with_default(ty: &str, default: &str) -> String {
+        format!(
+            r#"(
+                schema: Class({{ "field": {ty} }}),
+                validation: Some((defaults: Some(Class({{ "field": {default} }})))),
+            )"#
+        )
+    }
+
+    #[test]
+    fn default_types() {
+        assert!(registry(&[("a", &with_default("Num", "1.5"))]).is_ok());
+        assert!(registry(&[("a", &with_default("Opt(Arr(Str))", r#"["x"]"#))]).is_ok());
+        assert!(matches!(
+            default_error(&[("a", &with_default("Num", r#""1""#))]),
+            DefaultValueError::Mismatch(expected, "Str") if expected == "Num"
+        ));
+        assert!(matches!(
+            default_error(&[("a", &with_default("Arr(Bool)", "[true, 1]"))]),
+            DefaultValueError::Mismatch(expected, "Num") if expected == "Bool"
+        ));
+        assert!(matches!(
+            registry(&[(
+                "kind",
+                r#"(schema: Enum(["a"]), validation: Some((defaults: Some(Class({})))))"#
+            )]),
+            Err(RegistryInitError::DefaultsKind(_, "Enum", "Class"))
+        ));
+    }
+
+    #[test]
+    fn default_references() {
+        let kind = ("kind", r#"(schema: Enum(["a", "b"]))"#);
+        let point = ("point", r#"(schema: Class({ "x": Num, "y": Opt(Num) }))"#);
+
+        assert!(registry(&[kind, ("a", &with_default(r#"DefEnum("kind")"#, r#""b""#))]).is_ok());
+        assert!(matches!(
+            default_error(&[kind, ("a", &with_default(r#"DefEnum("kind")"#, r#""c""#))]),
+            DefaultValueError::UnknownVariant(_, variant) if variant == "c"
+        ));
+        assert!(registry(&[
+            point,
+            ("a", &with_default(r#"DefClass("point")"#, r#"{ "x": 1 }"#))
+        ])
+        .is_ok());
+        assert!(matches!(
+            default_error(&[point, ("a", &with_default(r#"DefClass("point")"#, r#"{ "y": 1 }"#))]),
+            DefaultValueError::MissingField(_, field) if field == "x"
+        ));
+        assert!(matches!(
+            default_error(&[point, ("a", &with_default(r#"DefClass("point")"#, r#"{ "x": 1, "z": 1 }"#))]),
+            DefaultValueError::UnknownField(_, field) if field == "z"
+        ));
+    }
+
+    #[test]
+    fn default_map_keys() {
+        for key in [r#""1""#, r#""-2.5""#, r#"".5""#, r#""1e3""#, "4"] {
+            assert!(
+                registry(&[(
+                    "a",
+                    &with_default("Map(Num, Bool)", &format!("{{ {key}: true }}"))
+                )])
+                .is_ok(),
+                "{key}"
+            );
+        }
+
+        for key in [
+            r#""NaN""#, r#""inf""#, r#"" 1""#, r#""0x1""#, r#""""#, "NaN", "inf",
+        ] {
+            assert!(
+                matches!(
+                    default_error(&[(
+                        "a",
+                        &with_default("Map(Num, Bool)", &format!("{{ {key}: true }}"))
+                    )]),
+                    DefaultValueError::NumKey(_)
+                ),
+                "{key}"
+            );
+        }
+    }
 }
@@ -0,0 +1,75 @@
+use convert_case::{Case, Casing};
+use serde::{Deserialize, Serialize};
+
+/// Case conversion from the wire names of class fields to the generated property names
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum RenameRule {
+    Camel,
+    Pascal,
+    Snake,
+    ScreamingSnake,
+    Kebab,
+    Lower,
+    Upper,
+}
+
+impl RenameRule {
+    const ALL: [&'static str; 7] = [
+        "camelCase",
+        "PascalCase",
+        "snake_case",
+        "SCREAMING_SNAKE_CASE",
+        "kebab-case",
+        "lowercase",
+        "UPPERCASE",
+    ];
+
+    pub fn apply(&self, name: impl AsRef<str>) -> String {
+        name.as_ref().to_case(match self {
+            RenameRule::Camel => Case::Camel,
+            RenameRule::Pascal => Case::Pascal,
+            RenameRule::Snake => Case::Snake,
+            RenameRule::ScreamingSnake => Case::UpperSnake,
+            RenameRule::Kebab => Case::Kebab,
+            RenameRule::Lower => Case::Flat,
+            RenameRule::Upper => Case::UpperFlat,
+        })
+    }
+}
+
+impl From<RenameRule> for String {
+    fn from(value: RenameRule) -> Self {
+        RenameRule::ALL[value as usize].into()
+    }
+}
+
+impl TryFrom<String> for RenameRule {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Ok(match value.as_str() {
+            "camelCase" => Self::Camel,
+            "PascalCase" => Self::Pascal,
+            "snake_case" => Self::Snake,
+            "SCREAMING_SNAKE_CASE" => Self::ScreamingSnake,
+            "kebab-case" => Self::Kebab,
+            "lowercase" => Self::Lower,
+            "UPPERCASE" => Self::Upper,
+            rule => {
+                return Err(format!(
+                    "Unknown rename rule '{rule}', available ones: {:?}",
+                    Self::ALL
+                ))
+            }
+        })
+    }
+}
+
+/// Name of the generated property for a field, as opposed to its wire name
+pub fn property_name(rename_all: Option<&RenameRule>, field_name: impl AsRef<str>) -> String {
+    match rename_all {
+        Some(rename_all) => rename_all.apply(field_name),
+        None => field_name.as_ref().into(),
+    }
+}
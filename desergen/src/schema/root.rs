@@ -0,0 +1,65 @@
+use std::{collections::HashSet, path::PathBuf};
+
+use super::module_path::ModulePath;
+
+/// Directory of schema files. The unnamed root is `<desergen_root>/schemas`, named ones
+/// come from `Config::roots` and are referenced from other roots as `<name>::path::to::schema`
+#[derive(Debug, Clone)]
+pub struct SchemaRoot {
+    pub name: Option<String>,
+    pub dir: PathBuf,
+    pub schemas: Vec<ModulePath>,
+}
+
+/// Module path that is unique across all roots
+pub fn qualify(root: Option<&String>, mod_path: &ModulePath) -> ModulePath {
+    match root {
+        Some(root) => mod_path.prepend(root),
+        None => mod_path.clone(),
+    }
+}
+
+/// Resolves a reference made from a schema in `root`: references starting with the
+/// name of a root point into that root, anything else stays in the same root
+pub fn resolve(
+    root_names: &HashSet<String>,
+    root: Option<&String>,
+    reference: &ModulePath,
+) -> ModulePath {
+    match !reference.parents().is_empty() && root_names.contains(reference.first()) {
+        true => reference.clone(),
+        false => qualify(root, reference),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolved(root: Option<&str>, reference: &str) -> String {
+        let root_names = HashSet::from(["shared".to_string()]);
+
+        resolve(
+            &root_names,
+            root.map(String::from).as_ref(),
+            &reference.parse().unwrap(),
+        )
+        .to_string()
+    }
+
+    #[test]
+    fn resolves_references() {
+        assert_eq!(resolved(None, "order"), "order");
+        assert_eq!(
+            resolved(None, "shared::common::money"),
+            "shared::common::money"
+        );
+        assert_eq!(
+            resolved(Some("shared"), "common::money"),
+            "shared::common::money"
+        );
+        assert_eq!(resolved(Some("app"), "shared::money"), "shared::money");
+        // A lone `shared` is a schema of the same root rather than the root itself
+        assert_eq!(resolved(Some("app"), "shared"), "app::shared");
+    }
+}
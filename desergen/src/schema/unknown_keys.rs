@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+
+/// Name of the property keeping the unknown keys in [`UnknownKeys::Passthrough`] mode
+pub const PASSTHROUGH_PROPERTY: &str = "extra";
+
+/// What class deserializers do with input keys that are not part of the schema
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum UnknownKeys {
+    /// Reject the input with an error
+    Strict,
+    /// Silently drop the keys
+    #[default]
+    Strip,
+    /// Keep the keys in the `extra` record of the class
+    Passthrough,
+}
+
+impl UnknownKeys {
+    const ALL: [&'static str; 3] = ["strict", "strip", "passthrough"];
+}
+
+impl From<UnknownKeys> for String {
+    fn from(value: UnknownKeys) -> Self {
+        UnknownKeys::ALL[value as usize].into()
+    }
+}
+
+impl TryFrom<String> for UnknownKeys {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Ok(match value.as_str() {
+            "strict" => Self::Strict,
+            "strip" => Self::Strip,
+            "passthrough" => Self::Passthrough,
+            policy => {
+                return Err(format!(
+                    "Unknown unknown-key policy '{policy}', available ones: {:?}",
+                    Self::ALL
+                ))
+            }
+        })
+    }
+}
@@ -1,26 +1,103 @@
+pub mod raw;
+
 use std::collections::HashMap;
 
-use serde::Deserialize;
+use indexmap::IndexMap;
+use uuid::Uuid;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug)]
 pub struct SchemaValidationInfo {
-    #[serde(default)]
     pub allow_undefined: Vec<String>,
-    #[serde(default)]
     pub aliases: HashMap<String, Vec<String>>,
     pub defaults: Option<SchemaValidationDefaults>,
 }
 
-#[derive(Debug, Deserialize)]
+impl SchemaValidationInfo {
+    pub fn class(&self) -> ClassSchemaValidationInfo<'_> {
+        ClassSchemaValidationInfo {
+            allow_undefined: &self.allow_undefined,
+            aliases: &self.aliases,
+            defaults: self.defaults.as_ref().and_then(|defaults| match defaults {
+                SchemaValidationDefaults::Class(defaults) => Some(defaults),
+                _ => None,
+            }),
+        }
+    }
+
+    pub fn enum_(&self) -> EnumSchemaValidationInfo<'_> {
+        EnumSchemaValidationInfo {
+            aliases: &self.aliases,
+            default: self.defaults.as_ref().and_then(|defaults| match defaults {
+                SchemaValidationDefaults::Enum(default) => Some(default),
+                _ => None,
+            }),
+        }
+    }
+}
+
+#[derive(Debug)]
 pub enum SchemaValidationDefaults {
-    Class(HashMap<String, String>),
+    Class(IndexMap<String, DefaultValue>),
     Enum(String),
 }
 
+/// Default value that has been checked against the member type it belongs to
+#[derive(Debug, Clone)]
+pub enum DefaultValue {
+    Num(f64),
+    Str(String),
+    Bool(bool),
+    Arr(Vec<DefaultValue>),
+    Map(Vec<(DefaultValue, DefaultValue)>),
+    DefClass(Uuid, IndexMap<String, DefaultValue>),
+    DefEnum(Uuid, String),
+}
+
+impl DefaultValue {
+    /// The value as it appears on the wire
+    pub fn to_json(&self) -> serde_json::Value {
+        match self {
+            DefaultValue::Num(num) => num_json(*num),
+            DefaultValue::Str(str) => str.as_str().into(),
+            DefaultValue::Bool(bool) => (*bool).into(),
+            DefaultValue::Arr(items) => items.iter().map(DefaultValue::to_json).collect(),
+            DefaultValue::Map(entries) => serde_json::Value::Object(
+                entries
+                    .iter()
+                    .map(|(key, val)| {
+                        let key = match key {
+                            DefaultValue::Num(num) => num.to_string(),
+                            DefaultValue::Str(str) | DefaultValue::DefEnum(_, str) => str.clone(),
+                            key => unreachable!("{key:?} can't be a map key"),
+                        };
+
+                        (key, val.to_json())
+                    })
+                    .collect(),
+            ),
+            DefaultValue::DefClass(_, fields) => serde_json::Value::Object(
+                fields
+                    .iter()
+                    .map(|(field_name, field)| (field_name.clone(), field.to_json()))
+                    .collect(),
+            ),
+            DefaultValue::DefEnum(_, variant) => variant.as_str().into(),
+        }
+    }
+}
+
+/// Whole numbers are written without a fraction, as they would be by hand
+fn num_json(num: f64) -> serde_json::Value {
+    match num.fract() == 0.0 && num.abs() < 1e15 {
+        true => (num as i64).into(),
+        false => num.into(),
+    }
+}
+
 pub struct ClassSchemaValidationInfo<'a> {
     pub allow_undefined: &'a Vec<String>,
     pub aliases: &'a HashMap<String, Vec<String>>,
-    pub defaults: &'a Option<HashMap<String, String>>,
+    pub defaults: Option<&'a IndexMap<String, DefaultValue>>,
 }
 
 pub struct EnumSchemaValidationInfo<'a> {
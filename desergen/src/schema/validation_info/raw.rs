@@ -0,0 +1,178 @@
+use std::collections::HashMap;
+
+use indexmap::IndexMap;
+use serde::{
+    de::{MapAccess, SeqAccess, Visitor},
+    ser::{SerializeMap, SerializeSeq},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct RawSchemaValidationInfo {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allow_undefined: Vec<String>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub aliases: HashMap<String, Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub defaults: Option<RawSchemaValidationDefaults>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub enum RawSchemaValidationDefaults {
+    Class(IndexMap<String, RawDefaultValue>),
+    Enum(String),
+}
+
+/// Untyped default value as written in the schema file, checked against the
+/// member type during [`Registry`](crate::schema::registry::Registry) processing
+#[derive(Debug, Clone, PartialEq)]
+pub enum RawDefaultValue {
+    Num(f64),
+    Str(String),
+    Bool(bool),
+    Arr(Vec<RawDefaultValue>),
+    Map(Vec<(RawDefaultValue, RawDefaultValue)>),
+}
+
+impl RawDefaultValue {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            RawDefaultValue::Num(_) => "Num",
+            RawDefaultValue::Str(_) => "Str",
+            RawDefaultValue::Bool(_) => "Bool",
+            RawDefaultValue::Arr(_) => "Arr",
+            RawDefaultValue::Map(_) => "Map",
+        }
+    }
+}
+
+pub struct RawDefaultValueVisitor;
+
+impl<'de> Visitor<'de> for RawDefaultValueVisitor {
+    type Value = RawDefaultValue;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            formatter,
+            "A number, string, boolean, sequence or map default value"
+        )
+    }
+
+    fn visit_bool<E>(self, v: bool) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(RawDefaultValue::Bool(v))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(RawDefaultValue::Num(v as f64))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(RawDefaultValue::Num(v as f64))
+    }
+
+    fn visit_f64<E>(self, v: f64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(RawDefaultValue::Num(v))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(RawDefaultValue::Str(v.into()))
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(RawDefaultValue::Str(v))
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(self)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut items = Vec::with_capacity(seq.size_hint().unwrap_or_default());
+
+        while let Some(item) = seq.next_element()? {
+            items.push(item);
+        }
+
+        Ok(RawDefaultValue::Arr(items))
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut entries = Vec::with_capacity(map.size_hint().unwrap_or_default());
+
+        while let Some(entry) = map.next_entry()? {
+            entries.push(entry);
+        }
+
+        Ok(RawDefaultValue::Map(entries))
+    }
+}
+
+impl<'de> Deserialize<'de> for RawDefaultValue {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(RawDefaultValueVisitor)
+    }
+}
+
+impl Serialize for RawDefaultValue {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            RawDefaultValue::Num(num) if num.fract() == 0.0 && num.abs() < 1e15 => {
+                serializer.serialize_i64(*num as i64)
+            }
+            RawDefaultValue::Num(num) => serializer.serialize_f64(*num),
+            RawDefaultValue::Str(str) => serializer.serialize_str(str),
+            RawDefaultValue::Bool(bool) => serializer.serialize_bool(*bool),
+            RawDefaultValue::Arr(items) => {
+                let mut seq = serializer.serialize_seq(Some(items.len()))?;
+
+                for item in items {
+                    seq.serialize_element(item)?;
+                }
+
+                seq.end()
+            }
+            RawDefaultValue::Map(entries) => {
+                let mut map = serializer.serialize_map(Some(entries.len()))?;
+
+                for (key, val) in entries {
+                    map.serialize_entry(key, val)?;
+                }
+
+                map.end()
+            }
+        }
+    }
+}
@@ -0,0 +1,87 @@
+//! Helpers shared by the unit tests
+
+use std::path::PathBuf;
+
+use tempfile::TempDir;
+
+use crate::{
+    generator::{backend::Backend, generate, GeneratorResult, OutputRoots},
+    schema::{
+        info::SchemaInfoDefaults,
+        registry::{RawSchemaEntry, Registry, RegistryInitResult},
+    },
+};
+
+/// Registry of schemas written in RON, by module path
+pub fn registry(schemas: &[(&str, &str)]) -> RegistryInitResult<Registry> {
+    registry_with(schemas, &SchemaInfoDefaults::default())
+}
+
+/// [`registry`] with project-wide settings
+pub fn registry_with(
+    schemas: &[(&str, &str)],
+    defaults: &SchemaInfoDefaults,
+) -> RegistryInitResult<Registry> {
+    process(
+        schemas
+            .iter()
+            .map(|(mod_path, schema)| (None, *mod_path, *schema)),
+        defaults,
+    )
+}
+
+/// Registry of schemas written in RON, by root and module path within it
+pub fn rooted_registry(schemas: &[(Option<&str>, &str, &str)]) -> RegistryInitResult<Registry> {
+    process(schemas.iter().copied(), &SchemaInfoDefaults::default())
+}
+
+fn process<'a>(
+    schemas: impl Iterator<Item = (Option<&'a str>, &'a str, &'a str)>,
+    defaults: &SchemaInfoDefaults,
+) -> RegistryInitResult<Registry> {
+    let entries = schemas
+        .map(|(root, mod_path, schema)| RawSchemaEntry {
+            root: root.map(String::from),
+            mod_path: mod_path.parse().unwrap(),
+            schema_info: ron::from_str(schema).unwrap(),
+            path: PathBuf::from(format!(
+                "{}schemas/{mod_path}.ron",
+                root.map(|root| format!("{root}/")).unwrap_or_default()
+            )),
+            last_updated: 0,
+        })
+        .collect();
+
+    let mut registry = Registry::default();
+    registry.process_raw_schemas(entries, defaults)?;
+
+    Ok(registry)
+}
+
+/// What `backend` generates for the schema at `mod_path`
+pub fn generated(
+    backend: &dyn Backend,
+    schemas: &[(&str, &str)],
+    mod_path: &str,
+) -> GeneratorResult<String> {
+    let registry = registry(schemas).unwrap();
+    let outputs = OutputRoots::from_iter([(None, PathBuf::from("out"))]);
+
+    Ok(generate(backend, &registry, &outputs, None)?
+        .into_iter()
+        .find(|file| {
+            file.source
+                .as_ref()
+                .is_some_and(|source| source.mod_path.to_string() == mod_path)
+        })
+        .map(|file| file.contents)
+        .unwrap())
+}
+
+/// Directory of the test's own, removed along with its contents once dropped
+pub fn temp_dir() -> TempDir {
+    tempfile::Builder::new()
+        .prefix("desergen-")
+        .tempdir()
+        .unwrap()
+}
@@ -0,0 +1,244 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::mpsc::{self, RecvTimeoutError},
+    time::Duration,
+};
+
+use miette::Diagnostic;
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher as _};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::{
+    cache::{Cache, Inputs},
+    config::Config,
+    generate_files,
+    generator::header::add_headers,
+    output_roots, registry_from_raw,
+    schema::{graph::with_referrers, registry::Registry},
+    schema_roots, write_output,
+};
+
+/// How long changes are collected before regenerating, as editors tend to save in several steps
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Regenerates whenever the config file or a schema changes, until interrupted. Failing
+/// builds are reported and the last successful one is kept to tell what changed
+pub fn watch(config_path: &Path, config_root_dir: &Path, force: bool) -> WatchResult<()> {
+    let (tx, rx) = mpsc::channel();
+    let mut notifier = notify::recommended_watcher(tx)?;
+
+    // The directory rather than the file, which editors replace when saving
+    let config_root_dir = match config_root_dir.as_os_str().is_empty() {
+        true => Path::new("."),
+        false => config_root_dir,
+    };
+    let config_root_dir = config_root_dir
+        .canonicalize()
+        .map_err(|err| WatchError::IO(err, config_root_dir.into()))?;
+    let config_path = config_root_dir.join(config_path.file_name().unwrap_or_default());
+    notifier.watch(&config_root_dir, RecursiveMode::NonRecursive)?;
+
+    let mut watcher = Watcher {
+        config_path,
+        config_root_dir,
+        force,
+        notifier,
+        schema_dirs: vec![],
+        previous: None,
+    };
+    let mut full = true;
+
+    loop {
+        match watcher.rebuild(full) {
+            Ok(()) => full = false,
+            Err(err) => eprintln!("{err:?}"),
+        }
+
+        tracing::info!("Watching for changes...");
+
+        // Waits for a change to something that's part of the build, then for things to settle down
+        let mut changed = false;
+
+        loop {
+            let event = match changed {
+                true => match rx.recv_timeout(DEBOUNCE) {
+                    Ok(event) => event,
+                    Err(RecvTimeoutError::Timeout) => break,
+                    Err(RecvTimeoutError::Disconnected) => return Ok(()),
+                },
+                false => match rx.recv() {
+                    Ok(event) => event,
+                    Err(_) => return Ok(()),
+                },
+            };
+
+            let event = match event {
+                Ok(event) => event,
+                Err(err) => {
+                    tracing::error!("[Watch] {err}");
+                    continue;
+                }
+            };
+
+            // Reading the files while rebuilding shouldn't trigger another rebuild
+            if matches!(event.kind, EventKind::Access(_)) {
+                continue;
+            }
+
+            let config_changed = event.paths.contains(&watcher.config_path);
+            let schemas_changed = event
+                .paths
+                .iter()
+                .any(|path| watcher.schema_dirs.iter().any(|dir| path.starts_with(dir)));
+
+            full |= config_changed;
+            changed |= config_changed || schemas_changed;
+        }
+    }
+}
+
+struct Watcher {
+    config_path: PathBuf,
+    config_root_dir: PathBuf,
+    force: bool,
+    notifier: RecommendedWatcher,
+    /// Schema directories of every root, watched recursively
+    schema_dirs: Vec<PathBuf>,
+    /// Registry of the last successful build
+    previous: Option<Registry>,
+}
+
+impl Watcher {
+    /// Reloads the config and the schemas, regenerating everything when `full`
+    /// and otherwise only the changed schemas and the ones referencing them.
+    /// The cache is kept up to date so a later `generate` skips what was regenerated here
+    fn rebuild(&mut self, full: bool) -> miette::Result<()> {
+        let config = Config::from_path(&self.config_path)?;
+        let roots = config.roots(&self.config_root_dir)?;
+        let desergen_root = self.config_root_dir.join(&config.desergen_root);
+
+        self.watch_schema_dirs(
+            roots
+                .iter()
+                .filter_map(|root| root.schemas_dir.canonicalize().ok())
+                .collect(),
+        )?;
+
+        let cache = Cache::load(&desergen_root);
+        let inputs = Inputs::read(
+            &self.config_path,
+            config.templates_dir(&self.config_root_dir).as_deref(),
+            &schema_roots(&roots)?,
+            &cache,
+        )?;
+        let registry = registry_from_raw(inputs.raw_schemas(), &config.schema_defaults)?;
+        let (only, removed) = match (&self.previous, full) {
+            (Some(previous), false) => (
+                Some(with_referrers(&registry, changed(previous, &registry))),
+                removed(previous, &registry),
+            ),
+            _ => (None, false),
+        };
+
+        // Removed schemas still take regenerating the support files and deleting their outputs
+        match &only {
+            Some(only) if only.is_empty() && !removed => tracing::info!("No schema changed"),
+            _ => {
+                let outputs = output_roots(&roots);
+                let mut files = generate_files(
+                    &config,
+                    &self.config_root_dir,
+                    &registry,
+                    &outputs,
+                    only.as_ref(),
+                )?;
+                add_headers(&mut files);
+
+                write_output(&files, &registry, only.as_ref(), &desergen_root, self.force)?;
+                cache
+                    .update(&inputs, &registry, &files)
+                    .save(&desergen_root)?;
+            }
+        }
+
+        self.previous = Some(registry);
+
+        Ok(())
+    }
+
+    fn watch_schema_dirs(&mut self, schema_dirs: Vec<PathBuf>) -> WatchResult<()> {
+        for dir in self
+            .schema_dirs
+            .iter()
+            .filter(|dir| !schema_dirs.contains(dir))
+        {
+            self.notifier.unwatch(dir)?;
+        }
+
+        for dir in schema_dirs
+            .iter()
+            .filter(|dir| !self.schema_dirs.contains(dir))
+        {
+            tracing::debug!("Watching {dir:?}");
+            self.notifier.watch(dir, RecursiveMode::Recursive)?;
+        }
+
+        self.schema_dirs = schema_dirs;
+
+        Ok(())
+    }
+}
+
+/// Schemas whose file was modified since the previous build, or that are new
+fn changed(previous: &Registry, registry: &Registry) -> HashSet<Uuid> {
+    let last_updated = previous
+        .schemas()
+        .map(|schema_info| {
+            (
+                schema_info.qualified_mod_path(),
+                (&schema_info.path, schema_info.last_updated),
+            )
+        })
+        .collect::<HashMap<_, _>>();
+
+    registry
+        .entries()
+        .filter(|(_, schema_info)| {
+            last_updated.get(&schema_info.qualified_mod_path())
+                != Some(&(&schema_info.path, schema_info.last_updated))
+        })
+        .map(|(id, schema_info)| {
+            tracing::info!("{} changed", schema_info.qualified_mod_path());
+            *id
+        })
+        .collect()
+}
+
+/// Whether a schema of the previous build is gone, logging the ones that are
+fn removed(previous: &Registry, registry: &Registry) -> bool {
+    let current = registry
+        .schemas()
+        .map(|schema_info| schema_info.qualified_mod_path())
+        .collect::<HashSet<_>>();
+
+    previous
+        .schemas()
+        .map(|schema_info| schema_info.qualified_mod_path())
+        .filter(|qualified_mod_path| !current.contains(qualified_mod_path))
+        .inspect(|qualified_mod_path| tracing::info!("{qualified_mod_path} removed"))
+        .count()
+        > 0
+}
+
+pub type WatchResult<T> = Result<T, WatchError>;
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Error, Diagnostic)]
+pub enum WatchError {
+    #[error("[Watch] {0}")]
+    Notify(#[from] notify::Error),
+    #[error("[Watch] [IO ({1:?})] {0}")]
+    IO(std::io::Error, PathBuf),
+}